@@ -1,19 +1,67 @@
 use eyre::eyre;
-use http::HeaderMap;
+use http::{HeaderMap, HeaderValue, Method};
+use http::header::CONTENT_TYPE;
 use reqwest::Client;
 
 use crate::api::endpoint::Endpoint;
 
+/// Authenticates requests to the private endpoints
+pub trait RequestSigner {
+    /// Headers carrying the signature, `request_path` includes the query string
+    /// and `body` is empty for the requests without a body
+    fn sign(&self, method: &Method, request_path: &str, body: &str) -> eyre::Result<HeaderMap>;
+}
+
 pub async fn http_urlencoded_query_request<E: Endpoint>(
     base_url: &str,
     body: &E::Request,
     headers: HeaderMap,
 ) -> eyre::Result<E::Response> {
+    let request_path = urlencoded_path::<E>(body)?;
+    send::<E>(base_url, &request_path, String::new(), headers).await
+}
+
+/// Sends the request of `GET` and `DELETE` endpoints as a query string and of the other ones as a JSON body,
+/// adding the headers of the signer to `headers`
+pub async fn http_signed_request<E: Endpoint>(
+    base_url: &str,
+    body: &E::Request,
+    signer: &impl RequestSigner,
+    mut headers: HeaderMap,
+) -> eyre::Result<E::Response> {
+    let (request_path, body) = if E::METHOD == Method::GET || E::METHOD == Method::DELETE {
+        (urlencoded_path::<E>(body)?, String::new())
+    } else {
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        (E::PATH.to_string(), serde_json::to_string(body)?)
+    };
+    headers.extend(signer.sign(&E::METHOD, &request_path, &body)?);
+    send::<E>(base_url, &request_path, body, headers).await
+}
+
+fn urlencoded_path<E: Endpoint>(body: &E::Request) -> eyre::Result<String> {
     let data = serde_urlencoded::to_string(body)?;
-    let url = format!("{}{}?{}", base_url, E::PATH, data);
-    let response = Client::new()
+    if data.is_empty() {
+        Ok(E::PATH.to_string())
+    } else {
+        Ok(format!("{}?{}", E::PATH, data))
+    }
+}
+
+async fn send<E: Endpoint>(
+    base_url: &str,
+    request_path: &str,
+    body: String,
+    headers: HeaderMap,
+) -> eyre::Result<E::Response> {
+    let url = format!("{}{}", base_url, request_path);
+    let mut request = Client::new()
         .request(E::METHOD, &url)
-        .headers(headers)
+        .headers(headers);
+    if !body.is_empty() {
+        request = request.body(body);
+    }
+    let response = request
         .send()
         .await?;
 
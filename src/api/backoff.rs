@@ -0,0 +1,129 @@
+use std::time::Duration;
+
+use rand::Rng;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct BackoffConfig {
+    /// Delay before the first retry
+    pub initial_delay_ms: u64,
+    /// Upper bound of the delay between retries
+    pub max_delay_ms: u64,
+    /// Factor the delay is multiplied by after every failed attempt
+    pub multiplier: f64,
+    /// Share of the delay which is randomly added or subtracted, from 0.0 to 1.0
+    pub jitter: f64,
+    /// Give up after this amount of consecutive failed attempts, `None` means retry forever
+    pub max_attempts: Option<u32>,
+    /// Pause before reconnecting after the exchange responded with HTTP 429
+    pub too_many_requests_cooldown_ms: u64,
+}
+
+impl Default for BackoffConfig {
+    fn default() -> Self {
+        Self {
+            initial_delay_ms: 1_000,
+            max_delay_ms: 60_000,
+            multiplier: 2.0,
+            jitter: 0.2,
+            max_attempts: None,
+            too_many_requests_cooldown_ms: 60_000,
+        }
+    }
+}
+
+/// Exponential backoff state for a sequence of retries
+#[derive(Debug, Clone)]
+pub struct Backoff {
+    config: BackoffConfig,
+    attempt: u32,
+}
+
+impl Backoff {
+    pub fn new(config: BackoffConfig) -> Self {
+        Self { config, attempt: 0 }
+    }
+
+    pub fn attempt(&self) -> u32 {
+        self.attempt
+    }
+
+    pub fn config(&self) -> &BackoffConfig {
+        &self.config
+    }
+
+    /// Delay before the next attempt, `None` once the attempts are exhausted
+    pub fn next_delay(&mut self) -> Option<Duration> {
+        if let Some(max_attempts) = self.config.max_attempts {
+            if self.attempt >= max_attempts {
+                return None;
+            }
+        }
+        let delay = self.base_delay_ms();
+        self.attempt += 1;
+
+        let jitter = self.config.jitter.clamp(0.0, 1.0);
+        let factor = if jitter > 0.0 {
+            rand::thread_rng().gen_range(1.0 - jitter..=1.0 + jitter)
+        } else {
+            1.0
+        };
+        Some(Duration::from_millis((delay * factor) as u64))
+    }
+
+    pub fn reset(&mut self) {
+        self.attempt = 0;
+    }
+
+    fn base_delay_ms(&self) -> f64 {
+        let delay = self.config.initial_delay_ms as f64 * self.config.multiplier.powi(self.attempt as i32);
+        delay.min(self.config.max_delay_ms as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::api::backoff::{Backoff, BackoffConfig};
+
+    #[test]
+    fn delay_grows_exponentially_up_to_max() {
+        let mut backoff = Backoff::new(BackoffConfig {
+            initial_delay_ms: 100,
+            max_delay_ms: 500,
+            multiplier: 2.0,
+            jitter: 0.0,
+            max_attempts: None,
+            too_many_requests_cooldown_ms: 0,
+        });
+        let delays: Vec<_> = (0..5).map(|_| backoff.next_delay().unwrap()).collect();
+        assert_eq!(delays, [100, 200, 400, 500, 500].map(Duration::from_millis));
+
+        backoff.reset();
+        assert_eq!(backoff.next_delay(), Some(Duration::from_millis(100)));
+    }
+
+    #[test]
+    fn gives_up_after_max_attempts() {
+        let mut backoff = Backoff::new(BackoffConfig {
+            max_attempts: Some(2),
+            ..Default::default()
+        });
+        assert!(backoff.next_delay().is_some());
+        assert!(backoff.next_delay().is_some());
+        assert!(backoff.next_delay().is_none());
+        assert_eq!(backoff.attempt(), 2);
+    }
+
+    #[test]
+    fn jitter_stays_within_bounds() {
+        let mut backoff = Backoff::new(BackoffConfig {
+            initial_delay_ms: 1_000,
+            jitter: 0.5,
+            ..Default::default()
+        });
+        let delay = backoff.next_delay().unwrap();
+        assert!(delay >= Duration::from_millis(500) && delay <= Duration::from_millis(1_500));
+    }
+}
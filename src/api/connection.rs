@@ -1,5 +1,6 @@
 use async_trait::async_trait;
 
+use crate::model::account::AccountEvent;
 use crate::model::internal::MdMessage;
 
 #[async_trait]
@@ -7,6 +8,30 @@ pub trait MdConnection: Send {
     async fn next(&mut self) -> eyre::Result<MdMessage>;
 }
 
+/// Private connection pushing balances, positions and orders of the account
+#[async_trait]
+pub trait AccountConnection: Send {
+    async fn next(&mut self) -> eyre::Result<AccountEvent>;
+}
+
 pub trait WsMessage {
+    /// Plain text frame the exchange answers a text ping with, e.g. `pong`, if it isn't JSON
+    const PONG_TEXT: Option<&'static str> = None;
+
     fn pong() -> Self;
+
+    /// `Some` if the message is the response to the login request
+    fn login_result(&self) -> Option<eyre::Result<()>> {
+        None
+    }
+
+    /// Id of the request the message responds to, for the exchanges correlating responses by id, e.g. JSON-RPC
+    fn response_id(&self) -> Option<u64> {
+        None
+    }
+
+    /// `Some` if the message is an error response, checked for the subscribe requests by `WebSocket::subscribe`
+    fn response_error(&self) -> Option<eyre::Report> {
+        None
+    }
 }
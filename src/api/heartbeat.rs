@@ -0,0 +1,114 @@
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+/// Application-level keepalive of a websocket connection:
+/// ping after `idle_timeout` without messages and reconnect if nothing arrives within `pong_timeout` after that.
+/// Exchanges which expect the pings regardless of the traffic use `Heartbeat::fixed_interval`.
+#[derive(Debug)]
+pub struct Heartbeat {
+    idle_timeout: Duration,
+    pong_timeout: Duration,
+    /// The messages don't postpone the next ping
+    fixed_interval: bool,
+    /// Time of the last message, or of the last ping for the fixed interval
+    last_message_at: Instant,
+    /// Set while waiting for the response to the sent ping
+    pong_deadline: Option<Instant>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum HeartbeatAction {
+    Ping,
+    Reconnect,
+}
+
+impl Heartbeat {
+    pub fn new(idle_timeout: Duration, pong_timeout: Duration) -> Self {
+        Self {
+            idle_timeout,
+            pong_timeout,
+            fixed_interval: false,
+            last_message_at: Instant::now(),
+            pong_deadline: None,
+        }
+    }
+
+    /// Pings every `interval` even if the messages keep arriving
+    pub fn fixed_interval(interval: Duration, pong_timeout: Duration) -> Self {
+        Self { fixed_interval: true, ..Self::new(interval, pong_timeout) }
+    }
+
+    pub fn pong_timeout(&self) -> Duration {
+        self.pong_timeout
+    }
+
+    /// Time to call `on_deadline` unless a message arrives before
+    pub fn deadline(&self) -> Instant {
+        self.pong_deadline.unwrap_or(self.last_message_at + self.idle_timeout)
+    }
+
+    /// Any message proves the connection is alive, a pong included
+    pub fn on_message(&mut self) {
+        if !self.fixed_interval {
+            self.last_message_at = Instant::now();
+        }
+        self.pong_deadline = None;
+    }
+
+    /// Starts over after a reconnect
+    pub fn reset(&mut self) {
+        self.last_message_at = Instant::now();
+        self.pong_deadline = None;
+    }
+
+    pub fn on_deadline(&mut self) -> HeartbeatAction {
+        if self.pong_deadline.take().is_some() {
+            self.last_message_at = Instant::now();
+            HeartbeatAction::Reconnect
+        } else {
+            if self.fixed_interval {
+                self.last_message_at = Instant::now();
+            }
+            self.pong_deadline = Some(Instant::now() + self.pong_timeout);
+            HeartbeatAction::Ping
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::api::heartbeat::{Heartbeat, HeartbeatAction};
+
+    #[test]
+    fn ping_then_reconnect() {
+        let mut heartbeat = Heartbeat::new(Duration::from_secs(20), Duration::from_secs(5));
+        let idle_deadline = heartbeat.deadline();
+
+        assert_eq!(heartbeat.on_deadline(), HeartbeatAction::Ping);
+        assert!(heartbeat.deadline() < idle_deadline);
+        heartbeat.on_message();
+        assert!(heartbeat.deadline() >= idle_deadline);
+
+        assert_eq!(heartbeat.on_deadline(), HeartbeatAction::Ping);
+        assert_eq!(heartbeat.on_deadline(), HeartbeatAction::Reconnect);
+        assert_eq!(heartbeat.on_deadline(), HeartbeatAction::Ping);
+    }
+
+    #[test]
+    fn fixed_interval_ignores_messages() {
+        let mut heartbeat = Heartbeat::fixed_interval(Duration::from_secs(20), Duration::from_secs(5));
+        let ping_deadline = heartbeat.deadline();
+        heartbeat.on_message();
+        assert_eq!(heartbeat.deadline(), ping_deadline);
+
+        assert_eq!(heartbeat.on_deadline(), HeartbeatAction::Ping);
+        let pong_deadline = heartbeat.deadline();
+        assert!(pong_deadline < ping_deadline + Duration::from_secs(20));
+        heartbeat.on_message();
+        assert!(heartbeat.deadline() > pong_deadline);
+        assert_eq!(heartbeat.on_deadline(), HeartbeatAction::Ping);
+    }
+}
@@ -1,5 +1,10 @@
 pub mod endpoint;
+#[allow(clippy::module_inception)]
 pub mod api;
+pub mod backoff;
 pub mod connection;
+pub mod heartbeat;
 pub mod poller;
+pub mod risk;
+pub mod trader;
 pub mod ws;
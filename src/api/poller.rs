@@ -1,9 +1,8 @@
 use async_trait::async_trait;
-use compact_str::CompactString;
-
 use crate::model::order_book::OrderBook;
+use crate::model::symbol::Symbol;
 
 #[async_trait]
 pub trait ExchangePoller {
-    async fn get_order_book(&self, symbol: CompactString) -> eyre::Result<OrderBook>;
+    async fn get_order_book(&self, symbol: &Symbol) -> eyre::Result<OrderBook>;
 }
\ No newline at end of file
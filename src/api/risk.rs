@@ -0,0 +1,647 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use derive_more::Display;
+use fixnum::ops::{Bounded, CheckedAdd, CheckedMul, CheckedSub, One, RoundMode, RoundingDiv, RoundingMul, Zero};
+use fixnum::ArithmeticError;
+use serde::Deserialize;
+
+use crate::api::trader::ExchangeTrader;
+use crate::model::account::PositionSide;
+use crate::model::account_store::AccountStore;
+use crate::model::exchange::Exchange;
+use crate::model::instrument::Instrument;
+use crate::model::internal::Side;
+use crate::model::order::{AmendRequest, CancelRequest, OrderAck, OrderRequest};
+use crate::model::order_tracker::OrderTracker;
+use crate::model::storage::Storage;
+use crate::model::symbol::Symbol;
+use crate::utils::basic_types::{Amount, Price};
+
+/// Pre-trade limits of `RiskTrader`, the unset ones are not checked
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct RiskLimits {
+    /// In quote currency, market orders are valued at the mid price
+    pub max_order_notional: Option<Amount>,
+    /// Absolute position per instrument including the order, in the units of the order amount
+    pub max_position: Option<Amount>,
+    /// Open orders of the exchange in `OrderTracker`
+    pub max_open_orders: Option<usize>,
+    /// Allowed deviation of the limit price from the mid price, e.g. 0.05 for 5%
+    pub price_band: Option<Price>,
+    /// Maximal order amount in lots of the instrument
+    pub max_order_lots: Option<u32>,
+    /// Orders and amends per instrument
+    pub rate_limit: Option<RateLimit>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct RateLimit {
+    pub max_requests: usize,
+    pub interval_ms: u64,
+}
+
+/// Reason of an order or amend rejected by `RiskTrader` before reaching the exchange.
+/// Returned by `ExchangeTrader` methods of `RiskTrader` as the error, available by `eyre::Report::downcast_ref`.
+#[derive(Debug, Display, PartialEq, Eq, Clone)]
+pub enum RiskRejection {
+    #[display("trading is stopped by the kill switch")]
+    KillSwitch,
+    #[display("{symbol} is not a known instrument")]
+    UnknownInstrument { symbol: Symbol },
+    #[display("no mid price of {symbol}")]
+    NoReferencePrice { symbol: Symbol },
+    #[display("price {price} is outside of the band around mid {mid}")]
+    PriceBand { price: Price, mid: Price },
+    #[display("amount {amount} is not a multiple of lot size {lot_size}")]
+    LotSize { amount: Amount, lot_size: Amount },
+    #[display("amount {amount} is less than min size {min_size}")]
+    MinSize { amount: Amount, min_size: Amount },
+    #[display("amount {amount} exceeds max order amount {limit}")]
+    OrderAmount { amount: Amount, limit: Amount },
+    #[display("notional {notional} exceeds max order notional {limit}")]
+    OrderNotional { notional: Amount, limit: Amount },
+    #[display("position {position} would exceed max position {limit}")]
+    Position { position: Amount, limit: Amount },
+    #[display("{count} open orders reached max open orders {limit}")]
+    OpenOrders { count: usize, limit: usize },
+    #[display("rate limit of {symbol} is reached")]
+    RateLimited { symbol: Symbol },
+    #[display("arithmetic overflow checking {symbol}")]
+    Overflow { symbol: Symbol },
+}
+
+impl std::error::Error for RiskRejection {}
+
+/// `ExchangeTrader` checking the orders and amends against `RiskLimits` before passing them to the inner one.
+///
+/// Reads the mid prices, positions and open orders of the exchange from the shared state,
+/// which is kept up to date by the caller. The cancels are never blocked.
+pub struct RiskTrader<T> {
+    inner: T,
+    exchange: Exchange,
+    limits: RiskLimits,
+    instruments: HashMap<Symbol, Instrument>,
+    storage: Arc<RwLock<Storage>>,
+    account: Arc<RwLock<AccountStore>>,
+    orders: Arc<RwLock<OrderTracker>>,
+    requests: Mutex<HashMap<Symbol, VecDeque<Instant>>>,
+    killed: AtomicBool,
+}
+
+impl<T: ExchangeTrader + Sync> RiskTrader<T> {
+    pub fn new(
+        inner: T,
+        exchange: Exchange,
+        limits: RiskLimits,
+        storage: Arc<RwLock<Storage>>,
+        account: Arc<RwLock<AccountStore>>,
+        orders: Arc<RwLock<OrderTracker>>,
+    ) -> Self {
+        Self {
+            inner,
+            exchange,
+            limits,
+            instruments: HashMap::new(),
+            storage,
+            account,
+            orders,
+            requests: Mutex::new(HashMap::new()),
+            killed: AtomicBool::new(false),
+        }
+    }
+
+    /// Instruments by their internal symbols, the orders of the other symbols are rejected
+    pub fn with_instruments(mut self, instruments: impl IntoIterator<Item = (Symbol, Instrument)>) -> Self {
+        self.instruments.extend(instruments);
+        self
+    }
+
+    /// Blocks the new orders and amends, then cancels all the open orders
+    pub async fn kill(&self) -> eyre::Result<Vec<eyre::Result<OrderAck>>> {
+        self.killed.store(true, Ordering::SeqCst);
+        self.inner.cancel_all(None).await
+    }
+
+    pub fn resume(&self) {
+        self.killed.store(false, Ordering::SeqCst);
+    }
+
+    pub fn is_killed(&self) -> bool {
+        self.killed.load(Ordering::SeqCst)
+    }
+
+    pub fn check_order(&self, order: &OrderRequest) -> Result<(), RiskRejection> {
+        self.check_batch_order(order, &mut BatchExposure::default())
+    }
+
+    /// Checks the order of a batch, counting the orders and amends of the batch accepted before it
+    fn check_batch_order(&self, order: &OrderRequest, batch: &mut BatchExposure) -> Result<(), RiskRejection> {
+        let symbol = &order.symbol;
+        let instrument = self.check_common(symbol)?;
+        self.check_amount(instrument, order.amount)?;
+        let mid = self.storage.read().unwrap().mid_price(self.exchange, symbol);
+        if let Some(price) = order.price {
+            self.check_price(symbol, price, mid)?;
+        }
+
+        if let Some(limit) = self.limits.max_order_notional {
+            let price = order.price.or(mid).ok_or_else(|| RiskRejection::NoReferencePrice { symbol: symbol.clone() })?;
+            let contract_value = instrument.contract_value.unwrap_or(Amount::ONE);
+            let notional = order.amount.rmul(contract_value, RoundMode::Nearest)
+                .and_then(|size| size.rmul(price, RoundMode::Nearest))
+                .map_err(|_| RiskRejection::Overflow { symbol: symbol.clone() })?;
+            if notional > limit {
+                return Err(RiskRejection::OrderNotional { notional, limit });
+            }
+        }
+
+        let mut position = None;
+        if let (Some(limit), false) = (self.limits.max_position, order.reduce_only) {
+            position = Some(self.check_position(symbol, order.side, order.amount, limit, batch)?);
+        }
+
+        if let Some(limit) = self.limits.max_open_orders {
+            let open = self.orders.read().unwrap().open_orders().filter(|o| o.exchange == self.exchange).count();
+            let count = open + batch.orders;
+            if count >= limit {
+                return Err(RiskRejection::OpenOrders { count, limit });
+            }
+        }
+
+        self.check_rate(symbol)?;
+        batch.orders += 1;
+        if let Some(position) = position {
+            batch.positions.insert(symbol.clone(), position);
+        }
+        Ok(())
+    }
+
+    /// The new price and amount are checked, and the position limit if the amended order is known
+    /// to `OrderTracker`, counting the remaining amount of the order. The notional limit is not checked.
+    pub fn check_amend(&self, amend: &AmendRequest) -> Result<(), RiskRejection> {
+        self.check_batch_amend(amend, &mut BatchExposure::default())
+    }
+
+    fn check_batch_amend(&self, amend: &AmendRequest, batch: &mut BatchExposure) -> Result<(), RiskRejection> {
+        let symbol = &amend.symbol;
+        let instrument = self.check_common(symbol)?;
+        if let Some(amount) = amend.new_amount {
+            self.check_amount(instrument, amount)?;
+        }
+        if let Some(price) = amend.new_price {
+            let mid = self.storage.read().unwrap().mid_price(self.exchange, symbol);
+            self.check_price(symbol, price, mid)?;
+        }
+
+        let mut position = None;
+        if let (Some(limit), Some(amount)) = (self.limits.max_position, amend.new_amount) {
+            let order = self.orders.read().unwrap().get(&amend.order).map(|o| (o.side, o.filled_amount));
+            if let Some((side, filled_amount)) = order {
+                let remaining = amount.saturating_sub(filled_amount).max(Amount::ZERO);
+                position = Some(self.check_position(symbol, side, remaining, limit, batch)?);
+            }
+        }
+
+        self.check_rate(symbol)?;
+        if let Some(position) = position {
+            batch.positions.insert(symbol.clone(), position);
+        }
+        Ok(())
+    }
+
+    /// Returns the projected position to be counted by the next requests of the batch
+    fn check_position(
+        &self,
+        symbol: &Symbol,
+        side: Side,
+        amount: Amount,
+        limit: Amount,
+        batch: &BatchExposure,
+    ) -> Result<Amount, RiskRejection> {
+        let position = self.projected_position(symbol, side, amount, batch)
+            .map_err(|_| RiskRejection::Overflow { symbol: symbol.clone() })?;
+        if position.abs > limit && position.abs > position.current_abs {
+            return Err(RiskRejection::Position { position: position.signed, limit });
+        }
+        Ok(position.signed)
+    }
+
+    fn check_common(&self, symbol: &Symbol) -> Result<&Instrument, RiskRejection> {
+        if self.is_killed() {
+            return Err(RiskRejection::KillSwitch);
+        }
+        self.instruments.get(symbol).ok_or_else(|| RiskRejection::UnknownInstrument { symbol: symbol.clone() })
+    }
+
+    /// Fat finger check of the amount against the lot size of the instrument
+    fn check_amount(&self, instrument: &Instrument, amount: Amount) -> Result<(), RiskRejection> {
+        let lot_size = instrument.lot_size;
+        if amount.round_towards_zero_by(lot_size) != amount {
+            return Err(RiskRejection::LotSize { amount, lot_size });
+        }
+        if amount < instrument.min_size {
+            return Err(RiskRejection::MinSize { amount, min_size: instrument.min_size });
+        }
+        if let Some(max_lots) = self.limits.max_order_lots {
+            let limit = lot_size.cmul(max_lots as i128).unwrap_or(Amount::MAX);
+            if amount > limit {
+                return Err(RiskRejection::OrderAmount { amount, limit });
+            }
+        }
+        Ok(())
+    }
+
+    fn check_price(&self, symbol: &Symbol, price: Price, mid: Option<Price>) -> Result<(), RiskRejection> {
+        let Some(band) = self.limits.price_band else {
+            return Ok(());
+        };
+        let mid = mid.ok_or_else(|| RiskRejection::NoReferencePrice { symbol: symbol.clone() })?;
+        let deviation = price.csub(mid)
+            .and_then(|diff| diff.abs())
+            .and_then(|diff| diff.rdiv(mid, RoundMode::Nearest))
+            .map_err(|_| RiskRejection::Overflow { symbol: symbol.clone() })?;
+        if deviation > band {
+            return Err(RiskRejection::PriceBand { price, mid });
+        }
+        Ok(())
+    }
+
+    /// Sliding window of the accepted requests of the symbol
+    fn check_rate(&self, symbol: &Symbol) -> Result<(), RiskRejection> {
+        let Some(limit) = &self.limits.rate_limit else {
+            return Ok(());
+        };
+        let now = Instant::now();
+        let interval = Duration::from_millis(limit.interval_ms);
+        let mut requests = self.requests.lock().unwrap();
+        let times = requests.entry(symbol.clone()).or_default();
+        while times.front().is_some_and(|t| now.duration_since(*t) >= interval) {
+            times.pop_front();
+        }
+        if times.len() >= limit.max_requests {
+            return Err(RiskRejection::RateLimited { symbol: symbol.clone() });
+        }
+        times.push_back(now);
+        Ok(())
+    }
+
+    /// Net position of the symbol after the order and the accepted requests of the batch are filled completely
+    fn projected_position(
+        &self,
+        symbol: &Symbol,
+        side: Side,
+        amount: Amount,
+        batch: &BatchExposure,
+    ) -> Result<ProjectedPosition, ArithmeticError> {
+        let current = match batch.positions.get(symbol) {
+            Some(position) => *position,
+            None => {
+                let account = self.account.read().unwrap();
+                let amount = |side| account.position(self.exchange, symbol, side).map_or(Amount::ZERO, |p| p.amount);
+                amount(PositionSide::Net)
+                    .cadd(amount(PositionSide::Long))?
+                    .csub(amount(PositionSide::Short))?
+            }
+        };
+        let signed = match side {
+            Side::Bid => current.cadd(amount)?,
+            Side::Ask => current.csub(amount)?,
+        };
+        Ok(ProjectedPosition { signed, abs: signed.abs()?, current_abs: current.abs()? })
+    }
+}
+
+/// Requests of a batch accepted so far, which the checks of the next ones count in addition to the shared state
+#[derive(Default)]
+struct BatchExposure {
+    /// Projected positions by symbol
+    positions: HashMap<Symbol, Amount>,
+    /// Accepted new orders
+    orders: usize,
+}
+
+struct ProjectedPosition {
+    signed: Amount,
+    abs: Amount,
+    current_abs: Amount,
+}
+
+#[async_trait]
+impl<T: ExchangeTrader + Sync> ExchangeTrader for RiskTrader<T> {
+    async fn place_order(&self, order: &OrderRequest) -> eyre::Result<OrderAck> {
+        self.check_order(order)?;
+        self.inner.place_order(order).await
+    }
+
+    async fn place_orders(&self, orders: &[OrderRequest]) -> eyre::Result<Vec<eyre::Result<OrderAck>>> {
+        let mut batch = BatchExposure::default();
+        let checks: Vec<_> = orders.iter().map(|o| self.check_batch_order(o, &mut batch)).collect();
+        let passed: Vec<_> = orders.iter().zip(&checks).filter(|(_, c)| c.is_ok()).map(|(o, _)| o.clone()).collect();
+        let acks = if passed.is_empty() { Vec::new() } else { self.inner.place_orders(&passed).await? };
+        Ok(merge(checks, acks))
+    }
+
+    async fn cancel_order(&self, cancel: &CancelRequest) -> eyre::Result<OrderAck> {
+        self.inner.cancel_order(cancel).await
+    }
+
+    async fn cancel_orders(&self, cancels: &[CancelRequest]) -> eyre::Result<Vec<eyre::Result<OrderAck>>> {
+        self.inner.cancel_orders(cancels).await
+    }
+
+    async fn amend_order(&self, amend: &AmendRequest) -> eyre::Result<OrderAck> {
+        self.check_amend(amend)?;
+        self.inner.amend_order(amend).await
+    }
+
+    async fn amend_orders(&self, amends: &[AmendRequest]) -> eyre::Result<Vec<eyre::Result<OrderAck>>> {
+        let mut batch = BatchExposure::default();
+        let checks: Vec<_> = amends.iter().map(|a| self.check_batch_amend(a, &mut batch)).collect();
+        let passed: Vec<_> = amends.iter().zip(&checks).filter(|(_, c)| c.is_ok()).map(|(a, _)| a.clone()).collect();
+        let acks = if passed.is_empty() { Vec::new() } else { self.inner.amend_orders(&passed).await? };
+        Ok(merge(checks, acks))
+    }
+
+    async fn cancel_all(&self, symbol: Option<&Symbol>) -> eyre::Result<Vec<eyre::Result<OrderAck>>> {
+        self.inner.cancel_all(symbol).await
+    }
+}
+
+/// Puts the results of the passed requests in place of the successful checks
+fn merge(checks: Vec<Result<(), RiskRejection>>, acks: Vec<eyre::Result<OrderAck>>) -> Vec<eyre::Result<OrderAck>> {
+    let mut acks = acks.into_iter();
+    checks
+        .into_iter()
+        .map(|check| match check {
+            Ok(()) => acks.next().unwrap_or_else(|| Err(eyre::eyre!("no result for the request"))),
+            Err(rejection) => Err(rejection.into()),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, RwLock};
+
+    use async_trait::async_trait;
+
+    use crate::api::risk::{RateLimit, RiskLimits, RiskRejection, RiskTrader};
+    use crate::api::trader::ExchangeTrader;
+    use crate::model::account::{AccountEvent, Position, PositionSide};
+    use crate::model::account_store::AccountStore;
+    use crate::model::exchange::Exchange;
+    use crate::model::instrument::{Instrument, InstrumentKind, InstrumentState};
+    use crate::model::internal::{L2Snapshot, MdInstrument, MdMessage, Side, SingleLot};
+    use crate::model::order::{AmendRequest, CancelRequest, OrderAck, OrderRef, OrderRequest, TimeInForce};
+    use crate::model::order_tracker::OrderTracker;
+    use crate::model::storage::Storage;
+    use crate::model::symbol::Symbol;
+    use crate::utils::basic_types::{Amount, Price};
+
+    #[derive(Default)]
+    struct MockTrader {
+        placed: AtomicUsize,
+        cancel_all: AtomicUsize,
+    }
+
+    fn ack() -> eyre::Result<OrderAck> {
+        Ok(OrderAck { order_id: "1".into(), client_order_id: None })
+    }
+
+    #[async_trait]
+    impl ExchangeTrader for MockTrader {
+        async fn place_order(&self, _: &OrderRequest) -> eyre::Result<OrderAck> {
+            self.placed.fetch_add(1, Ordering::SeqCst);
+            ack()
+        }
+
+        async fn place_orders(&self, orders: &[OrderRequest]) -> eyre::Result<Vec<eyre::Result<OrderAck>>> {
+            self.placed.fetch_add(orders.len(), Ordering::SeqCst);
+            Ok(orders.iter().map(|_| ack()).collect())
+        }
+
+        async fn cancel_order(&self, _: &CancelRequest) -> eyre::Result<OrderAck> {
+            ack()
+        }
+
+        async fn cancel_orders(&self, cancels: &[CancelRequest]) -> eyre::Result<Vec<eyre::Result<OrderAck>>> {
+            Ok(cancels.iter().map(|_| ack()).collect())
+        }
+
+        async fn amend_order(&self, _: &AmendRequest) -> eyre::Result<OrderAck> {
+            ack()
+        }
+
+        async fn amend_orders(&self, amends: &[AmendRequest]) -> eyre::Result<Vec<eyre::Result<OrderAck>>> {
+            Ok(amends.iter().map(|_| ack()).collect())
+        }
+
+        async fn cancel_all(&self, _: Option<&Symbol>) -> eyre::Result<Vec<eyre::Result<OrderAck>>> {
+            self.cancel_all.fetch_add(1, Ordering::SeqCst);
+            Ok(Vec::new())
+        }
+    }
+
+    fn amount(s: &str) -> Amount {
+        Amount::from_str(s).unwrap()
+    }
+
+    fn price(s: &str) -> Price {
+        Price::from_str(s).unwrap()
+    }
+
+    fn symbol() -> Symbol {
+        Symbol::perpetual("BTC", "USDT")
+    }
+
+    fn instrument() -> Instrument {
+        Instrument {
+            exchange: Exchange::Okex,
+            symbol: "BTC-USDT-SWAP".into(),
+            internal_symbol: symbol(),
+            kind: InstrumentKind::Perpetual,
+            base_currency: "BTC".into(),
+            quote_currency: "USDT".into(),
+            settle_currency: Some("USDT".into()),
+            tick_size: price("0.1"),
+            lot_size: amount("0.1"),
+            min_size: amount("0.1"),
+            contract_value: Some(amount("0.01")),
+            expiry: None,
+            state: InstrumentState::Live,
+        }
+    }
+
+    fn trader(limits: RiskLimits, position: &str) -> RiskTrader<MockTrader> {
+        let mut storage = Storage::new();
+        storage.on_ws_update(MdMessage::L2Snapshot(L2Snapshot {
+            exchange_time: None,
+            sequence_no: None,
+            exchange: Exchange::Okex,
+            instrument: MdInstrument::Symbol(symbol()),
+            bids: vec![SingleLot { price: price("29990"), amount: amount("1") }],
+            asks: vec![SingleLot { price: price("30010"), amount: amount("1") }],
+        }));
+        let mut account = AccountStore::new();
+        account.on_event(&AccountEvent::Position(Position {
+            exchange_time: None,
+            exchange: Exchange::Okex,
+            symbol: symbol(),
+            instrument_id: None,
+            side: PositionSide::Net,
+            amount: amount(position),
+            avg_price: None,
+            unrealized_pnl: None,
+        }));
+        RiskTrader::new(
+            MockTrader::default(),
+            Exchange::Okex,
+            limits,
+            Arc::new(RwLock::new(storage)),
+            Arc::new(RwLock::new(account)),
+            Arc::new(RwLock::new(OrderTracker::new(Exchange::Okex, "t"))),
+        )
+        .with_instruments([(symbol(), instrument())])
+    }
+
+    fn buy(price_str: &str, amount_str: &str) -> OrderRequest {
+        OrderRequest::limit(symbol(), Side::Bid, price(price_str), amount(amount_str), TimeInForce::Gtc)
+    }
+
+    #[test]
+    fn order_limits() {
+        let limits = RiskLimits {
+            max_order_notional: Some(amount("3000")),
+            max_position: Some(amount("10")),
+            price_band: Some(price("0.01")),
+            max_order_lots: Some(50),
+            ..Default::default()
+        };
+        let trader = trader(limits, "8");
+
+        assert_eq!(trader.check_order(&buy("30000", "1")), Ok(()));
+        assert_eq!(
+            trader.check_order(&buy("31000", "1")),
+            Err(RiskRejection::PriceBand { price: price("31000"), mid: price("30000") })
+        );
+        assert_eq!(
+            trader.check_order(&buy("30000", "1.05")),
+            Err(RiskRejection::LotSize { amount: amount("1.05"), lot_size: amount("0.1") })
+        );
+        assert_eq!(
+            trader.check_order(&buy("30000", "6")),
+            Err(RiskRejection::OrderAmount { amount: amount("6"), limit: amount("5") })
+        );
+        assert_eq!(
+            trader.check_order(&buy("30000", "3")),
+            Err(RiskRejection::Position { position: amount("11"), limit: amount("10") })
+        );
+        let sell = OrderRequest::market(symbol(), Side::Ask, amount("5"));
+        assert_eq!(trader.check_order(&sell), Ok(()));
+        assert_eq!(
+            trader.check_order(&OrderRequest::market(symbol(), Side::Ask, amount("5")).with_reduce_only()),
+            Ok(())
+        );
+        let unknown = OrderRequest::market(Symbol::perpetual("ETH", "USDT"), Side::Bid, amount("1"));
+        assert_eq!(
+            trader.check_order(&unknown),
+            Err(RiskRejection::UnknownInstrument { symbol: Symbol::perpetual("ETH", "USDT") })
+        );
+    }
+
+    #[tokio::test]
+    async fn rate_limit_and_batches() {
+        let limits = RiskLimits {
+            rate_limit: Some(RateLimit { max_requests: 2, interval_ms: 60_000 }),
+            ..Default::default()
+        };
+        let trader = trader(limits, "0");
+
+        let orders = vec![buy("30000", "1"), buy("30000", "0.01"), buy("30000", "1"), buy("30000", "1")];
+        let results = trader.place_orders(&orders).await.unwrap();
+        assert_eq!(rejections(&results), vec![
+            None,
+            Some(RiskRejection::LotSize { amount: amount("0.01"), lot_size: amount("0.1") }),
+            None,
+            Some(RiskRejection::RateLimited { symbol: symbol() }),
+        ]);
+        assert_eq!(trader.inner.placed.load(Ordering::SeqCst), 2);
+    }
+
+    fn rejections(results: &[eyre::Result<OrderAck>]) -> Vec<Option<RiskRejection>> {
+        results
+            .iter()
+            .map(|r| r.as_ref().err().and_then(|e| e.downcast_ref::<RiskRejection>()).cloned())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn batch_counts_accepted_orders() {
+        let orders = vec![buy("30000", "2"), buy("30000", "2"), buy("30000", "2")];
+
+        let open_limited = trader(RiskLimits { max_open_orders: Some(2), ..Default::default() }, "0");
+        let results = open_limited.place_orders(&orders).await.unwrap();
+        assert_eq!(rejections(&results), vec![None, None, Some(RiskRejection::OpenOrders { count: 2, limit: 2 })]);
+        assert_eq!(open_limited.inner.placed.load(Ordering::SeqCst), 2);
+
+        let trader = trader(RiskLimits { max_position: Some(amount("5")), ..Default::default() }, "0");
+        let results = trader.place_orders(&orders).await.unwrap();
+        assert_eq!(
+            rejections(&results),
+            vec![None, None, Some(RiskRejection::Position { position: amount("6"), limit: amount("5") })]
+        );
+        assert_eq!(trader.inner.placed.load(Ordering::SeqCst), 2);
+
+        // an order flattening the position is accepted and lets the next one through
+        let sell = OrderRequest::limit(symbol(), Side::Ask, price("30000"), amount("4"), TimeInForce::Gtc);
+        let results = trader.place_orders(&[buy("30000", "4"), buy("30000", "2"), sell, buy("30000", "2")]).await.unwrap();
+        assert_eq!(
+            rejections(&results),
+            vec![None, Some(RiskRejection::Position { position: amount("6"), limit: amount("5") }), None, None]
+        );
+    }
+
+    #[tokio::test]
+    async fn batch_counts_accepted_amends() {
+        let trader = trader(RiskLimits { max_position: Some(amount("5")), ..Default::default() }, "0");
+        let amend = |order: &OrderRequest, new_amount: &str| AmendRequest {
+            symbol: symbol(),
+            order: OrderRef::ClientOrderId(order.client_order_id.clone().unwrap()),
+            new_price: None,
+            new_amount: Some(amount(new_amount)),
+        };
+        let (first, second) = {
+            let mut tracker = trader.orders.write().unwrap();
+            (tracker.on_new(buy("30000", "1")), tracker.on_new(buy("30000", "1")))
+        };
+
+        assert_eq!(trader.check_amend(&amend(&first, "3")), Ok(()));
+        let results = trader.amend_orders(&[amend(&first, "3"), amend(&second, "3")]).await.unwrap();
+        assert_eq!(
+            rejections(&results),
+            vec![None, Some(RiskRejection::Position { position: amount("6"), limit: amount("5") })]
+        );
+    }
+
+    #[tokio::test]
+    async fn kill_switch() {
+        let trader = trader(RiskLimits::default(), "0");
+        trader.kill().await.unwrap();
+        assert_eq!(trader.inner.cancel_all.load(Ordering::SeqCst), 1);
+
+        let error = trader.place_order(&buy("30000", "1")).await.unwrap_err();
+        assert_eq!(error.downcast_ref::<RiskRejection>(), Some(&RiskRejection::KillSwitch));
+        let cancel = CancelRequest { symbol: symbol(), order: OrderRef::OrderId("1".into()) };
+        assert!(trader.cancel_order(&cancel).await.is_ok());
+
+        trader.resume();
+        assert!(trader.place_order(&buy("30000", "1")).await.is_ok());
+        assert_eq!(trader.inner.placed.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,25 @@
+use async_trait::async_trait;
+
+use crate::model::order::{AmendRequest, CancelRequest, OrderAck, OrderRequest};
+use crate::model::symbol::Symbol;
+
+/// Order entry. The batch methods report the result of every item in the order of the requests.
+/// When a batch is split into several requests, e.g. because of the exchange limit,
+/// the items of a failed request fail with its error and the results of the others are kept.
+#[async_trait]
+pub trait ExchangeTrader {
+    async fn place_order(&self, order: &OrderRequest) -> eyre::Result<OrderAck>;
+
+    async fn place_orders(&self, orders: &[OrderRequest]) -> eyre::Result<Vec<eyre::Result<OrderAck>>>;
+
+    async fn cancel_order(&self, cancel: &CancelRequest) -> eyre::Result<OrderAck>;
+
+    async fn cancel_orders(&self, cancels: &[CancelRequest]) -> eyre::Result<Vec<eyre::Result<OrderAck>>>;
+
+    async fn amend_order(&self, amend: &AmendRequest) -> eyre::Result<OrderAck>;
+
+    async fn amend_orders(&self, amends: &[AmendRequest]) -> eyre::Result<Vec<eyre::Result<OrderAck>>>;
+
+    /// Cancels all the open orders, of the symbol only if set
+    async fn cancel_all(&self, symbol: Option<&Symbol>) -> eyre::Result<Vec<eyre::Result<OrderAck>>>;
+}
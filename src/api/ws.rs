@@ -1,23 +1,31 @@
+use std::collections::VecDeque;
 use std::fmt::Debug;
 use std::marker::PhantomData;
 use std::time::Duration;
 
 use compact_str::CompactString;
-use eyre::bail;
+use derive_more::Display;
+use eyre::{bail, eyre};
 use futures_util::sink::SinkExt;
 use futures_util::StreamExt;
 use http::StatusCode;
 use log::{error, trace, warn};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use tokio::net::TcpStream;
 use tokio_tungstenite::{connect_async, MaybeTlsStream};
 use tokio_tungstenite::tungstenite::{Error, Message};
 use tokio_tungstenite::tungstenite::error::ProtocolError;
 use tokio_tungstenite::tungstenite::handshake::client::Response;
 
+use crate::api::backoff::{Backoff, BackoffConfig};
 use crate::api::connection::WsMessage;
+use crate::api::heartbeat::{Heartbeat, HeartbeatAction};
 use crate::model::stream::WsStream;
 
+const LOGIN_TIMEOUT: Duration = Duration::from_secs(10);
+/// Time to wait for the responses to the correlated subscribe requests
+const SUBSCRIBE_TIMEOUT: Duration = Duration::from_secs(10);
+
 pub type WebSocketStream = tokio_tungstenite::WebSocketStream<MaybeTlsStream<TcpStream>>;
 
 pub struct WebSocket<S, M>
@@ -28,9 +36,44 @@ pub struct WebSocket<S, M>
     stream: S,
     pub ws_url: CompactString,
     subscribe_interval_ms: u64,
+    backoff: Backoff,
+    /// Set when the exchange responded with HTTP 429, the next reconnect waits for the cooldown
+    rate_limited: bool,
+    /// Messages received while waiting for a response, returned by `next` first
+    buffered: VecDeque<M>,
+    /// Id of the last request correlated with its response
+    last_request_id: u64,
     _phantom_m: PhantomData<M>,
 }
 
+/// Returned by `WebSocket::reconnect` once the attempts of `BackoffConfig::max_attempts` are exhausted.
+/// The connection is unusable afterwards, every following reconnect fails immediately.
+#[derive(Debug, Display)]
+#[display("Failed to reconnect to {url} after {attempts} attempts")]
+pub struct ReconnectFailed {
+    pub url: CompactString,
+    pub attempts: u32,
+}
+
+impl std::error::Error for ReconnectFailed {}
+
+/// Result of `WebSocket::next_event`
+#[derive(Debug)]
+pub enum WsEvent<M> {
+    Message(M),
+    /// The heartbeat asks for the exchange specific ping to be sent
+    Ping,
+    /// The connection was reestablished and resubscribed, the local state built from the old one is stale
+    Reconnected,
+}
+
+/// Frame of the websocket decoded into the exchange message
+enum Frame<M> {
+    Message(M),
+    Close,
+    Skip,
+}
+
 impl<S, M> WebSocket<S, M>
     where
         S: WsStream + Send + 'static + Clone,
@@ -38,46 +81,210 @@ impl<S, M> WebSocket<S, M>
         M: for<'de> Deserialize<'de> + WsMessage,
 {
 
-    pub async fn try_establish_connection(url: &CompactString, stream: S, subscribe_interval_ms: u64) -> eyre::Result<Self> {
-        let (ws_stream, _response) = Self::connect(url).await;
+    pub async fn try_establish_connection(
+        url: &CompactString,
+        stream: S,
+        subscribe_interval_ms: u64,
+        backoff: BackoffConfig,
+    ) -> eyre::Result<Self> {
+        let mut backoff = Backoff::new(backoff);
+        let ws_stream = loop {
+            match Self::connect(url).await {
+                Ok((ws_stream, _response)) => break ws_stream,
+                Err(err) => {
+                    let Some(mut delay) = backoff.next_delay() else {
+                        bail!("WebSocket connection to {url} failed after {} attempts: {err}", backoff.attempt());
+                    };
+                    if is_too_many_requests(&err) {
+                        delay = delay.max(Duration::from_millis(backoff.config().too_many_requests_cooldown_ms));
+                    }
+                    warn!("WebSocket connection to {url} failed: {err}; retrying in {delay:?}");
+                    tokio::time::sleep(delay).await;
+                }
+            }
+        };
+        backoff.reset();
+
         Ok(Self {
             ws_stream,
             stream,
             ws_url: url.clone(),
             subscribe_interval_ms,
+            backoff,
+            rate_limited: false,
+            buffered: VecDeque::new(),
+            last_request_id: 0,
             _phantom_m: Default::default(),
         })
     }
 
-    async fn connect(url: &CompactString) -> (WebSocketStream, Response) {
-        let (stream, response) = connect_async(url.as_str())
-            .await
-            .expect("WebSocket connection failed");
+    /// Connects and subscribes. A failed subscription is retried by reconnecting with the backoff of `reconnect`,
+    /// so that the startup survives the same transient failures as the running connection.
+    pub async fn connect_and_subscribe(
+        url: &CompactString,
+        stream: S,
+        subscribe_interval_ms: u64,
+        backoff: BackoffConfig,
+    ) -> eyre::Result<Self> {
+        let mut ws = Self::try_establish_connection(url, stream, subscribe_interval_ms, backoff).await?;
+        if let Err(err) = ws.subscribe().await {
+            warn!("Subscription to {url} failed: {err}; reconnecting");
+            ws.reconnect().await?;
+        }
+        Ok(ws)
+    }
+
+    pub fn stream(&self) -> &S {
+        &self.stream
+    }
+
+    /// Changes made to the stream are used for subscription on the next reconnect
+    pub fn stream_mut(&mut self) -> &mut S {
+        &mut self.stream
+    }
+
+    async fn connect(url: &CompactString) -> Result<(WebSocketStream, Response), Error> {
+        let (stream, response) = connect_async(url.as_str()).await?;
         trace!("WebSocket connection established, response = {response:?}");
-        (stream, response)
+        Ok((stream, response))
     }
-    
-    pub async fn reconnect(&mut self) {
-        tokio::time::sleep(Duration::from_secs(1)).await;
+
+    /// Reconnects and resubscribes, retrying with exponential backoff.
+    /// Fails only when the attempts configured in `BackoffConfig::max_attempts` are exhausted.
+    pub async fn reconnect(&mut self) -> eyre::Result<()> {
         if let Err(err) = self.ws_stream.close(None).await {
             warn!("websocket stream close failure while reconnecting, {err}");
         }
-        trace!("Trying to reconnect to {}", self.ws_url);
-        let (ws_stream, _response) = Self::connect(&self.ws_url).await;
-        trace!("Reconnected to {}", self.ws_url);
-        self.ws_stream = ws_stream;
-        self.subscribe().await.expect("subscribe failure while reconnecting");
-        trace!("Resubscribed to url {}", &self.ws_url);
+        loop {
+            let Some(mut delay) = self.backoff.next_delay() else {
+                return Err(ReconnectFailed { url: self.ws_url.clone(), attempts: self.backoff.attempt() }.into());
+            };
+            if std::mem::take(&mut self.rate_limited) {
+                delay = delay.max(Duration::from_millis(self.backoff.config().too_many_requests_cooldown_ms));
+            }
+            tokio::time::sleep(delay).await;
+
+            trace!("Trying to reconnect to {}", self.ws_url);
+            match Self::connect(&self.ws_url).await {
+                Ok((ws_stream, _response)) => {
+                    trace!("Reconnected to {}", self.ws_url);
+                    self.ws_stream = ws_stream;
+                }
+                Err(err) => {
+                    self.rate_limited = is_too_many_requests(&err);
+                    warn!("Reconnect attempt {} to {} failed: {err}", self.backoff.attempt(), self.ws_url);
+                    continue;
+                }
+            }
+            match self.subscribe().await {
+                Ok(()) => {
+                    trace!("Resubscribed to url {}", &self.ws_url);
+                    self.backoff.reset();
+                    return Ok(());
+                }
+                Err(err) => {
+                    warn!("Resubscribe attempt {} to {} failed: {err}", self.backoff.attempt(), self.ws_url);
+                }
+            }
+        }
+    }
+
+    /// Sends the login requests of the stream and waits for their acknowledgement
+    async fn login(&mut self) -> eyre::Result<()> {
+        let requests = self.stream.login_requests();
+        if requests.is_empty() {
+            return Ok(());
+        }
+        for request in &requests {
+            self.send(request).await?;
+        }
+
+        let mut pending = requests.len();
+        tokio::time::timeout(LOGIN_TIMEOUT, async {
+            while pending > 0 {
+                let message = match self.recv().await? {
+                    Message::Text(s) => serde_json::from_str::<M>(&s)?,
+                    Message::Binary(data) => serde_json::from_slice::<M>(&data)?,
+                    Message::Close(_) => bail!("connection closed while logging in to {}", self.ws_url),
+                    _ => continue,
+                };
+                if let Some(result) = message.login_result() {
+                    result?;
+                    pending -= 1;
+                }
+            }
+            trace!("Logged in to {}", self.ws_url);
+            Ok(())
+        })
+            .await
+            .map_err(|_| eyre!("login to {} timed out", self.ws_url))?
     }
 
+    /// Logs in if the stream requires it and sends the subscribe requests.
+    /// Waits for the responses to the requests correlated by id, see `WsStream::set_request_id`.
     pub async fn subscribe(&mut self) -> eyre::Result<()> {
-        for subscribe_request in self.stream.subscribe_requests() {
-            let string = serde_json::to_string(&subscribe_request)?;
-            self.ws_stream.send(Message::text(string)).await?;
+        self.login().await?;
+        let mut correlated = Vec::new();
+        for mut subscribe_request in self.stream.subscribe_requests() {
+            let id = self.last_request_id + 1;
+            if S::set_request_id(&mut subscribe_request, id) {
+                self.last_request_id = id;
+                correlated.push(id);
+            }
+            self.send(&subscribe_request).await?;
             if self.subscribe_interval_ms != 0 {
                 tokio::time::sleep(Duration::from_millis(self.subscribe_interval_ms)).await;
             }
         }
+        for response in self.wait_responses(&correlated, SUBSCRIBE_TIMEOUT).await? {
+            if let Some(err) = response.response_error() {
+                bail!("subscription to {} failed: {err}", self.ws_url);
+            }
+        }
+        Ok(())
+    }
+
+    /// Id for the next request correlated with its response, unique within the connection
+    pub fn next_request_id(&mut self) -> u64 {
+        self.last_request_id += 1;
+        self.last_request_id
+    }
+
+    /// Sends the request with the id from `next_request_id` and waits for the response with the same id.
+    /// The other messages received meanwhile are returned by `next` afterwards.
+    pub async fn call<R: Serialize>(&mut self, id: u64, request: &R, timeout: Duration) -> eyre::Result<M> {
+        self.send(request).await?;
+        let mut responses = self.wait_responses(&[id], timeout).await?;
+        Ok(responses.pop().expect("should be one response per id"))
+    }
+
+    /// Responses in the order of arrival, fails if not all of them arrive within the timeout
+    async fn wait_responses(&mut self, ids: &[u64], timeout: Duration) -> eyre::Result<Vec<M>> {
+        let mut responses = Vec::with_capacity(ids.len());
+        if ids.is_empty() {
+            return Ok(responses);
+        }
+        tokio::time::timeout(timeout, async {
+            while responses.len() < ids.len() {
+                let message = match Self::decode(self.recv().await?)? {
+                    Frame::Message(message) => message,
+                    Frame::Close => bail!("connection to {} closed while waiting for responses", self.ws_url),
+                    Frame::Skip => continue,
+                };
+                match message.response_id() {
+                    Some(id) if ids.contains(&id) => responses.push(message),
+                    _ => self.buffered.push_back(message),
+                }
+            }
+            Ok(responses)
+        })
+            .await
+            .map_err(|_| eyre!("responses to requests {ids:?} from {} timed out", self.ws_url))?
+    }
+
+    pub async fn send<R: Serialize>(&mut self, request: &R) -> eyre::Result<()> {
+        let string = serde_json::to_string(request)?;
+        self.ws_stream.send(Message::text(string)).await?;
         Ok(())
     }
 
@@ -95,11 +302,16 @@ impl<S, M> WebSocket<S, M>
             Err(err) => match err {
                 Error::Protocol(ProtocolError::ResetWithoutClosingHandshake) => {
                     warn!("{}; reconnecting to {}", err, self.ws_url);
-                    return Ok(Message::Close(None))
+                    Ok(Message::Close(None))
                 }
                 Error::Http(response) if response.status() == StatusCode::TOO_MANY_REQUESTS => {
-                    error!("HTTP status: TOO_MANY_REQUESTS => 1min sleep before reconnecting to {}",  self.ws_url);
-                    return Ok(Message::Close(None))
+                    error!(
+                        "HTTP status: TOO_MANY_REQUESTS => {}ms sleep before reconnecting to {}",
+                        self.backoff.config().too_many_requests_cooldown_ms,
+                        self.ws_url,
+                    );
+                    self.rate_limited = true;
+                    Ok(Message::Close(None))
                 }
                 _ => Err(err),
             },
@@ -108,24 +320,238 @@ impl<S, M> WebSocket<S, M>
 
     pub async fn next(&mut self) -> eyre::Result<M> {
         loop {
-            let message = self.recv().await?;
-            return match message {
-                // .map_err(|_| eyre!("Failed to deserialize message: {s}"))
-                Message::Text(s) => Ok(serde_json::from_str::<M>(&s)?),
-                Message::Binary(data) => Ok(serde_json::from_slice::<M>(&data)?),
-                Message::Close(_) => {
-                    self.reconnect().await;
-                    continue;
-                },
-                Message::Pong(_) => Ok(M::pong()),
-                _ => bail!("unsupported websocket message"),
-            };
+            match self.try_next().await? {
+                Some(message) => return Ok(message),
+                None => self.reconnect().await?,
+            }
+        }
+    }
+
+    /// Like `next`, but returns `None` instead of reconnecting when the connection is closed,
+    /// for the callers which have to act on the disconnect before calling `reconnect`
+    pub async fn try_next(&mut self) -> eyre::Result<Option<M>> {
+        if let Some(message) = self.buffered.pop_front() {
+            return Ok(Some(message));
+        }
+        loop {
+            match Self::decode(self.recv().await?)? {
+                Frame::Message(message) => return Ok(Some(message)),
+                Frame::Close => return Ok(None),
+                Frame::Skip => {}
+            }
+        }
+    }
+
+    /// Next message, keeping the connection alive with the heartbeat.
+    /// Only the cancel-safe `try_next` is raced against the heartbeat deadline,
+    /// reconnecting on a closed connection or a missing pong happens outside of `select!`,
+    /// so that a deadline can't interrupt a reconnect in progress.
+    pub async fn next_event(&mut self, heartbeat: &mut Heartbeat) -> eyre::Result<WsEvent<M>> {
+        match self.try_next_event(heartbeat).await? {
+            Some(event) => Ok(event),
+            None => {
+                self.reconnect().await?;
+                heartbeat.reset();
+                Ok(WsEvent::Reconnected)
+            }
         }
+    }
+
+    /// Cancel-safe part of `next_event`, to be raced against the other sources of the caller.
+    /// Returns `None` when the connection is closed or the pong is missing,
+    /// then the caller has to `reconnect` and `reset` the heartbeat outside of `select!`.
+    pub async fn try_next_event(&mut self, heartbeat: &mut Heartbeat) -> eyre::Result<Option<WsEvent<M>>> {
+        let deadline = heartbeat.deadline();
+        let message = tokio::select! {
+            res = self.try_next() => res?,
+            _ = tokio::time::sleep_until(deadline) => match heartbeat.on_deadline() {
+                HeartbeatAction::Ping => return Ok(Some(WsEvent::Ping)),
+                HeartbeatAction::Reconnect => {
+                    warn!("No pong within {:?}, reconnecting to {}", heartbeat.pong_timeout(), self.ws_url);
+                    None
+                }
+            },
+        };
+        if message.is_some() {
+            heartbeat.on_message();
+        }
+        Ok(message.map(WsEvent::Message))
+    }
 
+    fn decode(message: Message) -> eyre::Result<Frame<M>> {
+        let frame = match message {
+            // .map_err(|_| eyre!("Failed to deserialize message: {s}"))
+            Message::Text(s) if M::PONG_TEXT.is_some_and(|pong| s == pong) => Frame::Message(M::pong()),
+            Message::Text(s) => Frame::Message(serde_json::from_str::<M>(&s)?),
+            Message::Binary(data) => Frame::Message(serde_json::from_slice::<M>(&data)?),
+            Message::Close(_) => Frame::Close,
+            Message::Pong(_) => Frame::Message(M::pong()),
+            // tungstenite queues the Pong reply itself and flushes it on the next read
+            Message::Ping(_) => Frame::Skip,
+            _ => bail!("unsupported websocket message"),
+        };
+        Ok(frame)
     }
 
     pub async fn ping(&mut self, data: Vec<u8>) -> eyre::Result<()> {
         self.ws_stream.send(Message::Ping(data.clone())).await?;
         Ok(())
     }
+
+    /// Application-level ping for exchanges which expect a plain text frame instead of the protocol Ping
+    pub async fn ping_text(&mut self, text: &str) -> eyre::Result<()> {
+        self.ws_stream.send(Message::text(text)).await?;
+        Ok(())
+    }
+}
+
+fn is_too_many_requests(err: &Error) -> bool {
+    matches!(err, Error::Http(response) if response.status() == StatusCode::TOO_MANY_REQUESTS)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use futures_util::{SinkExt, StreamExt};
+    use serde::{Deserialize, Serialize};
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio_tungstenite::tungstenite::Message;
+
+    use crate::api::backoff::BackoffConfig;
+    use crate::api::connection::WsMessage;
+    use crate::api::heartbeat::Heartbeat;
+    use crate::api::ws::{WebSocket, WsEvent};
+    use crate::model::stream::WsStream;
+
+    #[derive(Debug, Serialize, Clone)]
+    struct TestRequest {
+        id: Option<u64>,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(untagged)]
+    enum TestMessage {
+        Response { id: u64 },
+        Update { data: u64 },
+        Pong,
+    }
+
+    impl WsMessage for TestMessage {
+        fn pong() -> Self {
+            Self::Pong
+        }
+
+        fn response_id(&self) -> Option<u64> {
+            match self {
+                TestMessage::Response { id } => Some(*id),
+                _ => None,
+            }
+        }
+    }
+
+    #[derive(Clone)]
+    struct TestStream;
+
+    impl WsStream for TestStream {
+        type Kind = ();
+        type Subscribe = TestRequest;
+        type Login = ();
+
+        fn kind(&self) -> Self::Kind {}
+
+        fn subscribe_requests(&self) -> Vec<Self::Subscribe> {
+            vec![TestRequest { id: None }]
+        }
+
+        fn set_request_id(request: &mut Self::Subscribe, id: u64) -> bool {
+            request.id = Some(id);
+            true
+        }
+    }
+
+    /// Answers every request with an update followed by the response with the request id
+    async fn serve(listener: TcpListener) {
+        let (tcp, _) = listener.accept().await.unwrap();
+        answer(tcp).await;
+    }
+
+    async fn answer(tcp: TcpStream) {
+        let mut ws = tokio_tungstenite::accept_async(tcp).await.unwrap();
+        let mut data = 0;
+        while let Some(Ok(Message::Text(request))) = ws.next().await {
+            let id = serde_json::from_str::<serde_json::Value>(&request).unwrap()["id"].as_u64().unwrap();
+            data += 1;
+            ws.send(Message::text(format!(r#"{{"data":{data}}}"#))).await.unwrap();
+            ws.send(Message::text(format!(r#"{{"id":{id}}}"#))).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn responses_are_correlated_by_id() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap()).into();
+        tokio::spawn(serve(listener));
+
+        let mut ws = WebSocket::<TestStream, TestMessage>::try_establish_connection(&url, TestStream, 0, Default::default())
+            .await
+            .unwrap();
+        ws.subscribe().await.unwrap();
+
+        let id = ws.next_request_id();
+        assert_eq!(id, 2);
+        let response = ws.call(id, &TestRequest { id: Some(id) }, Duration::from_secs(5)).await.unwrap();
+        assert_eq!(response, TestMessage::Response { id });
+
+        assert_eq!(ws.next().await.unwrap(), TestMessage::Update { data: 1 });
+        assert_eq!(ws.next().await.unwrap(), TestMessage::Update { data: 2 });
+    }
+
+    #[tokio::test]
+    async fn failed_initial_subscription_is_retried() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap()).into();
+        tokio::spawn(async move {
+            // the first connection is closed without answering the subscribe request
+            let (tcp, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(tcp).await.unwrap();
+            ws.next().await.unwrap().unwrap();
+            ws.close(None).await.unwrap();
+
+            let (tcp, _) = listener.accept().await.unwrap();
+            answer(tcp).await;
+        });
+
+        let backoff = BackoffConfig { initial_delay_ms: 10, ..Default::default() };
+        let mut ws = WebSocket::<TestStream, TestMessage>::connect_and_subscribe(&url, TestStream, 0, backoff)
+            .await
+            .unwrap();
+        assert_eq!(ws.next().await.unwrap(), TestMessage::Update { data: 1 });
+    }
+
+    #[tokio::test]
+    async fn missing_pong_reconnects() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap()).into();
+        tokio::spawn(async move {
+            // the first connection answers the subscription and goes silent
+            let (tcp, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(tcp).await.unwrap();
+            ws.next().await.unwrap().unwrap();
+            ws.send(Message::text(r#"{"id":1}"#)).await.unwrap();
+            tokio::spawn(async move { while ws.next().await.is_some() {} });
+
+            let (tcp, _) = listener.accept().await.unwrap();
+            answer(tcp).await;
+        });
+
+        let backoff = BackoffConfig { initial_delay_ms: 10, ..Default::default() };
+        let mut ws = WebSocket::<TestStream, TestMessage>::connect_and_subscribe(&url, TestStream, 0, backoff)
+            .await
+            .unwrap();
+        let mut heartbeat = Heartbeat::new(Duration::from_millis(50), Duration::from_millis(50));
+        assert!(matches!(ws.next_event(&mut heartbeat).await.unwrap(), WsEvent::Ping));
+        assert!(matches!(ws.next_event(&mut heartbeat).await.unwrap(), WsEvent::Reconnected));
+        assert!(matches!(ws.next_event(&mut heartbeat).await.unwrap(), WsEvent::Message(TestMessage::Update { data: 1 })));
+    }
 }
@@ -0,0 +1,16 @@
+use compact_str::CompactString;
+use derive_more::Display;
+use serde::Deserialize;
+
+#[derive(Debug, Display, Deserialize)]
+#[display("{code}:{msg}")]
+pub struct BinanceErrorResponse {
+    pub code: i64,
+    pub msg: CompactString,
+}
+
+impl std::error::Error for BinanceErrorResponse {
+    fn description(&self) -> &str {
+        self.msg.as_str()
+    }
+}
@@ -0,0 +1,21 @@
+use serde::Deserialize;
+
+use crate::model::internal::SingleLot;
+use crate::utils::basic_types::{Amount, Price};
+
+/// `["price", "quantity"]` level of both the depth snapshot and the diff stream,
+/// the quantity is absolute and zero removes the level
+#[derive(Debug, Deserialize, Clone)]
+pub struct BinanceBookLevel {
+    pub price: Price,
+    pub amount: Amount,
+}
+
+impl BinanceBookLevel {
+    pub fn to_single_lot(&self) -> SingleLot {
+        SingleLot {
+            price: self.price,
+            amount: self.amount,
+        }
+    }
+}
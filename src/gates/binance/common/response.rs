@@ -0,0 +1,20 @@
+use serde::Deserialize;
+
+use crate::gates::binance::common::error::BinanceErrorResponse;
+
+/// Binance returns either the data itself or `{"code": -1121, "msg": "Invalid symbol."}`
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum BinanceResponse<R> {
+    Error(BinanceErrorResponse),
+    Data(R),
+}
+
+impl<R> BinanceResponse<R> {
+    pub fn into_result(self) -> Result<R, BinanceErrorResponse> {
+        match self {
+            BinanceResponse::Error(err) => Err(err),
+            BinanceResponse::Data(data) => Ok(data),
+        }
+    }
+}
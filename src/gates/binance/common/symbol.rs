@@ -0,0 +1,52 @@
+use compact_str::{CompactString, format_compact};
+use eyre::eyre;
+
+use crate::model::symbol::{Symbol, SymbolKind, SymbolMapping};
+
+/// Quote currencies of Binance spot pairs, the longer ones first so that e.g. `FDUSD` wins over `USD`
+const QUOTE_CURRENCIES: [&str; 16] = [
+    "FDUSD", "USDT", "USDC", "TUSD", "BUSD", "EURI", "USDP", "DAI", "EUR", "TRY", "BRL", "JPY", "BTC", "ETH", "BNB", "USD",
+];
+
+/// Binance spot symbols are the concatenation of base and quote currencies, e.g. `BTCUSDT`.
+/// Base and quote of a native symbol are told apart by the known quote currencies.
+pub struct BinanceSymbols;
+
+impl SymbolMapping for BinanceSymbols {
+    fn to_native(symbol: &Symbol) -> eyre::Result<CompactString> {
+        match symbol.kind {
+            SymbolKind::Spot => Ok(format_compact!("{}{}", symbol.base, symbol.quote)),
+            _ => Err(eyre!("Binance spot has no instrument for {symbol}")),
+        }
+    }
+
+    fn from_native(native: &str) -> eyre::Result<Symbol> {
+        let native = native.to_ascii_uppercase();
+        QUOTE_CURRENCIES
+            .iter()
+            .find_map(|quote| {
+                native
+                    .strip_suffix(quote)
+                    .filter(|base| !base.is_empty())
+                    .map(|base| Symbol::spot(base, *quote))
+            })
+            .ok_or_else(|| eyre!("unknown quote currency of Binance symbol {native}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::gates::binance::common::symbol::BinanceSymbols;
+    use crate::model::symbol::{Symbol, SymbolMapping};
+
+    #[test]
+    fn symbol_mapping() {
+        assert_eq!(BinanceSymbols::to_native(&Symbol::spot("BTC", "USDT")).unwrap(), "BTCUSDT");
+        assert!(BinanceSymbols::to_native(&Symbol::perpetual("BTC", "USDT")).is_err());
+
+        assert_eq!(BinanceSymbols::from_native("BTCUSDT").unwrap(), Symbol::spot("BTC", "USDT"));
+        assert_eq!(BinanceSymbols::from_native("ethbtc").unwrap(), Symbol::spot("ETH", "BTC"));
+        assert_eq!(BinanceSymbols::from_native("BTCFDUSD").unwrap(), Symbol::spot("BTC", "FDUSD"));
+        assert!(BinanceSymbols::from_native("USDT").is_err());
+    }
+}
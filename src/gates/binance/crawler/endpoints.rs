@@ -0,0 +1,16 @@
+use http::Method;
+
+use crate::api::endpoint::Endpoint;
+use crate::gates::binance::common::response::BinanceResponse;
+use crate::gates::binance::crawler::model::BinanceOrderBookSnapshot;
+use crate::gates::binance::crawler::request::GetDepthRequest;
+
+pub struct GetDepth;
+
+impl Endpoint for GetDepth {
+    type Request = GetDepthRequest;
+    type Response = BinanceResponse<BinanceOrderBookSnapshot>;
+
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "/api/v3/depth";
+}
@@ -0,0 +1,5 @@
+pub mod config;
+pub mod endpoints;
+pub mod model;
+pub mod poller;
+pub mod request;
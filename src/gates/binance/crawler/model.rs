@@ -0,0 +1,66 @@
+use serde::Deserialize;
+
+use crate::gates::binance::common::model::BinanceBookLevel;
+use crate::model::exchange::Exchange;
+use crate::model::internal::{L2Snapshot, MdInstrument};
+use crate::model::order_book::OrderBook;
+use crate::model::symbol::Symbol;
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BinanceOrderBookSnapshot {
+    /// Id of the last diff stream update included into the snapshot
+    pub last_update_id: u64,
+    pub bids: Vec<BinanceBookLevel>,
+    pub asks: Vec<BinanceBookLevel>,
+}
+
+impl BinanceOrderBookSnapshot {
+    pub fn to_internal_snapshot(&self, symbol: Symbol) -> L2Snapshot {
+        L2Snapshot {
+            exchange_time: None,
+            sequence_no: Some(self.last_update_id),
+            exchange: Exchange::Binance,
+            instrument: MdInstrument::Symbol(symbol),
+            bids: self.bids.iter().map(BinanceBookLevel::to_single_lot).collect(),
+            asks: self.asks.iter().map(BinanceBookLevel::to_single_lot).collect(),
+        }
+    }
+}
+
+impl From<BinanceOrderBookSnapshot> for OrderBook {
+    fn from(snapshot: BinanceOrderBookSnapshot) -> Self {
+        Self {
+            bids: snapshot.bids.into_iter().map(|l| (l.price, l.amount)).collect(),
+            asks: snapshot.asks.into_iter().map(|l| (l.price, l.amount)).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::gates::binance::common::response::BinanceResponse;
+    use crate::gates::binance::crawler::model::BinanceOrderBookSnapshot;
+    use crate::model::order_book::OrderBook;
+
+    #[test]
+    fn depth_parsing() {
+        let depth_str = fs::read_to_string("tests/binance_depth.json").unwrap();
+        let response: BinanceResponse<BinanceOrderBookSnapshot> = serde_json::from_str(&depth_str).unwrap();
+        let snapshot = response.into_result().unwrap();
+        assert_eq!(snapshot.last_update_id, 1027024);
+
+        let ob: OrderBook = snapshot.into();
+        assert_eq!(ob.bids.len(), 2);
+        assert_eq!(ob.asks.len(), 2);
+    }
+
+    #[test]
+    fn error_parsing() {
+        let error_str = r#"{"code":-1121,"msg":"Invalid symbol."}"#;
+        let response: BinanceResponse<BinanceOrderBookSnapshot> = serde_json::from_str(error_str).unwrap();
+        assert_eq!(response.into_result().unwrap_err().code, -1121);
+    }
+}
@@ -0,0 +1,70 @@
+use async_trait::async_trait;
+
+use crate::api::api;
+use crate::api::poller::ExchangePoller;
+use crate::gates::binance::common::symbol::BinanceSymbols;
+use crate::gates::binance::crawler::config::BinancePollerConfig;
+use crate::gates::binance::crawler::endpoints::GetDepth;
+use crate::gates::binance::crawler::model::BinanceOrderBookSnapshot;
+use crate::gates::binance::crawler::request::GetDepthRequest;
+use crate::model::order_book::OrderBook;
+use crate::model::symbol::{Symbol, SymbolMapping};
+
+#[derive(Debug, Default)]
+pub struct BinanceExchangePoller {
+    pub config: BinancePollerConfig,
+}
+
+#[async_trait]
+impl ExchangePoller for BinanceExchangePoller {
+    async fn get_order_book(&self, symbol: &Symbol) -> eyre::Result<OrderBook> {
+        Ok(self.get_depth(symbol, None).await?.into())
+    }
+}
+
+impl BinanceExchangePoller {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn with_config(config: BinancePollerConfig) -> Self {
+        Self { config }
+    }
+
+    /// Order book snapshot along with the id of the last diff stream update it includes
+    pub async fn get_depth(&self, symbol: &Symbol, limit: Option<u16>) -> eyre::Result<BinanceOrderBookSnapshot> {
+        let request = GetDepthRequest::new(BinanceSymbols::to_native(symbol)?, limit);
+
+        let response = api::http_urlencoded_query_request::<GetDepth>(
+            &self.config.http_url,
+            &request,
+            Default::default(),
+        ).await?;
+
+        Ok(response.into_result()?)
+    }
+}
+
+#[cfg(test)]
+mod api_tests {
+    use crate::api::poller::ExchangePoller;
+    use crate::gates::binance::crawler::poller::BinanceExchangePoller;
+    use crate::model::symbol::Symbol;
+
+    #[ignore]
+    #[tokio::test]
+    async fn get_ob_test() {
+        let poller = BinanceExchangePoller::new();
+        let ob = poller.get_order_book(&Symbol::spot("BTC", "USDT")).await.unwrap();
+        assert_ne!(ob.bids.len(), 0);
+        assert_ne!(ob.asks.len(), 0);
+    }
+
+    #[ignore]
+    #[tokio::test]
+    async fn get_ob_test_instrument_not_exist() {
+        let poller = BinanceExchangePoller::new();
+        let ob = poller.get_order_book(&Symbol::spot("BTC", "USDX")).await;
+        assert!(ob.is_err());
+    }
+}
@@ -0,0 +1,17 @@
+use compact_str::CompactString;
+use serde::Serialize;
+
+#[derive(Debug, Serialize, Clone)]
+pub struct GetDepthRequest {
+    /// Symbol, e.g. BTCUSDT
+    symbol: CompactString,
+    /// Default 100, maximum 5000. The request weight grows with the limit:
+    /// 5 up to 100, 25 up to 500, 50 up to 1000 and 250 up to 5000
+    limit: Option<u16>,
+}
+
+impl GetDepthRequest {
+    pub fn new(symbol: CompactString, limit: Option<u16>) -> Self {
+        Self { symbol, limit }
+    }
+}
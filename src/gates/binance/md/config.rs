@@ -0,0 +1,52 @@
+use compact_str::CompactString;
+use serde::Deserialize;
+
+use crate::api::backoff::BackoffConfig;
+use crate::gates::binance::crawler::config::BinancePollerConfig;
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct BinanceMdConnectionConfig {
+    pub ws_url: CompactString,
+    /// REST api the depth snapshots are fetched from
+    #[serde(default)]
+    pub rest: BinancePollerConfig,
+    /// Depth of the REST snapshot, 5000 at most. Bigger snapshots cost more request weight
+    pub snapshot_depth: u16,
+    /// Delays before fetching the snapshot again after a failed request or a stale snapshot.
+    /// `max_attempts` is ignored, the snapshot is refetched until the book is synchronized
+    #[serde(default = "default_snapshot_retry")]
+    pub snapshot_retry: BackoffConfig,
+    /// Send a protocol Ping if no message was received for this amount of seconds.
+    /// The diffs of an active symbol arrive every 100 ms, Binance's own pings don't count as messages
+    pub idle_timeout_seconds: u64,
+    /// Reconnect if no message arrives within this amount of seconds after the ping was sent
+    pub pong_timeout_seconds: u64,
+    /// Binance accepts at most 5 incoming messages per second
+    pub subscribe_interval_ms: u64,
+    /// Retry policy for connecting, reconnecting and resubscribing
+    #[serde(default)]
+    pub reconnect: BackoffConfig,
+}
+
+impl Default for BinanceMdConnectionConfig {
+    fn default() -> Self {
+        Self {
+            ws_url: "wss://stream.binance.com:9443/ws".into(),
+            rest: BinancePollerConfig::default(),
+            snapshot_depth: 1000,
+            snapshot_retry: default_snapshot_retry(),
+            idle_timeout_seconds: 10,
+            pong_timeout_seconds: 5,
+            subscribe_interval_ms: 250,
+            reconnect: BackoffConfig::default(),
+        }
+    }
+}
+
+fn default_snapshot_retry() -> BackoffConfig {
+    BackoffConfig {
+        initial_delay_ms: 250,
+        max_delay_ms: 30_000,
+        ..BackoffConfig::default()
+    }
+}
@@ -0,0 +1,238 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use compact_str::CompactString;
+use eyre::Result;
+use log::{error, trace, warn};
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+use crate::api::backoff::{Backoff, BackoffConfig};
+use crate::api::connection::MdConnection;
+use crate::api::heartbeat::Heartbeat;
+use crate::api::ws::{WebSocket, WsEvent};
+use crate::gates::binance::common::symbol::BinanceSymbols;
+use crate::gates::binance::crawler::model::BinanceOrderBookSnapshot;
+use crate::gates::binance::crawler::poller::BinanceExchangePoller;
+use crate::gates::binance::md::config::BinanceMdConnectionConfig;
+use crate::gates::binance::md::model::{BinanceDepthUpdate, BinanceWsMessage};
+use crate::gates::binance::md::stream::{BinanceStream, BinanceStreamKind};
+use crate::gates::binance::md::sync::{DepthSync, SnapshotOutcome, UpdateOutcome};
+use crate::model::exchange::Exchange;
+use crate::model::internal::{BookInvalid, BookInvalidReason, MdInstrument, MdMessage};
+use crate::model::symbol::{Symbol, SymbolMapping};
+
+type SnapshotResult = (CompactString, Result<BinanceOrderBookSnapshot>);
+
+/// Order books of Binance spot symbols built from `<symbol>@depth@100ms` diffs
+/// on top of the `/api/v3/depth` snapshots.
+///
+/// Snapshots are fetched in the background while the diffs are buffered, see `DepthSync`.
+/// A gap in the diffs, e.g. after a reconnect, emits `BookInvalid` and restarts the synchronization.
+pub struct BinanceMdConnection {
+    ws: WebSocket<BinanceStream, BinanceWsMessage>,
+    heartbeat: Heartbeat,
+    poller: Arc<BinanceExchangePoller>,
+    snapshot_depth: u16,
+    snapshot_retry: BackoffConfig,
+    /// Retry delays per symbol, dropped once its book is synchronized
+    snapshot_backoffs: HashMap<CompactString, Backoff>,
+    queue: VecDeque<MdMessage>,
+    /// Canonical symbols of the subscribed Binance symbols
+    symbols: HashMap<CompactString, Symbol>,
+    books: HashMap<CompactString, DepthSync>,
+    snapshot_tx: UnboundedSender<SnapshotResult>,
+    snapshot_rx: UnboundedReceiver<SnapshotResult>,
+}
+
+impl BinanceMdConnection {
+    pub async fn new(symbols: Vec<Symbol>, config: BinanceMdConnectionConfig) -> Result<Self> {
+        let natives = symbols.iter().map(BinanceSymbols::to_native).collect::<Result<Vec<_>>>()?;
+        let stream = BinanceStream { symbols: natives.clone(), kind: BinanceStreamKind::DepthUpdate };
+        let ws = WebSocket::connect_and_subscribe(
+            &config.ws_url,
+            stream,
+            config.subscribe_interval_ms,
+            config.reconnect.clone(),
+        ).await?;
+
+        let (snapshot_tx, snapshot_rx) = mpsc::unbounded_channel();
+        Ok(Self {
+            ws,
+            heartbeat: Heartbeat::new(
+                Duration::from_secs(config.idle_timeout_seconds),
+                Duration::from_secs(config.pong_timeout_seconds),
+            ),
+            poller: Arc::new(BinanceExchangePoller::with_config(config.rest)),
+            snapshot_depth: config.snapshot_depth,
+            snapshot_retry: config.snapshot_retry,
+            snapshot_backoffs: HashMap::new(),
+            queue: VecDeque::new(),
+            symbols: natives.into_iter().zip(symbols).collect(),
+            books: HashMap::new(),
+            snapshot_tx,
+            snapshot_rx,
+        })
+    }
+
+    fn on_depth_update(&mut self, update: BinanceDepthUpdate) {
+        let Some(symbol) = self.symbols.get(&update.symbol) else {
+            warn!("received Binance depth update of unknown symbol {}", update.symbol);
+            return;
+        };
+        let native = update.symbol.clone();
+        let exchange_time = update.event_time;
+        match self.books.entry(native.clone()).or_default().on_update(update) {
+            UpdateOutcome::Buffered { request_snapshot } => {
+                if request_snapshot {
+                    self.request_snapshot(native, Duration::ZERO);
+                }
+            }
+            UpdateOutcome::Apply(update) => {
+                self.queue.extend(update.to_md(symbol));
+            }
+            UpdateOutcome::Outdated => {
+                trace!("dropped outdated Binance depth update of {native}");
+            }
+            UpdateOutcome::Gap { expected, received } => {
+                warn!("Binance depth gap for {native}: expected {expected}, received {received}; resynchronizing");
+                self.queue.push_back(MdMessage::BookInvalid(BookInvalid {
+                    exchange_time: Some(exchange_time),
+                    sequence_no: Some(received),
+                    exchange: Exchange::Binance,
+                    instrument: MdInstrument::Symbol(symbol.clone()),
+                    reason: BookInvalidReason::SequenceGap { expected: Some(expected), received },
+                }));
+                self.request_snapshot(native, Duration::ZERO);
+            }
+        }
+    }
+
+    fn on_snapshot(&mut self, native: CompactString, snapshot: Result<BinanceOrderBookSnapshot>) {
+        let (Some(symbol), Some(sync)) = (self.symbols.get(&native), self.books.get_mut(&native)) else {
+            return;
+        };
+        let snapshot = match snapshot {
+            Ok(snapshot) => snapshot,
+            Err(err) => {
+                sync.on_snapshot_failure();
+                let delay = self.retry_delay(&native);
+                warn!("Failed to fetch Binance depth snapshot of {native}: {err}; retrying in {delay:?}");
+                self.request_snapshot(native, delay);
+                return;
+            }
+        };
+        match sync.on_snapshot(snapshot.last_update_id) {
+            SnapshotOutcome::Synced(buffer) => {
+                trace!("Binance depth of {native} synchronized at {}", snapshot.last_update_id);
+                self.queue.push_back(MdMessage::L2Snapshot(snapshot.to_internal_snapshot(symbol.clone())));
+                for update in buffer {
+                    self.queue.extend(update.to_md(symbol));
+                }
+                self.snapshot_backoffs.remove(&native);
+            }
+            SnapshotOutcome::Stale => {
+                let delay = self.retry_delay(&native);
+                trace!("Binance depth snapshot of {native} is older than the buffered updates, refetching in {delay:?}");
+                self.request_snapshot(native, delay);
+            }
+            SnapshotOutcome::Ignored => {}
+        }
+    }
+
+    /// Grows with every retry of the symbol, so that a lagging REST api isn't hammered
+    fn retry_delay(&mut self, native: &CompactString) -> Duration {
+        let backoff = self.snapshot_backoffs
+            .entry(native.clone())
+            .or_insert_with(|| Backoff::new(BackoffConfig { max_attempts: None, ..self.snapshot_retry.clone() }));
+        backoff.next_delay().expect("attempts are unlimited")
+    }
+
+    fn request_snapshot(&self, native: CompactString, delay: Duration) {
+        let Some(symbol) = self.symbols.get(&native).cloned() else {
+            return;
+        };
+        let poller = self.poller.clone();
+        let limit = self.snapshot_depth;
+        let tx = self.snapshot_tx.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(delay).await;
+            let snapshot = poller.get_depth(&symbol, Some(limit)).await;
+            // the connection may have been dropped meanwhile
+            let _ = tx.send((native, snapshot));
+        });
+    }
+}
+
+#[async_trait]
+impl MdConnection for BinanceMdConnection {
+    /// Only the cancel-safe `try_next_event` is raced against the snapshots,
+    /// so that a snapshot can't interrupt a reconnect in progress.
+    async fn next(&mut self) -> Result<MdMessage> {
+        while self.queue.is_empty() {
+            let event = tokio::select! {
+                res = self.ws.try_next_event(&mut self.heartbeat) => res?,
+                Some((native, snapshot)) = self.snapshot_rx.recv() => {
+                    self.on_snapshot(native, snapshot);
+                    continue;
+                }
+            };
+            match event {
+                Some(WsEvent::Message(message)) => match message {
+                    BinanceWsMessage::DepthUpdate(update) => self.on_depth_update(update),
+                    BinanceWsMessage::Response(response) => match response.error {
+                        Some(err) => warn!("Binance request {:?} failed: {err}", response.id),
+                        None => trace!("Binance request {:?} succeeded", response.id),
+                    },
+                    BinanceWsMessage::Pong => {}
+                },
+                Some(WsEvent::Ping) => {
+                    if let Err(err) = self.ws.ping(Vec::new()).await {
+                        error!("Failed to send ping to Binance MD stream, {err}");
+                    }
+                }
+                // the diffs missed meanwhile break the continuity, which `DepthSync` reports as a gap
+                None => {
+                    self.ws.reconnect().await?;
+                    self.heartbeat.reset();
+                }
+                // returned by `next_event` only
+                Some(WsEvent::Reconnected) => {}
+            }
+        }
+        let update = self.queue.pop_front().expect("should be some");
+        Ok(update)
+    }
+}
+
+#[cfg(test)]
+mod md_integration_tests {
+    use crate::api::connection::MdConnection;
+    use crate::gates::binance::md::config::BinanceMdConnectionConfig;
+    use crate::gates::binance::md::connection::BinanceMdConnection;
+    use crate::model::internal::MdMessage;
+    use crate::model::storage::Storage;
+    use crate::model::symbol::Symbol;
+
+    #[ignore]
+    #[tokio::test]
+    async fn ob_test() {
+        let mut storage = Storage::new();
+
+        let mut connection = BinanceMdConnection::new(
+            vec![Symbol::spot("BTC", "USDT"), Symbol::spot("ETH", "USDT")],
+            BinanceMdConnectionConfig::default(),
+        ).await.unwrap();
+
+        let mut snapshots = 0;
+        for _ in 0..1000 {
+            let m = connection.next().await.unwrap();
+            if matches!(m, MdMessage::L2Snapshot(_)) {
+                snapshots += 1;
+            }
+            storage.on_ws_update(m);
+        }
+        assert_eq!(snapshots, 2);
+    }
+}
@@ -0,0 +1,5 @@
+pub mod config;
+pub mod connection;
+pub mod model;
+pub mod stream;
+pub mod sync;
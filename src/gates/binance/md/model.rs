@@ -0,0 +1,128 @@
+use compact_str::CompactString;
+use serde::{Deserialize, Serialize};
+
+use crate::api::connection::WsMessage;
+use crate::gates::binance::common::error::BinanceErrorResponse;
+use crate::gates::binance::common::model::BinanceBookLevel;
+use crate::model::exchange::Exchange;
+use crate::model::internal::{L2Increment, MdInstrument, MdMessage, Side};
+use crate::model::symbol::Symbol;
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum BinanceWsMessage {
+    DepthUpdate(BinanceDepthUpdate),
+    Response(BinanceWsResponse),
+    Pong,
+}
+
+impl WsMessage for BinanceWsMessage {
+    fn pong() -> Self {
+        Self::Pong
+    }
+}
+
+/// Event of `<symbol>@depth@100ms` stream
+///
+/// https://developers.binance.com/docs/binance-spot-api-docs/web-socket-streams#diff-depth-stream
+#[derive(Debug, Deserialize, Clone)]
+pub struct BinanceDepthUpdate {
+    /// Event time, Unix timestamp format in milliseconds
+    #[serde(rename = "E")]
+    pub event_time: u64,
+    #[serde(rename = "s")]
+    pub symbol: CompactString,
+    #[serde(rename = "U")]
+    pub first_update_id: u64,
+    #[serde(rename = "u")]
+    pub final_update_id: u64,
+    #[serde(rename = "b")]
+    pub bids: Vec<BinanceBookLevel>,
+    #[serde(rename = "a")]
+    pub asks: Vec<BinanceBookLevel>,
+}
+
+impl BinanceDepthUpdate {
+    /// One increment per changed level, the last one is marked as the end of the update
+    pub fn to_md(&self, symbol: &Symbol) -> Vec<MdMessage> {
+        let levels = self.bids
+            .iter()
+            .map(|l| (Side::Bid, l))
+            .chain(self.asks.iter().map(|l| (Side::Ask, l)));
+        let total = self.bids.len() + self.asks.len();
+        levels
+            .enumerate()
+            .map(|(i, (side, level))| MdMessage::L2Increment(L2Increment {
+                exchange_time: Some(self.event_time),
+                sequence_no: Some(self.final_update_id),
+                exchange: Exchange::Binance,
+                instrument: MdInstrument::Symbol(symbol.clone()),
+                side,
+                price: level.price,
+                amount: level.amount,
+                is_eot: i + 1 == total,
+            }))
+            .collect()
+    }
+}
+
+/// Response to `SUBSCRIBE` request
+#[derive(Debug, Deserialize)]
+pub struct BinanceWsResponse {
+    pub id: Option<u64>,
+    pub error: Option<BinanceErrorResponse>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct BinanceWsRequest {
+    method: &'static str,
+    params: Vec<CompactString>,
+    id: u64,
+}
+
+impl BinanceWsRequest {
+    pub fn new_subscribe(streams: Vec<CompactString>, id: u64) -> Self {
+        Self {
+            method: "SUBSCRIBE",
+            params: streams,
+            id,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::gates::binance::md::model::BinanceWsMessage;
+    use crate::model::internal::{MdMessage, Side};
+    use crate::model::symbol::Symbol;
+
+    #[test]
+    fn depth_update_parsing() {
+        let update_str = fs::read_to_string("tests/binance_ws_depth_update.json").unwrap();
+        let message: BinanceWsMessage = serde_json::from_str(&update_str).unwrap();
+        let BinanceWsMessage::DepthUpdate(update) = message else {
+            panic!("expected depth update, got {message:?}");
+        };
+        assert_eq!((update.first_update_id, update.final_update_id), (157, 160));
+
+        let increments = update.to_md(&Symbol::spot("BNB", "BTC"));
+        assert_eq!(increments.len(), 3);
+        let MdMessage::L2Increment(last) = increments.last().unwrap() else {
+            panic!("expected increment");
+        };
+        assert_eq!(last.side, Side::Ask);
+        assert!(last.is_eot);
+    }
+
+    #[test]
+    fn response_parsing() {
+        let message: BinanceWsMessage = serde_json::from_str(r#"{"result":null,"id":1}"#).unwrap();
+        assert!(matches!(message, BinanceWsMessage::Response(r) if r.id == Some(1) && r.error.is_none()));
+
+        let error_str = r#"{"error":{"code":2,"msg":"Invalid request: unknown variant"},"id":2}"#;
+        let message: BinanceWsMessage = serde_json::from_str(error_str).unwrap();
+        assert!(matches!(message, BinanceWsMessage::Response(r) if r.error.as_ref().is_some_and(|e| e.code == 2)));
+    }
+}
@@ -0,0 +1,48 @@
+use compact_str::{CompactString, format_compact};
+
+use crate::gates::binance::md::model::BinanceWsRequest;
+use crate::model::stream::WsStream;
+
+#[derive(Clone)]
+pub struct BinanceStream {
+    /// Native symbols, e.g. BTCUSDT
+    pub symbols: Vec<CompactString>,
+    pub kind: BinanceStreamKind,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum BinanceStreamKind {
+    /// <symbol>@depth@100ms: changed levels, pushed every 100 ms.
+    /// Has to be synchronized with the `/api/v3/depth` snapshot.
+    ///
+    /// https://developers.binance.com/docs/binance-spot-api-docs/web-socket-streams#diff-depth-stream
+    DepthUpdate,
+}
+
+impl BinanceStreamKind {
+    pub fn stream_name(&self, symbol: &str) -> CompactString {
+        match self {
+            BinanceStreamKind::DepthUpdate => format_compact!("{}@depth@100ms", symbol.to_ascii_lowercase()),
+        }
+    }
+}
+
+impl BinanceStream {
+    pub fn streams(&self, symbols: &[CompactString]) -> Vec<CompactString> {
+        symbols.iter().map(|s| self.kind.stream_name(s)).collect()
+    }
+}
+
+impl WsStream for BinanceStream {
+    type Kind = BinanceStreamKind;
+    type Subscribe = BinanceWsRequest;
+    type Login = ();
+
+    fn kind(&self) -> Self::Kind {
+        self.kind
+    }
+
+    fn subscribe_requests(&self) -> Vec<Self::Subscribe> {
+        vec![BinanceWsRequest::new_subscribe(self.streams(&self.symbols), 1)]
+    }
+}
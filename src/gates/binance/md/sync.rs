@@ -0,0 +1,171 @@
+use std::mem;
+
+use crate::gates::binance::md::model::BinanceDepthUpdate;
+
+/// Synchronization of the diff depth stream with the REST snapshot of a single symbol.
+///
+/// 1. Buffer the stream events and request a snapshot.
+/// 2. Refetch the snapshot if its `lastUpdateId` is older than `U` of the first buffered event.
+/// 3. Drop the buffered events with `u <= lastUpdateId`.
+/// 4. Apply the rest; every next event has to start right after the previous one,
+///    i.e. `U <= last u + 1 <= u`, otherwise the book is out of sync and the process restarts.
+///
+/// https://developers.binance.com/docs/binance-spot-api-docs/web-socket-streams#how-to-manage-a-local-order-book-correctly
+#[derive(Debug)]
+pub enum DepthSync {
+    /// Waiting for the snapshot, the buffered events are continuous
+    Buffering {
+        buffer: Vec<BinanceDepthUpdate>,
+        snapshot_requested: bool,
+    },
+    Synced {
+        last_update_id: u64,
+    },
+}
+
+#[derive(Debug)]
+pub enum UpdateOutcome {
+    Buffered { request_snapshot: bool },
+    /// The event continues the book and should be applied
+    Apply(BinanceDepthUpdate),
+    /// The event is already included into the book
+    Outdated,
+    /// Events were missed, the book is dropped and a new snapshot is requested
+    Gap { expected: u64, received: u64 },
+}
+
+#[derive(Debug)]
+pub enum SnapshotOutcome {
+    /// The snapshot should be applied followed by the buffered events
+    Synced(Vec<BinanceDepthUpdate>),
+    /// The snapshot is older than the buffered events, another one is requested
+    Stale,
+    /// The book is already synchronized
+    Ignored,
+}
+
+impl Default for DepthSync {
+    fn default() -> Self {
+        DepthSync::Buffering {
+            buffer: Vec::new(),
+            snapshot_requested: false,
+        }
+    }
+}
+
+impl DepthSync {
+    pub fn on_update(&mut self, update: BinanceDepthUpdate) -> UpdateOutcome {
+        match self {
+            DepthSync::Buffering { buffer, snapshot_requested } => {
+                if buffer.last().is_some_and(|last| update.first_update_id > last.final_update_id + 1) {
+                    // the snapshot has to cover the gap anyway, the older events are of no use
+                    buffer.clear();
+                }
+                buffer.push(update);
+                UpdateOutcome::Buffered { request_snapshot: !mem::replace(snapshot_requested, true) }
+            }
+            DepthSync::Synced { last_update_id } => {
+                if update.final_update_id <= *last_update_id {
+                    return UpdateOutcome::Outdated;
+                }
+                let expected = *last_update_id + 1;
+                if update.first_update_id > expected {
+                    let received = update.first_update_id;
+                    *self = DepthSync::Buffering {
+                        buffer: vec![update],
+                        snapshot_requested: true,
+                    };
+                    return UpdateOutcome::Gap { expected, received };
+                }
+                *last_update_id = update.final_update_id;
+                UpdateOutcome::Apply(update)
+            }
+        }
+    }
+
+    pub fn on_snapshot(&mut self, snapshot_update_id: u64) -> SnapshotOutcome {
+        let DepthSync::Buffering { buffer, snapshot_requested } = self else {
+            return SnapshotOutcome::Ignored;
+        };
+        buffer.retain(|u| u.final_update_id > snapshot_update_id);
+        if buffer.first().is_some_and(|first| first.first_update_id > snapshot_update_id + 1) {
+            *snapshot_requested = true;
+            return SnapshotOutcome::Stale;
+        }
+
+        let buffer = mem::take(buffer);
+        let last_update_id = buffer.last().map_or(snapshot_update_id, |u| u.final_update_id);
+        *self = DepthSync::Synced { last_update_id };
+        SnapshotOutcome::Synced(buffer)
+    }
+
+    /// Drops the state after the snapshot request failed, the next event requests it again
+    pub fn on_snapshot_failure(&mut self) {
+        if let DepthSync::Buffering { snapshot_requested, .. } = self {
+            *snapshot_requested = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::gates::binance::md::model::BinanceDepthUpdate;
+    use crate::gates::binance::md::sync::{DepthSync, SnapshotOutcome, UpdateOutcome};
+
+    fn update(first_update_id: u64, final_update_id: u64) -> BinanceDepthUpdate {
+        BinanceDepthUpdate {
+            event_time: 0,
+            symbol: "BTCUSDT".into(),
+            first_update_id,
+            final_update_id,
+            bids: Vec::new(),
+            asks: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn buffered_events_are_applied_after_snapshot() {
+        let mut sync = DepthSync::default();
+        assert!(matches!(sync.on_update(update(100, 105)), UpdateOutcome::Buffered { request_snapshot: true }));
+        assert!(matches!(sync.on_update(update(106, 110)), UpdateOutcome::Buffered { request_snapshot: false }));
+        assert!(matches!(sync.on_update(update(111, 115)), UpdateOutcome::Buffered { request_snapshot: false }));
+
+        let SnapshotOutcome::Synced(buffer) = sync.on_snapshot(107) else {
+            panic!("expected synced, got {sync:?}");
+        };
+        let ids: Vec<_> = buffer.iter().map(|u| u.first_update_id).collect();
+        assert_eq!(ids, [106, 111]);
+
+        assert!(matches!(sync.on_update(update(111, 115)), UpdateOutcome::Outdated));
+        assert!(matches!(sync.on_update(update(116, 120)), UpdateOutcome::Apply(_)));
+        assert!(matches!(sync.on_snapshot(200), SnapshotOutcome::Ignored));
+    }
+
+    #[test]
+    fn stale_snapshot_is_refetched() {
+        let mut sync = DepthSync::default();
+        sync.on_update(update(100, 105));
+        assert!(matches!(sync.on_snapshot(90), SnapshotOutcome::Stale));
+        assert!(matches!(sync.on_update(update(106, 110)), UpdateOutcome::Buffered { request_snapshot: false }));
+        assert!(matches!(sync.on_snapshot(99), SnapshotOutcome::Synced(buffer) if buffer.len() == 2));
+    }
+
+    #[test]
+    fn gap_restarts_synchronization() {
+        let mut sync = DepthSync::default();
+        assert!(matches!(sync.on_snapshot(50), SnapshotOutcome::Synced(buffer) if buffer.is_empty()));
+        assert!(matches!(sync.on_update(update(45, 55)), UpdateOutcome::Apply(_)));
+        assert!(matches!(sync.on_update(update(60, 65)), UpdateOutcome::Gap { expected: 56, received: 60 }));
+        assert!(matches!(sync.on_update(update(66, 70)), UpdateOutcome::Buffered { request_snapshot: false }));
+        assert!(matches!(sync.on_snapshot(62), SnapshotOutcome::Synced(buffer) if buffer.len() == 2));
+    }
+
+    #[test]
+    fn discontinuous_buffer_is_dropped() {
+        let mut sync = DepthSync::default();
+        sync.on_update(update(100, 105));
+        sync.on_update(update(110, 115));
+        assert!(matches!(sync.on_snapshot(104), SnapshotOutcome::Stale));
+        assert!(matches!(sync.on_snapshot(109), SnapshotOutcome::Synced(buffer) if buffer.len() == 1));
+    }
+}
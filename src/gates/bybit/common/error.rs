@@ -0,0 +1,17 @@
+use compact_str::CompactString;
+use derive_more::Display;
+use serde::Deserialize;
+
+#[derive(Debug, Display, Deserialize)]
+#[display("{ret_code}:{ret_msg}")]
+#[serde(rename_all = "camelCase")]
+pub struct BybitErrorResponse {
+    pub ret_code: i64,
+    pub ret_msg: CompactString,
+}
+
+impl std::error::Error for BybitErrorResponse {
+    fn description(&self) -> &str {
+        self.ret_msg.as_str()
+    }
+}
@@ -0,0 +1,4 @@
+pub mod error;
+pub mod model;
+pub mod response;
+pub mod symbol;
@@ -0,0 +1,96 @@
+use compact_str::CompactString;
+use serde::{Deserialize, Serialize};
+
+use crate::model::exchange::Exchange;
+use crate::model::internal::{L2Increment, L2Snapshot, MdInstrument, MdMessage, Side, SingleLot};
+use crate::model::symbol::Symbol;
+use crate::utils::basic_types::{Amount, Price};
+
+/// Product type, every category has its own public websocket url
+#[derive(Debug, Deserialize, Serialize, Copy, Clone, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum BybitCategory {
+    Spot,
+    /// USDT and USDC perpetuals and futures
+    Linear,
+    /// Perpetuals and futures margined in the base currency
+    Inverse,
+    Option,
+}
+
+impl BybitCategory {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BybitCategory::Spot => "spot",
+            BybitCategory::Linear => "linear",
+            BybitCategory::Inverse => "inverse",
+            BybitCategory::Option => "option",
+        }
+    }
+
+    /// Maximum depth of both `/v5/market/orderbook` and the `orderbook` topic
+    pub fn max_order_book_depth(&self) -> u16 {
+        match self {
+            BybitCategory::Spot => 200,
+            BybitCategory::Linear | BybitCategory::Inverse => 500,
+            BybitCategory::Option => 25,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct BybitBookLevel {
+    pub price: Price,
+    pub amount: Amount,
+}
+
+/// Payload of both `orderbook.{depth}.{symbol}` topic and `/v5/market/orderbook` endpoint
+#[derive(Debug, Deserialize, Clone)]
+pub struct BybitOrderBook {
+    #[serde(rename = "s")]
+    pub symbol: CompactString,
+    #[serde(rename = "b")]
+    pub bids: Vec<BybitBookLevel>,
+    #[serde(rename = "a")]
+    pub asks: Vec<BybitBookLevel>,
+    /// Update id, consecutive within a topic. `1` means a snapshot after the restart of the Bybit service
+    #[serde(rename = "u")]
+    pub update_id: u64,
+    /// Cross sequence, comparable between the depths of the same symbol
+    pub seq: u64,
+}
+
+impl BybitOrderBook {
+    pub fn to_internal_snapshot(&self, symbol: Symbol, exchange_time: Option<u64>) -> L2Snapshot {
+        let to_lot = |l: &BybitBookLevel| SingleLot { price: l.price, amount: l.amount };
+        L2Snapshot {
+            exchange_time,
+            sequence_no: Some(self.update_id),
+            exchange: Exchange::Bybit,
+            instrument: MdInstrument::Symbol(symbol),
+            bids: self.bids.iter().map(to_lot).collect(),
+            asks: self.asks.iter().map(to_lot).collect(),
+        }
+    }
+
+    /// One increment per changed level, the amounts are absolute and zero removes the level
+    pub fn to_md(&self, symbol: &Symbol, exchange_time: u64) -> Vec<MdMessage> {
+        let total = self.bids.len() + self.asks.len();
+        self.bids
+            .iter()
+            .map(|l| (Side::Bid, l))
+            .chain(self.asks.iter().map(|l| (Side::Ask, l)))
+            .enumerate()
+            .map(|(i, (side, level))| MdMessage::L2Increment(L2Increment {
+                exchange_time: Some(exchange_time),
+                sequence_no: Some(self.update_id),
+                exchange: Exchange::Bybit,
+                instrument: MdInstrument::Symbol(symbol.clone()),
+                side,
+                price: level.price,
+                amount: level.amount,
+                is_eot: i + 1 == total,
+            }))
+            .collect()
+    }
+}
@@ -0,0 +1,34 @@
+use compact_str::CompactString;
+use serde::Deserialize;
+
+use crate::gates::bybit::common::error::BybitErrorResponse;
+
+/// Bybit wraps the data into `{"retCode": 0, "retMsg": "OK", "result": {...}}`,
+/// failed requests come with an empty `result`
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum BybitResponse<R> {
+    Data(BybitDataResponse<R>),
+    Error(BybitErrorResponse),
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BybitDataResponse<R> {
+    ret_code: i64,
+    ret_msg: CompactString,
+    result: R,
+}
+
+impl<R> BybitResponse<R> {
+    pub fn into_result(self) -> Result<R, BybitErrorResponse> {
+        match self {
+            BybitResponse::Data(data) if data.ret_code == 0 => Ok(data.result),
+            BybitResponse::Data(data) => Err(BybitErrorResponse {
+                ret_code: data.ret_code,
+                ret_msg: data.ret_msg,
+            }),
+            BybitResponse::Error(err) => Err(err),
+        }
+    }
+}
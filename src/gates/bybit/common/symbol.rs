@@ -0,0 +1,122 @@
+use std::str::FromStr;
+
+use compact_str::{CompactString, format_compact};
+use eyre::eyre;
+
+use crate::gates::bybit::common::model::BybitCategory;
+use crate::model::symbol::{Expiry, format_strike, OptionType, Symbol, SymbolKind, SymbolMapping};
+use crate::utils::basic_types::Price;
+
+/// Bybit symbols: `BTCUSDT` for spot and USDT perpetuals, `BTCPERP` for USDC perpetuals,
+/// `BTCUSD` for inverse perpetuals, `BTCUSDT-29DEC23` and `BTC-29DEC23` for USDT and USDC futures,
+/// `BTC-29DEC23-30000-C` for options.
+///
+/// Spot and linear perpetuals share the symbol, `from_native` maps such symbols to spot,
+/// `from_native_in` tells them apart by the category.
+/// Inverse futures, e.g. `BTCUSDZ23`, aren't supported since their symbol doesn't contain the expiry day.
+pub struct BybitSymbols;
+
+/// Quote currencies of Bybit pairs, the longer ones first so that e.g. `USDT` wins over `USD`
+const QUOTE_CURRENCIES: [&str; 9] = ["USDT", "USDC", "USDE", "EUR", "BRL", "BTC", "ETH", "DAI", "USD"];
+
+impl BybitSymbols {
+    /// Indices aren't mapped by `to_native`, their category is spot
+    pub fn category(symbol: &Symbol) -> BybitCategory {
+        match symbol.kind {
+            SymbolKind::Spot | SymbolKind::Index => BybitCategory::Spot,
+            SymbolKind::Perpetual | SymbolKind::Future { .. } if symbol.quote == "USD" => BybitCategory::Inverse,
+            SymbolKind::Perpetual | SymbolKind::Future { .. } => BybitCategory::Linear,
+            SymbolKind::Option { .. } => BybitCategory::Option,
+        }
+    }
+
+    pub fn from_native_in(category: BybitCategory, native: &str) -> eyre::Result<Symbol> {
+        let symbol = Self::from_native(native)?;
+        match (category, &symbol.kind) {
+            (BybitCategory::Spot, SymbolKind::Spot) => Ok(symbol),
+            (BybitCategory::Linear | BybitCategory::Inverse, SymbolKind::Spot) => {
+                Ok(Symbol::perpetual(symbol.base, symbol.quote))
+            }
+            _ if Self::category(&symbol) == category => Ok(symbol),
+            _ => Err(eyre!("Bybit symbol {native} doesn't belong to {} category", category.as_str())),
+        }
+    }
+}
+
+impl SymbolMapping for BybitSymbols {
+    fn to_native(symbol: &Symbol) -> eyre::Result<CompactString> {
+        let Symbol { base, quote, kind } = symbol;
+        let native = match kind {
+            SymbolKind::Spot => format_compact!("{base}{quote}"),
+            SymbolKind::Perpetual if quote == "USDC" => format_compact!("{base}PERP"),
+            SymbolKind::Perpetual => format_compact!("{base}{quote}"),
+            SymbolKind::Future { expiry } if quote == "USDC" => format_compact!("{base}-{}", expiry.to_dmmmyy()),
+            SymbolKind::Future { expiry } if quote == "USDT" => format_compact!("{base}USDT-{}", expiry.to_dmmmyy()),
+            SymbolKind::Future { .. } => return Err(eyre!("Bybit inverse futures aren't supported: {symbol}")),
+            SymbolKind::Index => return Err(eyre!("Bybit has no instrument for index {symbol}")),
+            SymbolKind::Option { expiry, strike, option_type } => format_compact!(
+                "{base}-{}-{}-{}",
+                expiry.to_dmmmyy(),
+                format_strike(*strike),
+                option_type.letter(),
+            ),
+        };
+        Ok(native)
+    }
+
+    fn from_native(native: &str) -> eyre::Result<Symbol> {
+        let parts: Vec<_> = native.split('-').collect();
+        match parts[..] {
+            [pair] => match pair.strip_suffix("PERP") {
+                Some(base) if !base.is_empty() => Ok(Symbol::perpetual(base, "USDC")),
+                _ => {
+                    let (base, quote) = split_pair(pair)?;
+                    Ok(Symbol::spot(base, quote))
+                }
+            },
+            [pair, expiry] => match pair.strip_suffix("USDT") {
+                Some(base) if !base.is_empty() => Ok(Symbol::future(base, "USDT", Expiry::from_dmmmyy(expiry)?)),
+                _ => Ok(Symbol::future(pair, "USDC", Expiry::from_dmmmyy(expiry)?)),
+            },
+            [base, expiry, strike, option_type] => Ok(Symbol::option(
+                base,
+                "USDC",
+                Expiry::from_dmmmyy(expiry)?,
+                Price::from_str(strike).map_err(|_| eyre!("non-decimal strike in Bybit symbol {native}"))?,
+                OptionType::from_letter(option_type)?,
+            )),
+            _ => Err(eyre!("unknown Bybit symbol format {native}")),
+        }
+    }
+}
+
+fn split_pair(pair: &str) -> eyre::Result<(&str, &str)> {
+    QUOTE_CURRENCIES
+        .iter()
+        .find_map(|quote| pair.strip_suffix(quote).filter(|base| !base.is_empty()).map(|base| (base, *quote)))
+        .ok_or_else(|| eyre!("unknown quote currency of Bybit symbol {pair}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::gates::bybit::common::model::BybitCategory;
+    use crate::gates::bybit::common::symbol::BybitSymbols;
+    use crate::model::symbol::{Symbol, SymbolMapping};
+
+    #[test]
+    fn symbol_mapping() {
+        let natives = ["BTCUSDT", "BTCPERP", "BTCUSDT-29DEC23", "BTC-29DEC23", "BTC-5JAN24-45000-C"];
+        for native in natives {
+            let symbol = BybitSymbols::from_native(native).unwrap();
+            assert_eq!(BybitSymbols::to_native(&symbol).unwrap(), native);
+        }
+        assert_eq!(BybitSymbols::from_native("BTC-5JAN24-45000-C").unwrap().to_string(), "BTC/USDC-20240105-45000-C");
+
+        let perpetual = BybitSymbols::from_native_in(BybitCategory::Linear, "BTCUSDT").unwrap();
+        assert_eq!(perpetual, Symbol::perpetual("BTC", "USDT"));
+        assert_eq!(BybitSymbols::category(&perpetual), BybitCategory::Linear);
+        let inverse = BybitSymbols::from_native_in(BybitCategory::Inverse, "BTCUSD").unwrap();
+        assert_eq!(BybitSymbols::category(&inverse), BybitCategory::Inverse);
+        assert!(BybitSymbols::from_native_in(BybitCategory::Spot, "BTC-29DEC23").is_err());
+    }
+}
@@ -0,0 +1,15 @@
+use compact_str::CompactString;
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct BybitPollerConfig {
+    pub http_url: CompactString,
+}
+
+impl Default for BybitPollerConfig {
+    fn default() -> Self {
+        Self {
+            http_url: "https://api.bybit.com".into(),
+        }
+    }
+}
@@ -0,0 +1,16 @@
+use http::Method;
+
+use crate::api::endpoint::Endpoint;
+use crate::gates::bybit::common::model::BybitOrderBook;
+use crate::gates::bybit::common::response::BybitResponse;
+use crate::gates::bybit::crawler::request::GetOrderBookRequest;
+
+pub struct GetOrderBook;
+
+impl Endpoint for GetOrderBook {
+    type Request = GetOrderBookRequest;
+    type Response = BybitResponse<BybitOrderBook>;
+
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "/v5/market/orderbook";
+}
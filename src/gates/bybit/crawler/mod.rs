@@ -0,0 +1,4 @@
+pub mod config;
+pub mod endpoints;
+pub mod poller;
+pub mod request;
@@ -0,0 +1,92 @@
+use async_trait::async_trait;
+
+use crate::api::api;
+use crate::api::poller::ExchangePoller;
+use crate::gates::bybit::common::model::BybitOrderBook;
+use crate::gates::bybit::common::symbol::BybitSymbols;
+use crate::gates::bybit::crawler::config::BybitPollerConfig;
+use crate::gates::bybit::crawler::endpoints::GetOrderBook;
+use crate::gates::bybit::crawler::request::GetOrderBookRequest;
+use crate::model::order_book::OrderBook;
+use crate::model::symbol::{Symbol, SymbolMapping};
+
+#[derive(Debug, Default)]
+pub struct BybitExchangePoller {
+    pub config: BybitPollerConfig,
+}
+
+#[async_trait]
+impl ExchangePoller for BybitExchangePoller {
+    /// Order book of the maximum depth available for the category
+    async fn get_order_book(&self, symbol: &Symbol) -> eyre::Result<OrderBook> {
+        let depth = BybitSymbols::category(symbol).max_order_book_depth();
+        let ob = self.get_order_book_snapshot(symbol, Some(depth)).await?;
+
+        Ok(OrderBook {
+            bids: ob.bids.into_iter().map(|l| (l.price, l.amount)).collect(),
+            asks: ob.asks.into_iter().map(|l| (l.price, l.amount)).collect(),
+        })
+    }
+}
+
+impl BybitExchangePoller {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn with_config(config: BybitPollerConfig) -> Self {
+        Self { config }
+    }
+
+    pub async fn get_order_book_snapshot(&self, symbol: &Symbol, limit: Option<u16>) -> eyre::Result<BybitOrderBook> {
+        let request = GetOrderBookRequest::new(BybitSymbols::category(symbol), BybitSymbols::to_native(symbol)?, limit);
+
+        let response = api::http_urlencoded_query_request::<GetOrderBook>(
+            &self.config.http_url,
+            &request,
+            Default::default(),
+        ).await?;
+
+        Ok(response.into_result()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::gates::bybit::common::model::BybitOrderBook;
+    use crate::gates::bybit::common::response::BybitResponse;
+
+    #[test]
+    fn order_book_parsing() {
+        let ob_str = fs::read_to_string("tests/bybit_order_book.json").unwrap();
+        let response: BybitResponse<BybitOrderBook> = serde_json::from_str(&ob_str).unwrap();
+        let ob = response.into_result().unwrap();
+        assert_eq!(ob.symbol, "BTCUSDT");
+        assert_eq!(ob.update_id, 230704);
+        assert_eq!((ob.bids.len(), ob.asks.len()), (2, 1));
+
+        let error_str = r#"{"retCode":10001,"retMsg":"Not supported symbols","result":{},"retExtInfo":{},"time":1716863719031}"#;
+        let response: BybitResponse<BybitOrderBook> = serde_json::from_str(error_str).unwrap();
+        assert_eq!(response.into_result().unwrap_err().ret_code, 10001);
+    }
+}
+
+#[cfg(test)]
+mod api_tests {
+    use crate::api::poller::ExchangePoller;
+    use crate::gates::bybit::crawler::poller::BybitExchangePoller;
+    use crate::model::symbol::Symbol;
+
+    #[ignore]
+    #[tokio::test]
+    async fn get_ob_test() {
+        let poller = BybitExchangePoller::new();
+        for symbol in [Symbol::spot("BTC", "USDT"), Symbol::perpetual("BTC", "USDT")] {
+            let ob = poller.get_order_book(&symbol).await.unwrap();
+            assert_ne!(ob.bids.len(), 0);
+            assert_ne!(ob.asks.len(), 0);
+        }
+    }
+}
@@ -0,0 +1,23 @@
+use compact_str::CompactString;
+use serde::Serialize;
+
+use crate::gates::bybit::common::model::BybitCategory;
+
+#[derive(Debug, Serialize, Clone)]
+pub struct GetOrderBookRequest {
+    category: BybitCategory,
+    /// Symbol, e.g. BTCUSDT
+    symbol: CompactString,
+    /// Depth per side: 1-200 for spot, 1-500 for linear and inverse, 1-25 for option
+    limit: Option<u16>,
+}
+
+impl GetOrderBookRequest {
+    pub fn new(category: BybitCategory, symbol: CompactString, limit: Option<u16>) -> Self {
+        Self {
+            category,
+            symbol,
+            limit,
+        }
+    }
+}
@@ -0,0 +1,43 @@
+use compact_str::{CompactString, format_compact};
+use serde::Deserialize;
+
+use crate::api::backoff::BackoffConfig;
+use crate::gates::bybit::common::model::BybitCategory;
+use crate::gates::bybit::md::stream::BybitTopic;
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct BybitMdConnectionConfig {
+    /// Base of the public websocket url, the category is appended to it
+    pub ws_base_url: CompactString,
+    /// Every symbol of the connection has to belong to this category
+    pub category: BybitCategory,
+    pub topics: Vec<BybitTopic>,
+    /// Send `{"op":"ping"}` every this amount of seconds regardless of the traffic, Bybit recommends 20
+    pub ping_interval_seconds: u64,
+    /// Reconnect if no message arrives within this amount of seconds after the ping was sent
+    pub pong_timeout_seconds: u64,
+    pub subscribe_interval_ms: u64,
+    /// Retry policy for connecting, reconnecting and resubscribing
+    #[serde(default)]
+    pub reconnect: BackoffConfig,
+}
+
+impl BybitMdConnectionConfig {
+    pub fn ws_url(&self) -> CompactString {
+        format_compact!("{}/{}", self.ws_base_url, self.category.as_str())
+    }
+}
+
+impl Default for BybitMdConnectionConfig {
+    fn default() -> Self {
+        Self {
+            ws_base_url: "wss://stream.bybit.com/v5/public".into(),
+            category: BybitCategory::Spot,
+            topics: vec![BybitTopic::OrderBook { depth: 50 }, BybitTopic::PublicTrade],
+            ping_interval_seconds: 20,
+            pong_timeout_seconds: 10,
+            subscribe_interval_ms: 100,
+            reconnect: BackoffConfig::default(),
+        }
+    }
+}
@@ -0,0 +1,196 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use compact_str::CompactString;
+use eyre::{eyre, Result};
+use log::{error, trace, warn};
+
+use crate::api::connection::MdConnection;
+use crate::api::heartbeat::Heartbeat;
+use crate::api::ws::{WebSocket, WsEvent};
+use crate::gates::bybit::common::model::BybitOrderBook;
+use crate::gates::bybit::common::symbol::BybitSymbols;
+use crate::gates::bybit::md::config::BybitMdConnectionConfig;
+use crate::gates::bybit::md::model::{BybitDataType, BybitOpResponse, BybitTopicMessage, BybitTrade, BybitWsMessage, BybitWsRequest};
+use crate::gates::bybit::md::stream::{BybitStream, BybitTopic};
+use crate::model::exchange::Exchange;
+use crate::model::internal::{BookInvalid, BookInvalidReason, MdInstrument, MdMessage};
+use crate::model::symbol::{Symbol, SymbolMapping};
+
+/// Order books and public trades of Bybit symbols of a single category.
+///
+/// Every `orderbook` topic starts with a `snapshot`, the following `delta` messages must continue its update id.
+/// A gap emits `BookInvalid` and resubscribes to the topic, the deltas are dropped until the new snapshot arrives.
+pub struct BybitMdConnection {
+    ws: WebSocket<BybitStream, BybitWsMessage>,
+    queue: VecDeque<MdMessage>,
+    /// Canonical symbols of the subscribed Bybit symbols
+    symbols: HashMap<CompactString, Symbol>,
+    /// Update id of the last applied message per `orderbook` topic
+    last_update_ids: HashMap<CompactString, u64>,
+    /// `orderbook` topics waiting for a snapshot after resubscribing
+    resyncing: HashSet<CompactString>,
+    heartbeat: Heartbeat,
+}
+
+impl BybitMdConnection {
+    pub async fn new(symbols: Vec<Symbol>, config: BybitMdConnectionConfig) -> Result<Self> {
+        if let Some(symbol) = symbols.iter().find(|s| BybitSymbols::category(s) != config.category) {
+            return Err(eyre!("{symbol} doesn't belong to Bybit {} category", config.category.as_str()));
+        }
+        let natives = symbols.iter().map(BybitSymbols::to_native).collect::<Result<Vec<_>>>()?;
+        let stream = BybitStream { symbols: natives.clone(), topics: config.topics.clone(), category: config.category };
+        let ws = WebSocket::connect_and_subscribe(
+            &config.ws_url(),
+            stream,
+            config.subscribe_interval_ms,
+            config.reconnect.clone(),
+        ).await?;
+
+        Ok(Self {
+            ws,
+            queue: VecDeque::new(),
+            symbols: natives.into_iter().zip(symbols).collect(),
+            last_update_ids: HashMap::new(),
+            resyncing: HashSet::new(),
+            heartbeat: Heartbeat::fixed_interval(
+                Duration::from_secs(config.ping_interval_seconds),
+                Duration::from_secs(config.pong_timeout_seconds),
+            ),
+        })
+    }
+
+    fn symbol(&self, topic: &str) -> Option<Symbol> {
+        let symbol = BybitTopic::symbol(topic).and_then(|native| self.symbols.get(native)).cloned();
+        if symbol.is_none() {
+            warn!("received Bybit message of unknown topic {topic}");
+        }
+        symbol
+    }
+
+    async fn on_order_book(&mut self, message: BybitTopicMessage<BybitOrderBook>) -> Result<()> {
+        let Some(symbol) = self.symbol(&message.topic) else {
+            return Ok(());
+        };
+        let topic = message.topic;
+        let book = message.data;
+        let data_type = match message.data_type {
+            // Bybit sends update id 1 after a restart of its service, the data is a full snapshot
+            BybitDataType::Delta if book.update_id == 1 => BybitDataType::Snapshot,
+            data_type => data_type,
+        };
+        match data_type {
+            BybitDataType::Snapshot => {
+                self.resyncing.remove(&topic);
+                self.last_update_ids.insert(topic, book.update_id);
+                let snapshot = book.to_internal_snapshot(symbol, Some(message.ts));
+                self.queue.push_back(MdMessage::L2Snapshot(snapshot));
+            }
+            BybitDataType::Delta => {
+                if self.resyncing.contains(&topic) {
+                    return Ok(());
+                }
+                let expected = self.last_update_ids.get(&topic).map(|id| id + 1);
+                if expected != Some(book.update_id) {
+                    warn!("Bybit update id gap for {topic}: expected {expected:?}, received {}; resubscribing", book.update_id);
+                    self.last_update_ids.remove(&topic);
+                    self.queue.push_back(MdMessage::BookInvalid(BookInvalid {
+                        exchange_time: Some(message.ts),
+                        sequence_no: Some(book.update_id),
+                        exchange: Exchange::Bybit,
+                        instrument: MdInstrument::Symbol(symbol),
+                        reason: BookInvalidReason::SequenceGap { expected, received: book.update_id },
+                    }));
+                    return self.resubscribe(topic).await;
+                }
+                self.last_update_ids.insert(topic, book.update_id);
+                self.queue.extend(book.to_md(&symbol, message.ts));
+            }
+        }
+        Ok(())
+    }
+
+    fn on_trades(&mut self, message: BybitTopicMessage<Vec<BybitTrade>>) {
+        let Some(symbol) = self.symbol(&message.topic) else {
+            return;
+        };
+        self.queue.extend(message.data.iter().map(|t| MdMessage::Trade(t.to_internal(symbol.clone()))));
+    }
+
+    fn on_op_response(&self, response: BybitOpResponse) {
+        match response.success {
+            Some(false) => warn!("Bybit {} request failed: {:?}", response.op, response.ret_msg),
+            _ if response.is_pong() => {}
+            _ => trace!("Bybit {} request succeeded", response.op),
+        }
+    }
+
+    /// Unsubscribes from the topic and subscribes back, so that Bybit sends a fresh snapshot
+    async fn resubscribe(&mut self, topic: CompactString) -> Result<()> {
+        self.resyncing.insert(topic.clone());
+        self.ws.send(&BybitWsRequest::new_unsubscribe(vec![topic.clone()])).await?;
+        self.ws.send(&BybitWsRequest::new_subscribe(vec![topic])).await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl MdConnection for BybitMdConnection {
+    /// Bybit closes the connection without a ping for 10 minutes, the recommended interval is 20 seconds.
+    ///
+    /// https://bybit-exchange.github.io/docs/v5/ws/connect#how-to-send-the-heartbeat-packet
+    async fn next(&mut self) -> Result<MdMessage> {
+        while self.queue.is_empty() {
+            match self.ws.next_event(&mut self.heartbeat).await? {
+                WsEvent::Message(message) => match message {
+                    BybitWsMessage::OrderBook(message) => self.on_order_book(message).await?,
+                    BybitWsMessage::Trades(message) => self.on_trades(message),
+                    BybitWsMessage::OpResponse(response) => self.on_op_response(response),
+                    BybitWsMessage::Pong => {}
+                },
+                WsEvent::Ping => {
+                    if let Err(err) = self.ws.send(&BybitWsRequest::ping()).await {
+                        error!("Failed to send ping to Bybit MD stream, {err}");
+                    }
+                }
+                WsEvent::Reconnected => self.resyncing.clear(),
+            }
+        }
+        let update = self.queue.pop_front().expect("should be some");
+        Ok(update)
+    }
+}
+
+#[cfg(test)]
+mod md_integration_tests {
+    use crate::api::connection::MdConnection;
+    use crate::gates::bybit::common::model::BybitCategory;
+    use crate::gates::bybit::md::config::BybitMdConnectionConfig;
+    use crate::gates::bybit::md::connection::BybitMdConnection;
+    use crate::model::internal::MdMessage;
+    use crate::model::storage::Storage;
+    use crate::model::symbol::Symbol;
+
+    #[ignore]
+    #[tokio::test]
+    async fn ob_test() {
+        let mut storage = Storage::new();
+
+        let config = BybitMdConnectionConfig { category: BybitCategory::Linear, ..Default::default() };
+        let mut connection = BybitMdConnection::new(
+            vec![Symbol::perpetual("BTC", "USDT"), Symbol::perpetual("ETH", "USDT")],
+            config,
+        ).await.unwrap();
+
+        let mut snapshots = 0;
+        for _ in 0..1000 {
+            let m = connection.next().await.unwrap();
+            if matches!(m, MdMessage::L2Snapshot(_)) {
+                snapshots += 1;
+            }
+            storage.on_ws_update(m);
+        }
+        assert!(snapshots >= 2);
+    }
+}
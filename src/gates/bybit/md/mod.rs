@@ -0,0 +1,4 @@
+pub mod config;
+pub mod connection;
+pub mod model;
+pub mod stream;
@@ -0,0 +1,188 @@
+use compact_str::CompactString;
+use serde::{Deserialize, Serialize};
+
+use crate::api::connection::WsMessage;
+use crate::gates::bybit::common::model::BybitOrderBook;
+use crate::model::exchange::Exchange;
+use crate::model::internal::{MdInstrument, Side, Trade};
+use crate::model::symbol::Symbol;
+use crate::utils::basic_types::{Amount, Price};
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum BybitWsMessage {
+    OrderBook(BybitTopicMessage<BybitOrderBook>),
+    Trades(BybitTopicMessage<Vec<BybitTrade>>),
+    OpResponse(BybitOpResponse),
+    Pong,
+}
+
+impl WsMessage for BybitWsMessage {
+    fn pong() -> Self {
+        Self::Pong
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BybitTopicMessage<D> {
+    pub topic: CompactString,
+    #[serde(rename = "type")]
+    pub data_type: BybitDataType,
+    /// Time the message was generated by Bybit, Unix timestamp format in milliseconds
+    pub ts: u64,
+    pub data: D,
+}
+
+#[derive(Debug, Deserialize, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BybitDataType {
+    Snapshot,
+    Delta,
+}
+
+/// Response to `subscribe`, `unsubscribe` and `ping` requests.
+/// The pong of the spot url comes as `{"op":"ping","ret_msg":"pong",...}`, of the other urls as `{"op":"pong",...}`
+#[derive(Debug, Deserialize)]
+pub struct BybitOpResponse {
+    pub op: CompactString,
+    pub success: Option<bool>,
+    pub ret_msg: Option<CompactString>,
+    pub req_id: Option<CompactString>,
+}
+
+impl BybitOpResponse {
+    pub fn is_pong(&self) -> bool {
+        self.op == "pong" || self.ret_msg.as_deref() == Some("pong")
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct BybitTrade {
+    /// Trade time, Unix timestamp format in milliseconds
+    #[serde(rename = "T")]
+    pub ts: u64,
+    #[serde(rename = "s")]
+    pub symbol: CompactString,
+    /// Side of the taker
+    #[serde(rename = "S")]
+    pub side: BybitSide,
+    #[serde(rename = "v")]
+    pub amount: Amount,
+    #[serde(rename = "p")]
+    pub price: Price,
+    #[serde(rename = "i")]
+    pub trade_id: CompactString,
+}
+
+#[derive(Debug, Deserialize, Copy, Clone, PartialEq, Eq)]
+pub enum BybitSide {
+    Buy,
+    Sell,
+}
+
+impl BybitTrade {
+    pub fn to_internal(&self, symbol: Symbol) -> Trade {
+        Trade {
+            exchange_time: Some(self.ts),
+            exchange: Exchange::Bybit,
+            instrument: MdInstrument::Symbol(symbol),
+            trade_id: self.trade_id.clone(),
+            price: self.price,
+            amount: self.amount,
+            side: match self.side {
+                BybitSide::Buy => Side::Bid,
+                BybitSide::Sell => Side::Ask,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct BybitWsRequest {
+    op: &'static str,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    args: Vec<CompactString>,
+}
+
+impl BybitWsRequest {
+    pub fn new_subscribe(topics: Vec<CompactString>) -> Self {
+        Self { op: "subscribe", args: topics }
+    }
+
+    pub fn new_unsubscribe(topics: Vec<CompactString>) -> Self {
+        Self { op: "unsubscribe", args: topics }
+    }
+
+    pub fn ping() -> Self {
+        Self { op: "ping", args: Vec::new() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::gates::bybit::md::model::{BybitDataType, BybitWsMessage, BybitWsRequest};
+    use crate::model::internal::{MdMessage, Side};
+    use crate::model::symbol::Symbol;
+
+    #[test]
+    fn order_book_parsing() {
+        let symbol = Symbol::perpetual("BTC", "USDT");
+        for (file, data_type, update_id) in [
+            ("tests/bybit_ws_order_book_snapshot.json", BybitDataType::Snapshot, 18521288),
+            ("tests/bybit_ws_order_book_delta.json", BybitDataType::Delta, 18521289),
+        ] {
+            let message: BybitWsMessage = serde_json::from_str(&fs::read_to_string(file).unwrap()).unwrap();
+            let BybitWsMessage::OrderBook(message) = message else {
+                panic!("expected order book, got {message:?}");
+            };
+            assert_eq!(message.topic, "orderbook.50.BTCUSDT");
+            assert_eq!(message.data_type, data_type);
+            assert_eq!(message.data.update_id, update_id);
+        }
+
+        let delta_str = fs::read_to_string("tests/bybit_ws_order_book_delta.json").unwrap();
+        let BybitWsMessage::OrderBook(delta) = serde_json::from_str(&delta_str).unwrap() else {
+            panic!("expected order book");
+        };
+        let increments = delta.data.to_md(&symbol, delta.ts);
+        assert_eq!(increments.len(), 3);
+        let MdMessage::L2Increment(last) = increments.last().unwrap() else {
+            panic!("expected increment");
+        };
+        assert_eq!(last.side, Side::Ask);
+        assert_eq!(last.amount, Default::default());
+        assert!(last.is_eot);
+    }
+
+    #[test]
+    fn trades_parsing() {
+        let trades_str = fs::read_to_string("tests/bybit_ws_public_trade.json").unwrap();
+        let message: BybitWsMessage = serde_json::from_str(&trades_str).unwrap();
+        let BybitWsMessage::Trades(message) = message else {
+            panic!("expected trades, got {message:?}");
+        };
+        assert_eq!(message.data.len(), 2);
+        let trade = message.data[1].to_internal(Symbol::spot("BTC", "USDT"));
+        assert_eq!(trade.side, Side::Ask);
+        assert_eq!(trade.trade_id, "2290000000061666328");
+        assert_eq!(trade.exchange_time, Some(1672304486865));
+    }
+
+    #[test]
+    fn op_response_parsing() {
+        let spot_pong = r#"{"success":true,"ret_msg":"pong","conn_id":"0970e817-426e-429a-a679-ff7f55e0b16a","op":"ping"}"#;
+        let linear_pong = r#"{"req_id":"100001","op":"pong","args":["1675418560633"],"conn_id":"cfcb4ocsvfriu23r3er0-1b"}"#;
+        for pong in [spot_pong, linear_pong] {
+            let message: BybitWsMessage = serde_json::from_str(pong).unwrap();
+            assert!(matches!(message, BybitWsMessage::OpResponse(r) if r.is_pong()));
+        }
+
+        let error_str = r#"{"success":false,"ret_msg":"error:handler not found,topic:orderbook.1.BTCUSDTX","conn_id":"2324d924","req_id":"","op":"subscribe"}"#;
+        let message: BybitWsMessage = serde_json::from_str(error_str).unwrap();
+        assert!(matches!(message, BybitWsMessage::OpResponse(r) if r.success == Some(false) && !r.is_pong()));
+
+        assert_eq!(serde_json::to_string(&BybitWsRequest::ping()).unwrap(), r#"{"op":"ping"}"#);
+    }
+}
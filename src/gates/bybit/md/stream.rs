@@ -0,0 +1,70 @@
+use compact_str::{CompactString, format_compact};
+use serde::Deserialize;
+
+use crate::gates::bybit::common::model::BybitCategory;
+use crate::gates::bybit::md::model::BybitWsRequest;
+use crate::model::stream::WsStream;
+
+/// Bybit accepts at most 10 args per subscribe request on the spot url
+const MAX_ARGS_PER_REQUEST: usize = 10;
+
+#[derive(Clone)]
+pub struct BybitStream {
+    /// Native symbols, e.g. BTCUSDT
+    pub symbols: Vec<CompactString>,
+    pub topics: Vec<BybitTopic>,
+    pub category: BybitCategory,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize)]
+pub enum BybitTopic {
+    /// orderbook.{depth}.{symbol}: `snapshot` on subscription followed by `delta` messages.
+    /// Supported depths differ by category, e.g. 1, 50, 200 for spot.
+    ///
+    /// https://bybit-exchange.github.io/docs/v5/websocket/public/orderbook
+    OrderBook { depth: u16 },
+    /// publicTrade.{symbol}: public trades, one message may contain several trades.
+    ///
+    /// https://bybit-exchange.github.io/docs/v5/websocket/public/trade
+    PublicTrade,
+}
+
+impl BybitTopic {
+    pub fn topic(&self, symbol: &str) -> CompactString {
+        match self {
+            BybitTopic::OrderBook { depth } => format_compact!("orderbook.{depth}.{symbol}"),
+            BybitTopic::PublicTrade => format_compact!("publicTrade.{symbol}"),
+        }
+    }
+
+    /// Native symbol the topic belongs to, e.g. `BTCUSDT` of `orderbook.50.BTCUSDT`
+    pub fn symbol(topic: &str) -> Option<&str> {
+        topic.rsplit_once('.').map(|(_, symbol)| symbol)
+    }
+}
+
+impl BybitStream {
+    pub fn topics(&self, symbols: &[CompactString]) -> Vec<CompactString> {
+        symbols
+            .iter()
+            .flat_map(|s| self.topics.iter().map(move |t| t.topic(s)))
+            .collect()
+    }
+}
+
+impl WsStream for BybitStream {
+    type Kind = BybitCategory;
+    type Subscribe = BybitWsRequest;
+    type Login = ();
+
+    fn kind(&self) -> Self::Kind {
+        self.category
+    }
+
+    fn subscribe_requests(&self) -> Vec<Self::Subscribe> {
+        self.topics(&self.symbols)
+            .chunks(MAX_ARGS_PER_REQUEST)
+            .map(|args| BybitWsRequest::new_subscribe(args.to_vec()))
+            .collect()
+    }
+}
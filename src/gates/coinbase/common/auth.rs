@@ -0,0 +1,94 @@
+use std::fmt::{Debug, Formatter};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use compact_str::{CompactString, ToCompactString};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+/// Path signed on websocket subscription
+const WS_SUBSCRIBE_PATH: &str = "/users/self/verify";
+
+#[derive(Deserialize, Clone)]
+pub struct CoinbaseCredentials {
+    pub api_key: CompactString,
+    /// Base64 encoded secret, as shown on the API key creation
+    pub secret: CompactString,
+    pub passphrase: CompactString,
+}
+
+impl Debug for CoinbaseCredentials {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CoinbaseCredentials")
+            .field("api_key", &self.api_key)
+            .finish_non_exhaustive()
+    }
+}
+
+impl CoinbaseCredentials {
+    /// Base64 encoded HMAC SHA256 of `timestamp + method + request_path + body` keyed by the base64 decoded secret
+    ///
+    /// https://docs.cdp.coinbase.com/exchange/docs/rest-auth#signing-a-message
+    pub fn sign(&self, timestamp: &str, method: &str, request_path: &str, body: &str) -> eyre::Result<String> {
+        let secret = STANDARD.decode(self.secret.as_bytes())?;
+        let mut mac = Hmac::<Sha256>::new_from_slice(&secret).expect("HMAC accepts keys of any size");
+        mac.update(timestamp.as_bytes());
+        mac.update(method.as_bytes());
+        mac.update(request_path.as_bytes());
+        mac.update(body.as_bytes());
+        Ok(STANDARD.encode(mac.finalize().into_bytes()))
+    }
+
+    /// Authentication fields of the websocket `subscribe` message, signed with the current time
+    ///
+    /// https://docs.cdp.coinbase.com/exchange/docs/websocket-auth
+    pub fn subscribe_auth(&self) -> eyre::Result<CoinbaseWsAuth> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs()
+            .to_compact_string();
+        self.subscribe_auth_at(timestamp)
+    }
+
+    fn subscribe_auth_at(&self, timestamp: CompactString) -> eyre::Result<CoinbaseWsAuth> {
+        let signature = self.sign(&timestamp, "GET", WS_SUBSCRIBE_PATH, "")?.into();
+        Ok(CoinbaseWsAuth {
+            key: self.api_key.clone(),
+            passphrase: self.passphrase.clone(),
+            timestamp,
+            signature,
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct CoinbaseWsAuth {
+    key: CompactString,
+    passphrase: CompactString,
+    /// Unix timestamp in seconds
+    timestamp: CompactString,
+    signature: CompactString,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::gates::coinbase::common::auth::CoinbaseCredentials;
+
+    #[test]
+    fn subscribe_auth_signature() {
+        let credentials = CoinbaseCredentials {
+            api_key: "key".into(),
+            secret: "c2VjcmV0".into(),
+            passphrase: "passphrase".into(),
+        };
+        let auth = credentials.subscribe_auth_at("1565815347".into()).unwrap();
+        assert_eq!(auth.signature, credentials.sign("1565815347", "GET", "/users/self/verify", "").unwrap());
+        assert_eq!(auth.signature.len(), 44);
+
+        let invalid = CoinbaseCredentials { secret: "not base64!".into(), ..credentials };
+        assert!(invalid.subscribe_auth().is_err());
+    }
+}
@@ -0,0 +1,40 @@
+use compact_str::{CompactString, format_compact};
+use eyre::eyre;
+
+use crate::model::symbol::{Symbol, SymbolKind, SymbolMapping};
+
+/// Coinbase Exchange products are spot pairs separated by a dash, e.g. `BTC-USD`
+pub struct CoinbaseSymbols;
+
+impl SymbolMapping for CoinbaseSymbols {
+    fn to_native(symbol: &Symbol) -> eyre::Result<CompactString> {
+        match symbol.kind {
+            SymbolKind::Spot => Ok(format_compact!("{}-{}", symbol.base, symbol.quote)),
+            _ => Err(eyre!("Coinbase Exchange has no product for {symbol}")),
+        }
+    }
+
+    fn from_native(native: &str) -> eyre::Result<Symbol> {
+        match native.split_once('-') {
+            Some((base, quote)) if !base.is_empty() && !quote.is_empty() && !quote.contains('-') => {
+                Ok(Symbol::spot(base, quote))
+            }
+            _ => Err(eyre!("unknown Coinbase product format {native}")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::gates::coinbase::common::symbol::CoinbaseSymbols;
+    use crate::model::symbol::{Symbol, SymbolMapping};
+
+    #[test]
+    fn symbol_mapping() {
+        let symbol = CoinbaseSymbols::from_native("BTC-USD").unwrap();
+        assert_eq!(symbol, Symbol::spot("BTC", "USD"));
+        assert_eq!(CoinbaseSymbols::to_native(&symbol).unwrap(), "BTC-USD");
+        assert!(CoinbaseSymbols::from_native("BTCUSD").is_err());
+        assert!(CoinbaseSymbols::to_native(&Symbol::perpetual("BTC", "USD")).is_err());
+    }
+}
@@ -0,0 +1,35 @@
+use compact_str::CompactString;
+use serde::Deserialize;
+
+use crate::api::backoff::BackoffConfig;
+use crate::gates::coinbase::common::auth::CoinbaseCredentials;
+use crate::gates::coinbase::md::stream::CoinbaseChannel;
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct CoinbaseMdConnectionConfig {
+    pub ws_url: CompactString,
+    pub channel: CoinbaseChannel,
+    /// Required by the `level2` channel
+    #[serde(default)]
+    pub credentials: Option<CoinbaseCredentials>,
+    /// Send the websocket ping if no message was received for this amount of seconds
+    pub idle_timeout_seconds: u64,
+    /// Reconnect if no message arrives within this amount of seconds after the ping was sent
+    pub pong_timeout_seconds: u64,
+    /// Retry policy for connecting, reconnecting and resubscribing
+    #[serde(default)]
+    pub reconnect: BackoffConfig,
+}
+
+impl Default for CoinbaseMdConnectionConfig {
+    fn default() -> Self {
+        Self {
+            ws_url: "wss://ws-feed.exchange.coinbase.com".into(),
+            channel: CoinbaseChannel::Level2Batch,
+            credentials: None,
+            idle_timeout_seconds: 20,
+            pong_timeout_seconds: 5,
+            reconnect: BackoffConfig::default(),
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use compact_str::CompactString;
+use eyre::{eyre, Result};
+use log::{error, trace, warn};
+
+use crate::api::connection::MdConnection;
+use crate::api::heartbeat::Heartbeat;
+use crate::api::ws::{WebSocket, WsEvent};
+use crate::gates::coinbase::common::symbol::CoinbaseSymbols;
+use crate::gates::coinbase::md::config::CoinbaseMdConnectionConfig;
+use crate::gates::coinbase::md::model::CoinbaseWsMessage;
+use crate::gates::coinbase::md::stream::CoinbaseStream;
+use crate::model::internal::MdMessage;
+use crate::model::symbol::{Symbol, SymbolMapping};
+
+/// Order books of Coinbase Exchange products from the `level2` or `level2_batch` channel.
+///
+/// The channel has no sequence numbers: the book is the `snapshot` sent on every (re)subscription
+/// with the absolute sizes of the following `l2update` messages applied on top of it.
+pub struct CoinbaseMdConnection {
+    ws: WebSocket<CoinbaseStream, CoinbaseWsMessage>,
+    queue: VecDeque<MdMessage>,
+    /// Canonical symbols of the subscribed Coinbase products
+    symbols: HashMap<CompactString, Symbol>,
+    heartbeat: Heartbeat,
+}
+
+impl CoinbaseMdConnection {
+    pub async fn new(symbols: Vec<Symbol>, config: CoinbaseMdConnectionConfig) -> Result<Self> {
+        let channel = config.channel;
+        match &config.credentials {
+            None if channel.requires_auth() => {
+                return Err(eyre!("Coinbase {} channel requires authentication, but no credentials were provided", channel.name()));
+            }
+            // fail early on a malformed secret, the subscription is signed again on every reconnect
+            Some(credentials) => {
+                credentials.subscribe_auth()?;
+            }
+            None => {}
+        }
+        let product_ids = symbols.iter().map(CoinbaseSymbols::to_native).collect::<Result<Vec<_>>>()?;
+        let stream = CoinbaseStream { product_ids: product_ids.clone(), channel, credentials: config.credentials };
+        let ws = WebSocket::connect_and_subscribe(
+            &config.ws_url,
+            stream,
+            0,
+            config.reconnect.clone(),
+        ).await?;
+
+        Ok(Self {
+            ws,
+            queue: VecDeque::new(),
+            symbols: product_ids.into_iter().zip(symbols).collect(),
+            heartbeat: Heartbeat::new(
+                Duration::from_secs(config.idle_timeout_seconds),
+                Duration::from_secs(config.pong_timeout_seconds),
+            ),
+        })
+    }
+
+    fn symbol(&self, product_id: &str) -> Option<&Symbol> {
+        let symbol = self.symbols.get(product_id);
+        if symbol.is_none() {
+            warn!("received Coinbase message of unknown product {product_id}");
+        }
+        symbol
+    }
+
+    fn on_message(&mut self, message: CoinbaseWsMessage) -> Result<()> {
+        match message {
+            CoinbaseWsMessage::Snapshot(snapshot) => {
+                if let Some(symbol) = self.symbol(&snapshot.product_id) {
+                    let snapshot = snapshot.to_internal(symbol.clone());
+                    self.queue.push_back(MdMessage::L2Snapshot(snapshot));
+                }
+            }
+            CoinbaseWsMessage::L2Update(update) => {
+                if let Some(symbol) = self.symbol(&update.product_id) {
+                    let increments = update.to_md(symbol);
+                    self.queue.extend(increments);
+                }
+            }
+            CoinbaseWsMessage::Subscriptions(subscriptions) => {
+                trace!("Coinbase subscriptions: {:?}", subscriptions.channels);
+            }
+            // Coinbase closes the connection after the error, the subscription is retried on reconnect
+            CoinbaseWsMessage::Error(err) => {
+                return Err(eyre!("Coinbase error: {}, reason: {:?}", err.message, err.reason));
+            }
+            CoinbaseWsMessage::Pong => {}
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl MdConnection for CoinbaseMdConnection {
+    async fn next(&mut self) -> Result<MdMessage> {
+        while self.queue.is_empty() {
+            match self.ws.next_event(&mut self.heartbeat).await? {
+                WsEvent::Message(message) => self.on_message(message)?,
+                WsEvent::Ping => {
+                    if let Err(err) = self.ws.ping(Vec::new()).await {
+                        error!("Failed to send ping to Coinbase MD stream, {err}");
+                    }
+                }
+                WsEvent::Reconnected => {}
+            }
+        }
+        let update = self.queue.pop_front().expect("should be some");
+        Ok(update)
+    }
+}
+
+#[cfg(test)]
+mod md_integration_tests {
+    use crate::api::connection::MdConnection;
+    use crate::gates::coinbase::md::config::CoinbaseMdConnectionConfig;
+    use crate::gates::coinbase::md::connection::CoinbaseMdConnection;
+    use crate::model::internal::MdMessage;
+    use crate::model::storage::Storage;
+    use crate::model::symbol::Symbol;
+
+    #[ignore]
+    #[tokio::test]
+    async fn ob_test() {
+        let mut storage = Storage::new();
+
+        let mut connection = CoinbaseMdConnection::new(
+            vec![Symbol::spot("BTC", "USD"), Symbol::spot("ETH", "USD")],
+            CoinbaseMdConnectionConfig::default(),
+        ).await.unwrap();
+
+        let mut snapshots = 0;
+        for _ in 0..1000 {
+            let m = connection.next().await.unwrap();
+            if matches!(m, MdMessage::L2Snapshot(_)) {
+                snapshots += 1;
+            }
+            storage.on_ws_update(m);
+        }
+        assert_eq!(snapshots, 2);
+    }
+}
@@ -0,0 +1,222 @@
+use compact_str::CompactString;
+use serde::{Deserialize, Serialize};
+
+use crate::api::connection::WsMessage;
+use crate::gates::coinbase::common::auth::CoinbaseWsAuth;
+use crate::gates::coinbase::md::stream::CoinbaseChannel;
+use crate::model::exchange::Exchange;
+use crate::model::internal::{L2Increment, L2Snapshot, MdInstrument, MdMessage, Side, SingleLot};
+use crate::model::symbol::Symbol;
+use crate::utils::basic_types::{Amount, deserialize_rfc3339_millis, Price};
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum CoinbaseWsMessage {
+    Snapshot(CoinbaseSnapshot),
+    L2Update(CoinbaseL2Update),
+    /// Confirmation of the subscription, lists all the active channels
+    Subscriptions(CoinbaseSubscriptions),
+    Error(CoinbaseError),
+    #[serde(skip)]
+    Pong,
+}
+
+impl WsMessage for CoinbaseWsMessage {
+    fn pong() -> Self {
+        Self::Pong
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct CoinbaseBookLevel {
+    pub price: Price,
+    pub amount: Amount,
+}
+
+/// Full order book of the product, sent once after the subscription
+#[derive(Debug, Deserialize, Clone)]
+pub struct CoinbaseSnapshot {
+    pub product_id: CompactString,
+    pub bids: Vec<CoinbaseBookLevel>,
+    pub asks: Vec<CoinbaseBookLevel>,
+    /// Missing in the snapshots of the older feed versions
+    #[serde(default, deserialize_with = "deserialize_optional_time")]
+    pub time: Option<u64>,
+}
+
+impl CoinbaseSnapshot {
+    pub fn to_internal(&self, symbol: Symbol) -> L2Snapshot {
+        let to_lot = |l: &CoinbaseBookLevel| SingleLot { price: l.price, amount: l.amount };
+        L2Snapshot {
+            exchange_time: self.time,
+            sequence_no: None,
+            exchange: Exchange::Coinbase,
+            instrument: MdInstrument::Symbol(symbol),
+            bids: self.bids.iter().map(to_lot).collect(),
+            asks: self.asks.iter().map(to_lot).collect(),
+        }
+    }
+}
+
+/// Changed levels of the product, the sizes are absolute and `0` removes the level
+#[derive(Debug, Deserialize, Clone)]
+pub struct CoinbaseL2Update {
+    pub product_id: CompactString,
+    pub changes: Vec<CoinbaseChange>,
+    /// Time of the event, Unix timestamp format in milliseconds
+    #[serde(deserialize_with = "deserialize_rfc3339_millis")]
+    pub time: u64,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct CoinbaseChange {
+    pub side: CoinbaseSide,
+    pub price: Price,
+    pub size: Amount,
+}
+
+/// Side of the changed level: `buy` for bids, `sell` for asks
+#[derive(Debug, Deserialize, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CoinbaseSide {
+    Buy,
+    Sell,
+}
+
+impl From<CoinbaseSide> for Side {
+    fn from(side: CoinbaseSide) -> Self {
+        match side {
+            CoinbaseSide::Buy => Side::Bid,
+            CoinbaseSide::Sell => Side::Ask,
+        }
+    }
+}
+
+impl CoinbaseL2Update {
+    /// One increment per change, the last one is marked as the end of the update
+    pub fn to_md(&self, symbol: &Symbol) -> Vec<MdMessage> {
+        self.changes
+            .iter()
+            .enumerate()
+            .map(|(i, change)| MdMessage::L2Increment(L2Increment {
+                exchange_time: Some(self.time),
+                sequence_no: None,
+                exchange: Exchange::Coinbase,
+                instrument: MdInstrument::Symbol(symbol.clone()),
+                side: change.side.into(),
+                price: change.price,
+                amount: change.size,
+                is_eot: i + 1 == self.changes.len(),
+            }))
+            .collect()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CoinbaseSubscriptions {
+    pub channels: Vec<CoinbaseSubscription>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CoinbaseSubscription {
+    pub name: CompactString,
+    #[serde(default)]
+    pub product_ids: Vec<CompactString>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CoinbaseError {
+    pub message: CompactString,
+    #[serde(default)]
+    pub reason: Option<CompactString>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct CoinbaseWsRequest {
+    #[serde(rename = "type")]
+    request_type: &'static str,
+    product_ids: Vec<CompactString>,
+    channels: Vec<&'static str>,
+    #[serde(flatten)]
+    auth: Option<CoinbaseWsAuth>,
+}
+
+impl CoinbaseWsRequest {
+    pub fn new_subscribe(product_ids: Vec<CompactString>, channel: CoinbaseChannel, auth: Option<CoinbaseWsAuth>) -> Self {
+        Self {
+            request_type: "subscribe",
+            product_ids,
+            channels: vec![channel.name()],
+            auth,
+        }
+    }
+}
+
+fn deserialize_optional_time<'de, D>(deserializer: D) -> Result<Option<u64>, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+{
+    deserialize_rfc3339_millis(deserializer).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::gates::coinbase::md::model::{CoinbaseWsMessage, CoinbaseWsRequest};
+    use crate::gates::coinbase::md::stream::CoinbaseChannel;
+    use crate::model::internal::{MdMessage, Side};
+    use crate::model::symbol::Symbol;
+
+    #[test]
+    fn snapshot_parsing() {
+        let snapshot_str = fs::read_to_string("tests/coinbase_ws_snapshot.json").unwrap();
+        let message: CoinbaseWsMessage = serde_json::from_str(&snapshot_str).unwrap();
+        let CoinbaseWsMessage::Snapshot(snapshot) = message else {
+            panic!("expected snapshot, got {message:?}");
+        };
+        let snapshot = snapshot.to_internal(Symbol::spot("BTC", "USD"));
+        assert_eq!((snapshot.bids.len(), snapshot.asks.len()), (2, 2));
+        assert_eq!(snapshot.exchange_time, Some(1565815347000));
+    }
+
+    #[test]
+    fn l2update_parsing() {
+        let update_str = fs::read_to_string("tests/coinbase_ws_l2update.json").unwrap();
+        let message: CoinbaseWsMessage = serde_json::from_str(&update_str).unwrap();
+        let CoinbaseWsMessage::L2Update(update) = message else {
+            panic!("expected l2update, got {message:?}");
+        };
+        let increments = update.to_md(&Symbol::spot("BTC", "USD"));
+        let sides: Vec<_> = increments
+            .iter()
+            .map(|m| match m {
+                MdMessage::L2Increment(increment) => (increment.side, increment.is_eot),
+                _ => panic!("expected increment, got {m:?}"),
+            })
+            .collect();
+        assert_eq!(sides, [(Side::Bid, false), (Side::Ask, false), (Side::Ask, true)]);
+        let MdMessage::L2Increment(removed) = &increments[2] else {
+            unreachable!()
+        };
+        assert_eq!(removed.amount, Default::default());
+        assert_eq!(removed.exchange_time, Some(1565815347265));
+    }
+
+    #[test]
+    fn subscriptions_and_error_parsing() {
+        let subscriptions_str = r#"{"type":"subscriptions","channels":[{"name":"level2_batch","product_ids":["BTC-USD"],"account_ids":null}]}"#;
+        let message: CoinbaseWsMessage = serde_json::from_str(subscriptions_str).unwrap();
+        assert!(matches!(message, CoinbaseWsMessage::Subscriptions(s) if s.channels[0].product_ids == ["BTC-USD"]));
+
+        let error_str = r#"{"type":"error","message":"Failed to subscribe","reason":"BTC-XXX is not a valid product"}"#;
+        let message: CoinbaseWsMessage = serde_json::from_str(error_str).unwrap();
+        assert!(matches!(message, CoinbaseWsMessage::Error(e) if e.reason.is_some()));
+
+        let request = CoinbaseWsRequest::new_subscribe(vec!["BTC-USD".into()], CoinbaseChannel::Level2Batch, None);
+        assert_eq!(
+            serde_json::to_string(&request).unwrap(),
+            r#"{"type":"subscribe","product_ids":["BTC-USD"],"channels":["level2_batch"]}"#,
+        );
+    }
+}
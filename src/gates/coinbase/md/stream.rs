@@ -0,0 +1,61 @@
+use compact_str::CompactString;
+use log::error;
+use serde::Deserialize;
+
+use crate::gates::coinbase::common::auth::CoinbaseCredentials;
+use crate::gates::coinbase::md::model::CoinbaseWsRequest;
+use crate::model::stream::WsStream;
+
+#[derive(Clone)]
+pub struct CoinbaseStream {
+    /// Native products, e.g. BTC-USD
+    pub product_ids: Vec<CompactString>,
+    pub channel: CoinbaseChannel,
+    /// Required for the `level2` channel only
+    pub credentials: Option<CoinbaseCredentials>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize)]
+pub enum CoinbaseChannel {
+    /// level2: `snapshot` of the full book followed by `l2update` for every change. Requires authentication.
+    ///
+    /// https://docs.cdp.coinbase.com/exchange/docs/websocket-channels#level2-channel
+    Level2,
+    /// level2_batch: the same messages as `level2`, batched every 50 ms. Doesn't require authentication.
+    Level2Batch,
+}
+
+impl CoinbaseChannel {
+    pub fn name(&self) -> &'static str {
+        match self {
+            CoinbaseChannel::Level2 => "level2",
+            CoinbaseChannel::Level2Batch => "level2_batch",
+        }
+    }
+
+    pub fn requires_auth(&self) -> bool {
+        matches!(self, CoinbaseChannel::Level2)
+    }
+}
+
+impl WsStream for CoinbaseStream {
+    type Kind = CoinbaseChannel;
+    type Subscribe = CoinbaseWsRequest;
+    type Login = ();
+
+    fn kind(&self) -> Self::Kind {
+        self.channel
+    }
+
+    /// Coinbase authenticates the subscription itself, the signature is refreshed on every call
+    fn subscribe_requests(&self) -> Vec<Self::Subscribe> {
+        let auth = self.credentials.as_ref().and_then(|c| match c.subscribe_auth() {
+            Ok(auth) => Some(auth),
+            Err(err) => {
+                error!("Failed to sign Coinbase subscription, subscribing without authentication: {err}");
+                None
+            }
+        });
+        vec![CoinbaseWsRequest::new_subscribe(self.product_ids.clone(), self.channel, auth)]
+    }
+}
@@ -0,0 +1,96 @@
+use std::str::FromStr;
+
+use compact_str::{CompactString, format_compact};
+use eyre::eyre;
+
+use crate::model::symbol::{Expiry, format_strike, OptionType, Symbol, SymbolKind, SymbolMapping};
+use crate::utils::basic_types::Price;
+
+/// Quote currency of the inverse instruments, which have no quote in their names
+const INVERSE_QUOTE: &str = "USD";
+
+/// Deribit instruments: `BTC-PERPETUAL`, `BTC-29DEC23` and `BTC-5JAN24-45000-C` for the inverse ones,
+/// `BTC_USDC-PERPETUAL`, `XRP_USDC-30JUN23-0d625-C` for the linear ones, `BTC_USDC` for spot.
+/// Linear option strikes use `d` as the decimal point.
+pub struct DeribitSymbols;
+
+impl DeribitSymbols {
+    fn pair(symbol: &Symbol) -> CompactString {
+        match symbol.quote.as_str() {
+            INVERSE_QUOTE => symbol.base.clone(),
+            quote => format_compact!("{}_{quote}", symbol.base),
+        }
+    }
+}
+
+impl SymbolMapping for DeribitSymbols {
+    fn to_native(symbol: &Symbol) -> eyre::Result<CompactString> {
+        let pair = Self::pair(symbol);
+        let native = match &symbol.kind {
+            SymbolKind::Spot if symbol.quote == INVERSE_QUOTE => {
+                return Err(eyre!("Deribit has no spot pair for {symbol}"));
+            }
+            SymbolKind::Spot => pair,
+            SymbolKind::Index => return Err(eyre!("Deribit has no instrument for index {symbol}")),
+            SymbolKind::Perpetual => format_compact!("{pair}-PERPETUAL"),
+            SymbolKind::Future { expiry } => format_compact!("{pair}-{}", expiry.to_dmmmyy()),
+            SymbolKind::Option { expiry, strike, option_type } => format_compact!(
+                "{pair}-{}-{}-{}",
+                expiry.to_dmmmyy(),
+                format_strike(*strike).replace('.', "d"),
+                option_type.letter(),
+            ),
+        };
+        Ok(native)
+    }
+
+    fn from_native(native: &str) -> eyre::Result<Symbol> {
+        let parts: Vec<_> = native.split('-').collect();
+        let (base, quote) = match parts.first().map(|pair| pair.split_once('_')) {
+            Some(Some((base, quote))) => (base, quote),
+            _ => (parts[0], INVERSE_QUOTE),
+        };
+        if base.is_empty() || quote.is_empty() {
+            return Err(eyre!("unknown Deribit instrument format {native}"));
+        }
+        match parts[1..] {
+            [] if quote != INVERSE_QUOTE => Ok(Symbol::spot(base, quote)),
+            ["PERPETUAL"] => Ok(Symbol::perpetual(base, quote)),
+            [expiry] => Ok(Symbol::future(base, quote, Expiry::from_dmmmyy(expiry)?)),
+            [expiry, strike, option_type] => Ok(Symbol::option(
+                base,
+                quote,
+                Expiry::from_dmmmyy(expiry)?,
+                Price::from_str(&strike.replace('d', "."))
+                    .map_err(|_| eyre!("non-decimal strike in Deribit instrument {native}"))?,
+                OptionType::from_letter(option_type)?,
+            )),
+            _ => Err(eyre!("unknown Deribit instrument format {native}")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::gates::deribit::common::symbol::DeribitSymbols;
+    use crate::model::symbol::{Symbol, SymbolMapping};
+
+    #[test]
+    fn symbol_mapping() {
+        let natives = [
+            ("BTC-PERPETUAL", "BTC/USD-PERP"),
+            ("BTC_USDC-PERPETUAL", "BTC/USDC-PERP"),
+            ("BTC-29DEC23", "BTC/USD-20231229"),
+            ("BTC-5JAN24-45000-C", "BTC/USD-20240105-45000-C"),
+            ("XRP_USDC-30JUN23-0d625-P", "XRP/USDC-20230630-0.625-P"),
+            ("BTC_USDC", "BTC/USDC"),
+        ];
+        for (native, canonical) in natives {
+            let symbol = DeribitSymbols::from_native(native).unwrap();
+            assert_eq!(symbol.to_string(), canonical);
+            assert_eq!(DeribitSymbols::to_native(&symbol).unwrap(), native);
+        }
+        assert!(DeribitSymbols::from_native("BTC").is_err());
+        assert!(DeribitSymbols::to_native(&Symbol::spot("BTC", "USD")).is_err());
+    }
+}
@@ -0,0 +1,34 @@
+use compact_str::CompactString;
+use serde::Deserialize;
+
+use crate::api::backoff::BackoffConfig;
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct DeribitMdConnectionConfig {
+    pub ws_url: CompactString,
+    /// Notification interval of the `book` channel, `100ms` or `agg2`
+    pub interval: CompactString,
+    /// Interval of the Deribit heartbeats in seconds, 10 at least.
+    /// Deribit closes the connection if a `test_request` heartbeat isn't answered.
+    pub heartbeat_interval_seconds: u64,
+    /// Reconnect if no message arrives within this amount of seconds after `public/test` was sent
+    pub pong_timeout_seconds: u64,
+    /// Time to wait for the responses to the requests sent on resubscribing
+    pub request_timeout_ms: u64,
+    /// Retry policy for connecting, reconnecting and resubscribing
+    #[serde(default)]
+    pub reconnect: BackoffConfig,
+}
+
+impl Default for DeribitMdConnectionConfig {
+    fn default() -> Self {
+        Self {
+            ws_url: "wss://www.deribit.com/ws/api/v2".into(),
+            interval: "100ms".into(),
+            heartbeat_interval_seconds: 30,
+            pong_timeout_seconds: 5,
+            request_timeout_ms: 5000,
+            reconnect: BackoffConfig::default(),
+        }
+    }
+}
@@ -0,0 +1,191 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use compact_str::CompactString;
+use eyre::{bail, Result};
+use log::{error, trace, warn};
+
+use crate::api::connection::{MdConnection, WsMessage};
+use crate::api::heartbeat::Heartbeat;
+use crate::api::ws::{WebSocket, WsEvent};
+use crate::gates::deribit::common::symbol::DeribitSymbols;
+use crate::gates::deribit::md::config::DeribitMdConnectionConfig;
+use crate::gates::deribit::md::model::{DeribitBook, DeribitBookType, DeribitHeartbeatType, DeribitRequest, DeribitWsMessage};
+use crate::gates::deribit::md::stream::DeribitStream;
+use crate::model::exchange::Exchange;
+use crate::model::internal::{BookInvalid, BookInvalidReason, MdInstrument, MdMessage};
+use crate::model::symbol::{Symbol, SymbolMapping};
+
+/// Order books of Deribit futures and options from the `book.{instrument_name}.{interval}` channel.
+///
+/// Every `change` notification must link to the previous one by `prev_change_id`. A broken link emits `BookInvalid`
+/// and resubscribes to the channel, the changes are dropped until the new snapshot arrives.
+pub struct DeribitMdConnection {
+    ws: WebSocket<DeribitStream, DeribitWsMessage>,
+    queue: VecDeque<MdMessage>,
+    /// Canonical symbols of the subscribed Deribit instruments
+    symbols: HashMap<CompactString, Symbol>,
+    last_change_ids: HashMap<CompactString, u64>,
+    /// Instruments waiting for a snapshot after resubscribing
+    resyncing: HashSet<CompactString>,
+    request_timeout: Duration,
+    heartbeat: Heartbeat,
+}
+
+impl DeribitMdConnection {
+    pub async fn new(symbols: Vec<Symbol>, config: DeribitMdConnectionConfig) -> Result<Self> {
+        let instruments = symbols.iter().map(DeribitSymbols::to_native).collect::<Result<Vec<_>>>()?;
+        let stream = DeribitStream {
+            instruments: instruments.clone(),
+            interval: config.interval.clone(),
+            heartbeat_interval: config.heartbeat_interval_seconds,
+        };
+        let ws = WebSocket::connect_and_subscribe(
+            &config.ws_url,
+            stream,
+            0,
+            config.reconnect.clone(),
+        ).await?;
+
+        Ok(Self {
+            ws,
+            queue: VecDeque::new(),
+            symbols: instruments.into_iter().zip(symbols).collect(),
+            last_change_ids: HashMap::new(),
+            resyncing: HashSet::new(),
+            request_timeout: Duration::from_millis(config.request_timeout_ms),
+            heartbeat: Heartbeat::new(
+                Duration::from_secs(config.heartbeat_interval_seconds),
+                Duration::from_secs(config.pong_timeout_seconds),
+            ),
+        })
+    }
+
+    async fn on_book(&mut self, book: DeribitBook) -> Result<()> {
+        let instrument = book.instrument_name.clone();
+        let Some(symbol) = self.symbols.get(&instrument).cloned() else {
+            warn!("received Deribit book of unknown instrument {instrument}");
+            return Ok(());
+        };
+        match book.book_type {
+            DeribitBookType::Snapshot => {
+                self.resyncing.remove(&instrument);
+                self.last_change_ids.insert(instrument, book.change_id);
+                self.queue.push_back(MdMessage::L2Snapshot(book.to_internal_snapshot(symbol)));
+            }
+            DeribitBookType::Change => {
+                if self.resyncing.contains(&instrument) {
+                    return Ok(());
+                }
+                let expected = self.last_change_ids.get(&instrument).copied();
+                if expected.is_none() || book.prev_change_id != expected {
+                    warn!(
+                        "Deribit change id gap for {instrument}: expected {expected:?}, received {:?}; resubscribing",
+                        book.prev_change_id,
+                    );
+                    self.last_change_ids.remove(&instrument);
+                    self.queue.push_back(MdMessage::BookInvalid(BookInvalid {
+                        exchange_time: Some(book.timestamp),
+                        sequence_no: Some(book.change_id),
+                        exchange: Exchange::Deribit,
+                        instrument: MdInstrument::Symbol(symbol),
+                        reason: BookInvalidReason::SequenceGap {
+                            expected,
+                            received: book.prev_change_id.unwrap_or(book.change_id),
+                        },
+                    }));
+                    return self.resubscribe(instrument).await;
+                }
+                self.last_change_ids.insert(instrument, book.change_id);
+                self.queue.extend(book.to_md(&symbol));
+            }
+        }
+        Ok(())
+    }
+
+    /// Unsubscribes from the channel and subscribes back, so that Deribit sends a fresh snapshot.
+    /// Both requests wait for their responses, the notifications received meanwhile are processed afterwards.
+    async fn resubscribe(&mut self, instrument: CompactString) -> Result<()> {
+        self.resyncing.insert(instrument.clone());
+        let channel = self.ws.stream().channel(&instrument);
+        for request in [DeribitRequest::unsubscribe(vec![channel.clone()]), DeribitRequest::subscribe(vec![channel])] {
+            let id = self.ws.next_request_id();
+            let response = self.ws.call(id, &request.with_id(id), self.request_timeout).await?;
+            if let Some(err) = response.response_error() {
+                bail!("Failed to resubscribe to Deribit book of {instrument}: {err}");
+            }
+        }
+        Ok(())
+    }
+
+    async fn send_test(&mut self) -> Result<()> {
+        let id = self.ws.next_request_id();
+        self.ws.send(&DeribitRequest::test().with_id(id)).await
+    }
+}
+
+#[async_trait]
+impl MdConnection for DeribitMdConnection {
+    /// Deribit sends a heartbeat every interval, so the silence for longer than that means a stalled connection
+    ///
+    /// https://docs.deribit.com/#public-set_heartbeat
+    async fn next(&mut self) -> Result<MdMessage> {
+        while self.queue.is_empty() {
+            match self.ws.next_event(&mut self.heartbeat).await? {
+                WsEvent::Message(message) => match message {
+                    DeribitWsMessage::Subscription(notification) => self.on_book(notification.params.data).await?,
+                    DeribitWsMessage::Heartbeat(notification) => {
+                        if notification.params.heartbeat_type == DeribitHeartbeatType::TestRequest {
+                            self.send_test().await?;
+                        }
+                    }
+                    DeribitWsMessage::Response(response) => match response.error {
+                        Some(err) => warn!("Deribit request {} failed: {} {}", response.id, err.code, err.message),
+                        None => trace!("Deribit request {} succeeded", response.id),
+                    },
+                    DeribitWsMessage::Pong => {}
+                },
+                WsEvent::Ping => {
+                    if let Err(err) = self.send_test().await {
+                        error!("Failed to send public/test to Deribit MD stream, {err}");
+                    }
+                }
+                WsEvent::Reconnected => {}
+            }
+        }
+        let update = self.queue.pop_front().expect("should be some");
+        Ok(update)
+    }
+}
+
+#[cfg(test)]
+mod md_integration_tests {
+    use crate::api::connection::MdConnection;
+    use crate::gates::deribit::md::config::DeribitMdConnectionConfig;
+    use crate::gates::deribit::md::connection::DeribitMdConnection;
+    use crate::model::internal::MdMessage;
+    use crate::model::storage::Storage;
+    use crate::model::symbol::Symbol;
+
+    #[ignore]
+    #[tokio::test]
+    async fn ob_test() {
+        let mut storage = Storage::new();
+
+        let mut connection = DeribitMdConnection::new(
+            vec![Symbol::perpetual("BTC", "USD"), Symbol::perpetual("ETH", "USD")],
+            DeribitMdConnectionConfig::default(),
+        ).await.unwrap();
+
+        let mut snapshots = 0;
+        for _ in 0..1000 {
+            let m = connection.next().await.unwrap();
+            if matches!(m, MdMessage::L2Snapshot(_)) {
+                snapshots += 1;
+            }
+            storage.on_ws_update(m);
+        }
+        assert_eq!(snapshots, 2);
+    }
+}
@@ -0,0 +1,286 @@
+use compact_str::CompactString;
+use eyre::eyre;
+use fixnum::ops::Zero;
+use serde::{Deserialize, Serialize};
+
+use crate::api::connection::WsMessage;
+use crate::model::exchange::Exchange;
+use crate::model::internal::{L2Increment, L2Snapshot, MdInstrument, MdMessage, Side, SingleLot};
+use crate::model::symbol::Symbol;
+use crate::utils::basic_types::{Amount, deserialize_float_decimal, Price};
+
+/// JSON-RPC 2.0 messages: notifications of the subscribed channels, heartbeats and responses to the requests
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum DeribitWsMessage {
+    Subscription(DeribitNotification<DeribitSubscription>),
+    Heartbeat(DeribitNotification<DeribitHeartbeat>),
+    Response(DeribitResponse),
+    Pong,
+}
+
+impl WsMessage for DeribitWsMessage {
+    fn pong() -> Self {
+        Self::Pong
+    }
+
+    fn response_id(&self) -> Option<u64> {
+        match self {
+            DeribitWsMessage::Response(response) => Some(response.id),
+            _ => None,
+        }
+    }
+
+    fn response_error(&self) -> Option<eyre::Report> {
+        match self {
+            DeribitWsMessage::Response(DeribitResponse { error: Some(err), .. }) => {
+                Some(eyre!("Deribit error {}: {}", err.code, err.message))
+            }
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeribitNotification<P> {
+    pub method: CompactString,
+    pub params: P,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeribitSubscription {
+    pub channel: CompactString,
+    pub data: DeribitBook,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeribitHeartbeat {
+    #[serde(rename = "type")]
+    pub heartbeat_type: DeribitHeartbeatType,
+}
+
+#[derive(Debug, Deserialize, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DeribitHeartbeatType {
+    /// Sent every heartbeat interval
+    Heartbeat,
+    /// Deribit closes the connection unless `public/test` is sent in response
+    TestRequest,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeribitResponse {
+    pub id: u64,
+    #[serde(default)]
+    pub result: Option<serde_json::Value>,
+    #[serde(default)]
+    pub error: Option<DeribitError>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeribitError {
+    pub code: i64,
+    pub message: CompactString,
+}
+
+/// Data of the `book.{instrument_name}.{interval}` channel
+///
+/// https://docs.deribit.com/#book-instrument_name-interval
+#[derive(Debug, Deserialize, Clone)]
+pub struct DeribitBook {
+    #[serde(rename = "type")]
+    pub book_type: DeribitBookType,
+    /// Unix timestamp format in milliseconds
+    pub timestamp: u64,
+    pub instrument_name: CompactString,
+    pub change_id: u64,
+    /// Missing in snapshots, `change_id` of the previous message otherwise
+    pub prev_change_id: Option<u64>,
+    pub bids: Vec<DeribitBookLevel>,
+    pub asks: Vec<DeribitBookLevel>,
+}
+
+#[derive(Debug, Deserialize, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DeribitBookType {
+    Snapshot,
+    Change,
+}
+
+/// `[action, price, amount]`, the amount is absolute. In USD for the inverse instruments, in base currency otherwise.
+#[derive(Debug, Deserialize, Clone)]
+pub struct DeribitBookLevel(
+    pub DeribitLevelAction,
+    #[serde(deserialize_with = "deserialize_float_decimal")]
+    pub Price,
+    #[serde(deserialize_with = "deserialize_float_decimal")]
+    pub Amount,
+);
+
+#[derive(Debug, Deserialize, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DeribitLevelAction {
+    New,
+    Change,
+    Delete,
+}
+
+impl DeribitBookLevel {
+    pub fn amount(&self) -> Amount {
+        match self.0 {
+            DeribitLevelAction::Delete => Amount::ZERO,
+            DeribitLevelAction::New | DeribitLevelAction::Change => self.2,
+        }
+    }
+}
+
+impl DeribitBook {
+    pub fn to_internal_snapshot(&self, symbol: Symbol) -> L2Snapshot {
+        let to_lot = |l: &DeribitBookLevel| SingleLot { price: l.1, amount: l.amount() };
+        L2Snapshot {
+            exchange_time: Some(self.timestamp),
+            sequence_no: Some(self.change_id),
+            exchange: Exchange::Deribit,
+            instrument: MdInstrument::Symbol(symbol),
+            bids: self.bids.iter().map(to_lot).collect(),
+            asks: self.asks.iter().map(to_lot).collect(),
+        }
+    }
+
+    /// One increment per changed level, the last one is marked as the end of the update
+    pub fn to_md(&self, symbol: &Symbol) -> Vec<MdMessage> {
+        let total = self.bids.len() + self.asks.len();
+        self.bids
+            .iter()
+            .map(|l| (Side::Bid, l))
+            .chain(self.asks.iter().map(|l| (Side::Ask, l)))
+            .enumerate()
+            .map(|(i, (side, level))| MdMessage::L2Increment(L2Increment {
+                exchange_time: Some(self.timestamp),
+                sequence_no: Some(self.change_id),
+                exchange: Exchange::Deribit,
+                instrument: MdInstrument::Symbol(symbol.clone()),
+                side,
+                price: level.1,
+                amount: level.amount(),
+                is_eot: i + 1 == total,
+            }))
+            .collect()
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct DeribitRequest {
+    jsonrpc: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<u64>,
+    method: &'static str,
+    params: DeribitParams,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(untagged)]
+pub enum DeribitParams {
+    Channels { channels: Vec<CompactString> },
+    Heartbeat { interval: u64 },
+    Empty {},
+}
+
+impl DeribitRequest {
+    pub fn new(method: &'static str, params: DeribitParams) -> Self {
+        Self { jsonrpc: "2.0", id: None, method, params }
+    }
+
+    pub fn subscribe(channels: Vec<CompactString>) -> Self {
+        Self::new("public/subscribe", DeribitParams::Channels { channels })
+    }
+
+    pub fn unsubscribe(channels: Vec<CompactString>) -> Self {
+        Self::new("public/unsubscribe", DeribitParams::Channels { channels })
+    }
+
+    /// Enables heartbeats with the interval in seconds, 10 at least
+    pub fn set_heartbeat(interval: u64) -> Self {
+        Self::new("public/set_heartbeat", DeribitParams::Heartbeat { interval })
+    }
+
+    /// Answers the `test_request` heartbeat, also used as a ping
+    pub fn test() -> Self {
+        Self::new("public/test", DeribitParams::Empty {})
+    }
+
+    pub fn with_id(mut self, id: u64) -> Self {
+        self.set_id(id);
+        self
+    }
+
+    pub fn set_id(&mut self, id: u64) {
+        self.id = Some(id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::str::FromStr;
+
+    use crate::api::connection::WsMessage;
+    use crate::gates::deribit::md::model::{DeribitBookType, DeribitHeartbeatType, DeribitRequest, DeribitWsMessage};
+    use crate::model::internal::MdMessage;
+    use crate::model::symbol::Symbol;
+    use crate::utils::basic_types::Price;
+
+    #[test]
+    fn book_parsing() {
+        for (file, book_type, prev_change_id) in [
+            ("tests/deribit_ws_book_snapshot.json", DeribitBookType::Snapshot, None),
+            ("tests/deribit_ws_book_change.json", DeribitBookType::Change, Some(297217)),
+        ] {
+            let message: DeribitWsMessage = serde_json::from_str(&fs::read_to_string(file).unwrap()).unwrap();
+            let DeribitWsMessage::Subscription(notification) = message else {
+                panic!("expected subscription, got {message:?}");
+            };
+            assert_eq!(notification.params.channel, "book.BTC-PERPETUAL.100ms");
+            assert_eq!(notification.params.data.book_type, book_type);
+            assert_eq!(notification.params.data.prev_change_id, prev_change_id);
+        }
+
+        let change_str = fs::read_to_string("tests/deribit_ws_book_change.json").unwrap();
+        let DeribitWsMessage::Subscription(notification) = serde_json::from_str(&change_str).unwrap() else {
+            panic!("expected subscription");
+        };
+        let increments = notification.params.data.to_md(&Symbol::perpetual("BTC", "USD"));
+        let MdMessage::L2Increment(deleted) = &increments[0] else {
+            panic!("expected increment");
+        };
+        assert_eq!(deleted.price, Price::from_str("5041.94").unwrap());
+        assert_eq!(deleted.amount, Default::default());
+        assert!(matches!(increments.last(), Some(MdMessage::L2Increment(last)) if last.is_eot));
+    }
+
+    #[test]
+    fn responses_and_heartbeats_parsing() {
+        let response_str = r#"{"jsonrpc":"2.0","id":2,"result":["book.BTC-PERPETUAL.100ms"],"usIn":1535043730126248,"usOut":1535043730126250,"usDiff":2,"testnet":false}"#;
+        let message: DeribitWsMessage = serde_json::from_str(response_str).unwrap();
+        assert_eq!(message.response_id(), Some(2));
+        assert!(message.response_error().is_none());
+
+        let error_str = r#"{"jsonrpc":"2.0","id":3,"error":{"message":"Invalid params","code":-32602},"usIn":1,"usOut":2,"usDiff":1,"testnet":false}"#;
+        let message: DeribitWsMessage = serde_json::from_str(error_str).unwrap();
+        assert_eq!(message.response_id(), Some(3));
+        assert!(message.response_error().is_some());
+
+        let heartbeat_str = r#"{"jsonrpc":"2.0","method":"heartbeat","params":{"type":"test_request"}}"#;
+        let message: DeribitWsMessage = serde_json::from_str(heartbeat_str).unwrap();
+        assert!(matches!(message, DeribitWsMessage::Heartbeat(h) if h.params.heartbeat_type == DeribitHeartbeatType::TestRequest));
+
+        assert_eq!(
+            serde_json::to_string(&DeribitRequest::set_heartbeat(30).with_id(1)).unwrap(),
+            r#"{"jsonrpc":"2.0","id":1,"method":"public/set_heartbeat","params":{"interval":30}}"#,
+        );
+        assert_eq!(
+            serde_json::to_string(&DeribitRequest::test()).unwrap(),
+            r#"{"jsonrpc":"2.0","method":"public/test","params":{}}"#,
+        );
+    }
+}
@@ -0,0 +1,50 @@
+use compact_str::{CompactString, format_compact};
+
+use crate::gates::deribit::md::model::DeribitRequest;
+use crate::model::stream::WsStream;
+
+#[derive(Clone)]
+pub struct DeribitStream {
+    /// Native instruments, e.g. BTC-PERPETUAL
+    pub instruments: Vec<CompactString>,
+    /// Notification interval of the `book` channel, `100ms` or `agg2`. `raw` requires authorization.
+    pub interval: CompactString,
+    /// Heartbeat interval in seconds
+    pub heartbeat_interval: u64,
+}
+
+impl DeribitStream {
+    /// book.{instrument_name}.{interval}: `snapshot` followed by `change` notifications linked by `prev_change_id`
+    pub fn channel(&self, instrument: &str) -> CompactString {
+        format_compact!("book.{instrument}.{}", self.interval)
+    }
+
+    /// Instrument the channel belongs to, e.g. `BTC-PERPETUAL` of `book.BTC-PERPETUAL.100ms`
+    pub fn instrument(channel: &str) -> Option<&str> {
+        channel.strip_prefix("book.")?.rsplit_once('.').map(|(instrument, _)| instrument)
+    }
+}
+
+impl WsStream for DeribitStream {
+    type Kind = CompactString;
+    type Subscribe = DeribitRequest;
+    type Login = ();
+
+    fn kind(&self) -> Self::Kind {
+        self.interval.clone()
+    }
+
+    /// The heartbeats are per connection, so they are enabled again on every reconnect
+    fn subscribe_requests(&self) -> Vec<Self::Subscribe> {
+        let channels = self.instruments.iter().map(|i| self.channel(i)).collect();
+        vec![
+            DeribitRequest::set_heartbeat(self.heartbeat_interval),
+            DeribitRequest::subscribe(channels),
+        ]
+    }
+
+    fn set_request_id(request: &mut Self::Subscribe, id: u64) -> bool {
+        request.set_id(id);
+        true
+    }
+}
@@ -0,0 +1,37 @@
+use compact_str::{CompactString, format_compact};
+use eyre::eyre;
+
+use crate::model::symbol::{Symbol, SymbolKind, SymbolMapping};
+
+/// Kraken websocket v2 spot symbols use the common currency codes separated by a slash, e.g. `BTC/USD`
+pub struct KrakenSymbols;
+
+impl SymbolMapping for KrakenSymbols {
+    fn to_native(symbol: &Symbol) -> eyre::Result<CompactString> {
+        match symbol.kind {
+            SymbolKind::Spot => Ok(format_compact!("{}/{}", symbol.base, symbol.quote)),
+            _ => Err(eyre!("Kraken spot has no pair for {symbol}")),
+        }
+    }
+
+    fn from_native(native: &str) -> eyre::Result<Symbol> {
+        match native.split_once('/') {
+            Some((base, quote)) if !base.is_empty() && !quote.is_empty() => Ok(Symbol::spot(base, quote)),
+            _ => Err(eyre!("unknown Kraken symbol format {native}")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::gates::kraken::common::symbol::KrakenSymbols;
+    use crate::model::symbol::{Symbol, SymbolMapping};
+
+    #[test]
+    fn symbol_mapping() {
+        let symbol = KrakenSymbols::from_native("BTC/USD").unwrap();
+        assert_eq!(symbol, Symbol::spot("BTC", "USD"));
+        assert_eq!(KrakenSymbols::to_native(&symbol).unwrap(), "BTC/USD");
+        assert!(KrakenSymbols::from_native("XBTUSD").is_err());
+    }
+}
@@ -0,0 +1,101 @@
+use crate::model::order_book::OrderBook;
+use crate::utils::basic_types::Price;
+
+/// Amount of levels per side which take part in the checksum calculation
+const CHECKSUM_DEPTH: usize = 10;
+
+/// Decimal places of the pair, Kraken formats the checksum values with them
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct KrakenPrecision {
+    pub price: usize,
+    pub qty: usize,
+}
+
+/// Concatenates the top 10 asks from the best price up followed by the top 10 bids from the best price down.
+/// Every level is its price and quantity formatted with the pair precision,
+/// with the decimal point and the leading zeros removed.
+///
+/// https://docs.kraken.com/api/docs/guides/spot-ws-book-v2
+pub fn checksum_string(book: &OrderBook, precision: KrakenPrecision) -> String {
+    let asks = book.asks.iter().take(CHECKSUM_DEPTH);
+    let bids = book.bids.iter().rev().take(CHECKSUM_DEPTH);
+
+    let mut s = String::with_capacity(CHECKSUM_DEPTH * 2 * 24);
+    for (price, qty) in asks.chain(bids) {
+        push_decimal(&mut s, price, precision.price);
+        push_decimal(&mut s, qty, precision.qty);
+    }
+    s
+}
+
+/// CRC32 of the checksum string, Kraken sends it as an unsigned 32-bit integer
+pub fn checksum(book: &OrderBook, precision: KrakenPrecision) -> u32 {
+    crc32fast::hash(checksum_string(book, precision).as_bytes())
+}
+
+fn push_decimal(s: &mut String, value: &Price, precision: usize) {
+    let formatted = value.to_string();
+    let (integer, fraction) = formatted.split_once('.').unwrap_or((&formatted, ""));
+    let digits = integer
+        .chars()
+        .chain(fraction.chars().chain(std::iter::repeat('0')).take(precision));
+    let start = s.len();
+    for digit in digits {
+        if digit != '0' || s.len() > start {
+            s.push(digit);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::str::FromStr;
+
+    use crate::gates::kraken::md::checksum::{checksum, checksum_string, KrakenPrecision};
+    use crate::gates::kraken::md::model::KrakenWsMessage;
+    use crate::model::internal::{MdMessage, Side};
+    use crate::model::order_book::OrderBook;
+    use crate::model::symbol::Symbol;
+    use crate::utils::basic_types::Price;
+
+    fn book(bids: &[(&str, &str)], asks: &[(&str, &str)]) -> OrderBook {
+        let level = |(p, a): &(&str, &str)| (Price::from_str(p).unwrap(), Price::from_str(a).unwrap());
+        OrderBook {
+            bids: bids.iter().map(level).collect(),
+            asks: asks.iter().map(level).collect(),
+        }
+    }
+
+    #[test]
+    fn checksum_string_strips_points_and_leading_zeros() {
+        let precision = KrakenPrecision { price: 4, qty: 8 };
+        let ob = book(&[("0.5666", "4831.75496356"), ("0.5665", "6658.22734739")], &[("0.5668", "1.5")]);
+        assert_eq!(checksum_string(&ob, precision), "566815000000056664831754963565665665822734739");
+        assert_eq!(checksum(&ob, precision), 1_492_355_633);
+    }
+
+    #[test]
+    fn checksum_of_applied_messages() {
+        let precision = KrakenPrecision { price: 4, qty: 8 };
+        let symbol = Symbol::spot("MATIC", "USD");
+        let mut ob = OrderBook::new();
+        for file in ["tests/kraken_ws_book_snapshot.json", "tests/kraken_ws_book_update.json"] {
+            let message: KrakenWsMessage = serde_json::from_str(&fs::read_to_string(file).unwrap()).unwrap();
+            let KrakenWsMessage::Book(message) = message else {
+                panic!("expected book, got {message:?}");
+            };
+            let book = &message.data[0];
+            for m in book.to_md(&symbol) {
+                if let MdMessage::L2Increment(increment) = m {
+                    ob.set_level(increment.side, increment.price, increment.amount);
+                }
+            }
+            assert_eq!(checksum(&ob, precision), book.checksum);
+        }
+
+        assert_eq!(ob.truncate(2), [(Side::Bid, Price::from_str("0.5657").unwrap())]);
+        assert_eq!(ob.bids.keys().next(), Some(&Price::from_str("0.5665").unwrap()));
+        assert_eq!(ob.asks.len(), 2);
+    }
+}
@@ -0,0 +1,36 @@
+use compact_str::CompactString;
+use serde::Deserialize;
+
+use crate::api::backoff::BackoffConfig;
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct KrakenMdConnectionConfig {
+    pub ws_url: CompactString,
+    /// Book depth to subscribe to, one of 10, 25, 100, 500, 1000
+    pub depth: u16,
+    /// Send `{"method":"ping"}` if no message was received for this amount of seconds.
+    /// Kraken sends a heartbeat every second while subscribed, so this fires only on a stalled connection.
+    pub idle_timeout_seconds: u64,
+    /// Reconnect if no message arrives within this amount of seconds after the ping was sent
+    pub pong_timeout_seconds: u64,
+    pub subscribe_interval_ms: u64,
+    /// Verify the local order book against the checksum sent with every `book` message
+    pub validate_checksum: bool,
+    /// Retry policy for connecting, reconnecting and resubscribing
+    #[serde(default)]
+    pub reconnect: BackoffConfig,
+}
+
+impl Default for KrakenMdConnectionConfig {
+    fn default() -> Self {
+        Self {
+            ws_url: "wss://ws.kraken.com/v2".into(),
+            depth: 10,
+            idle_timeout_seconds: 10,
+            pong_timeout_seconds: 5,
+            subscribe_interval_ms: 100,
+            validate_checksum: true,
+            reconnect: BackoffConfig::default(),
+        }
+    }
+}
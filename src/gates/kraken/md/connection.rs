@@ -0,0 +1,239 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use compact_str::CompactString;
+use eyre::Result;
+use fixnum::ops::Zero;
+use log::{error, trace, warn};
+
+use crate::api::connection::MdConnection;
+use crate::api::heartbeat::Heartbeat;
+use crate::api::ws::{WebSocket, WsEvent};
+use crate::gates::kraken::common::symbol::KrakenSymbols;
+use crate::gates::kraken::md::checksum::{self, KrakenPrecision};
+use crate::gates::kraken::md::config::KrakenMdConnectionConfig;
+use crate::gates::kraken::md::model::{KrakenBook, KrakenDataType, KrakenMethodResponse, KrakenWsMessage, KrakenWsRequest};
+use crate::gates::kraken::md::stream::KrakenStream;
+use crate::model::exchange::Exchange;
+use crate::model::internal::{BookInvalid, BookInvalidReason, L2Increment, MdInstrument, MdMessage};
+use crate::model::order_book::OrderBook;
+use crate::model::symbol::{Symbol, SymbolMapping};
+use crate::utils::basic_types::Amount;
+
+/// Order books of Kraken spot pairs from the websocket v2 `book` channel.
+///
+/// The local books are truncated to the subscribed depth after every message, the levels pushed out
+/// are emitted as removals, so that the consumers' books stay within the depth too.
+/// Every message is verified against its checksum, a mismatch emits `BookInvalid` and resubscribes to the pair.
+/// The books are held back until the `instrument` snapshot brings the precisions the checksums depend on.
+pub struct KrakenMdConnection {
+    ws: WebSocket<KrakenStream, KrakenWsMessage>,
+    queue: VecDeque<MdMessage>,
+    /// Canonical symbols of the subscribed Kraken pairs
+    symbols: HashMap<CompactString, Symbol>,
+    depth: u16,
+    books: HashMap<CompactString, OrderBook>,
+    /// From the `instrument` channel, required to calculate the checksums
+    precisions: HashMap<CompactString, KrakenPrecision>,
+    /// Books received before the instrument snapshot, replayed once the precisions are known
+    pending_books: Vec<(KrakenDataType, KrakenBook)>,
+    /// Pairs waiting for a snapshot after resubscribing
+    resyncing: HashSet<CompactString>,
+    validate_checksum: bool,
+    heartbeat: Heartbeat,
+}
+
+impl KrakenMdConnection {
+    pub async fn new(symbols: Vec<Symbol>, config: KrakenMdConnectionConfig) -> Result<Self> {
+        let natives = symbols.iter().map(KrakenSymbols::to_native).collect::<Result<Vec<_>>>()?;
+        let stream = KrakenStream { symbols: natives.clone(), depth: config.depth };
+        let ws = WebSocket::connect_and_subscribe(
+            &config.ws_url,
+            stream,
+            config.subscribe_interval_ms,
+            config.reconnect.clone(),
+        ).await?;
+
+        Ok(Self {
+            ws,
+            queue: VecDeque::new(),
+            symbols: natives.into_iter().zip(symbols).collect(),
+            depth: config.depth,
+            books: HashMap::new(),
+            precisions: HashMap::new(),
+            pending_books: Vec::new(),
+            resyncing: HashSet::new(),
+            validate_checksum: config.validate_checksum,
+            heartbeat: Heartbeat::new(
+                Duration::from_secs(config.idle_timeout_seconds),
+                Duration::from_secs(config.pong_timeout_seconds),
+            ),
+        })
+    }
+
+    async fn on_book(&mut self, data_type: KrakenDataType, book: KrakenBook) -> Result<()> {
+        let native = book.symbol.clone();
+        let Some(symbol) = self.symbols.get(&native).cloned() else {
+            warn!("received Kraken book of unknown symbol {native}");
+            return Ok(());
+        };
+
+        let mut messages = match data_type {
+            KrakenDataType::Snapshot => {
+                self.resyncing.remove(&native);
+                let snapshot = book.to_internal_snapshot(symbol.clone());
+                let ob = self.books.entry(native.clone()).or_default();
+                ob.process_snapshot(snapshot.clone());
+                vec![MdMessage::L2Snapshot(snapshot)]
+            }
+            KrakenDataType::Update => {
+                if self.resyncing.contains(&native) {
+                    return Ok(());
+                }
+                let Some(ob) = self.books.get_mut(&native) else {
+                    warn!("Kraken book update of {native} before the snapshot, resubscribing");
+                    return self.resubscribe(native).await;
+                };
+                let increments = book.to_md(&symbol);
+                for increment in &increments {
+                    if let MdMessage::L2Increment(increment) = increment {
+                        ob.set_level(increment.side, increment.price, increment.amount);
+                    }
+                }
+                increments
+            }
+        };
+
+        let ob = self.books.get_mut(&native).expect("book is inserted above");
+        for (side, price) in ob.truncate(usize::from(self.depth)) {
+            messages.push(MdMessage::L2Increment(L2Increment {
+                exchange_time: book.timestamp,
+                sequence_no: None,
+                exchange: Exchange::Kraken,
+                instrument: MdInstrument::Symbol(symbol.clone()),
+                side,
+                price,
+                amount: Amount::ZERO,
+                is_eot: false,
+            }));
+        }
+        mark_end_of_transaction(&mut messages);
+
+        if self.validate_checksum {
+            match self.precisions.get(&native) {
+                Some(&precision) => {
+                    let actual = checksum::checksum(ob, precision);
+                    if actual != book.checksum {
+                        warn!("Kraken checksum mismatch for {native}: expected {}, actual {actual}; resubscribing", book.checksum);
+                        self.books.remove(&native);
+                        self.queue.push_back(MdMessage::BookInvalid(BookInvalid {
+                            exchange_time: book.timestamp,
+                            sequence_no: None,
+                            exchange: Exchange::Kraken,
+                            instrument: MdInstrument::Symbol(symbol),
+                            reason: BookInvalidReason::ChecksumMismatch {
+                                expected: book.checksum.into(),
+                                actual: actual.into(),
+                            },
+                        }));
+                        return self.resubscribe(native).await;
+                    }
+                }
+                None => warn!("Kraken pair {native} is missing from the instrument channel, skipping checksum validation"),
+            }
+        }
+
+        self.queue.extend(messages);
+        Ok(())
+    }
+
+    fn on_method_response(&self, response: KrakenMethodResponse) {
+        match response.success {
+            Some(false) => warn!("Kraken {} request failed: {:?}", response.method, response.error),
+            _ => trace!("Kraken {} request succeeded", response.method),
+        }
+    }
+
+    /// Unsubscribes from the pair book and subscribes back, so that Kraken sends a fresh snapshot
+    async fn resubscribe(&mut self, native: CompactString) -> Result<()> {
+        self.resyncing.insert(native.clone());
+        self.ws.send(&KrakenWsRequest::new_book_unsubscribe(vec![native.clone()], self.depth)).await?;
+        self.ws.send(&KrakenWsRequest::new_book_subscribe(vec![native], self.depth)).await?;
+        Ok(())
+    }
+}
+
+/// Only the last increment of the message closes the transaction
+fn mark_end_of_transaction(messages: &mut [MdMessage]) {
+    let len = messages.len();
+    for (i, message) in messages.iter_mut().enumerate() {
+        if let MdMessage::L2Increment(increment) = message {
+            increment.is_eot = i + 1 == len;
+        }
+    }
+}
+
+#[async_trait]
+impl MdConnection for KrakenMdConnection {
+    async fn next(&mut self) -> Result<MdMessage> {
+        while self.queue.is_empty() {
+            match self.ws.next_event(&mut self.heartbeat).await? {
+                WsEvent::Message(message) => match message {
+                    KrakenWsMessage::Book(message) if self.validate_checksum && self.precisions.is_empty() => {
+                        trace!("Kraken instrument snapshot is not received yet, holding the books back");
+                        self.pending_books.extend(message.data.into_iter().map(|book| (message.data_type, book)));
+                    }
+                    KrakenWsMessage::Book(message) => {
+                        for book in message.data {
+                            self.on_book(message.data_type, book).await?;
+                        }
+                    }
+                    KrakenWsMessage::Instrument(message) => {
+                        self.precisions.extend(message.data.pairs.iter().map(|p| (p.symbol.clone(), p.precision())));
+                        for (data_type, book) in std::mem::take(&mut self.pending_books) {
+                            self.on_book(data_type, book).await?;
+                        }
+                    }
+                    KrakenWsMessage::MethodResponse(response) => self.on_method_response(response),
+                    KrakenWsMessage::Channel(_) | KrakenWsMessage::Pong => {}
+                },
+                WsEvent::Ping => {
+                    if let Err(err) = self.ws.send(&KrakenWsRequest::ping()).await {
+                        error!("Failed to send ping to Kraken MD stream, {err}");
+                    }
+                }
+                WsEvent::Reconnected => {}
+            }
+        }
+        let update = self.queue.pop_front().expect("should be some");
+        Ok(update)
+    }
+}
+
+#[cfg(test)]
+mod md_integration_tests {
+    use crate::api::connection::MdConnection;
+    use crate::gates::kraken::md::config::KrakenMdConnectionConfig;
+    use crate::gates::kraken::md::connection::KrakenMdConnection;
+    use crate::model::internal::MdMessage;
+    use crate::model::storage::Storage;
+    use crate::model::symbol::Symbol;
+
+    #[ignore]
+    #[tokio::test]
+    async fn ob_test() {
+        let mut storage = Storage::new();
+
+        let mut connection = KrakenMdConnection::new(
+            vec![Symbol::spot("BTC", "USD"), Symbol::spot("ETH", "USD")],
+            KrakenMdConnectionConfig::default(),
+        ).await.unwrap();
+
+        for _ in 0..1000 {
+            let m = connection.next().await.unwrap();
+            assert!(!matches!(m, MdMessage::BookInvalid(_)), "{m:?}");
+            storage.on_ws_update(m);
+        }
+    }
+}
@@ -0,0 +1,237 @@
+use compact_str::CompactString;
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::api::connection::WsMessage;
+use crate::gates::kraken::md::checksum::KrakenPrecision;
+use crate::model::exchange::Exchange;
+use crate::model::internal::{L2Increment, L2Snapshot, MdInstrument, MdMessage, Side, SingleLot};
+use crate::model::symbol::Symbol;
+use crate::utils::basic_types::{Amount, deserialize_float_decimal, deserialize_rfc3339_millis, Price};
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum KrakenWsMessage {
+    Book(KrakenChannelMessage<Vec<KrakenBook>>),
+    Instrument(KrakenChannelMessage<KrakenInstruments>),
+    /// Response to `subscribe`, `unsubscribe` and `ping` requests
+    MethodResponse(KrakenMethodResponse),
+    /// `heartbeat` and `status` channels
+    Channel(KrakenChannel),
+    Pong,
+}
+
+impl WsMessage for KrakenWsMessage {
+    fn pong() -> Self {
+        Self::Pong
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct KrakenChannelMessage<D> {
+    pub channel: CompactString,
+    #[serde(rename = "type")]
+    pub data_type: KrakenDataType,
+    pub data: D,
+}
+
+#[derive(Debug, Deserialize, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum KrakenDataType {
+    Snapshot,
+    Update,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct KrakenChannel {
+    pub channel: CompactString,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct KrakenMethodResponse {
+    pub method: CompactString,
+    pub success: Option<bool>,
+    pub error: Option<CompactString>,
+    pub req_id: Option<u64>,
+}
+
+/// Prices and quantities come as JSON numbers
+#[derive(Debug, Deserialize, Clone)]
+pub struct KrakenBookLevel {
+    #[serde(deserialize_with = "deserialize_float_decimal")]
+    pub price: Price,
+    #[serde(deserialize_with = "deserialize_float_decimal")]
+    pub qty: Amount,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct KrakenBook {
+    pub symbol: CompactString,
+    pub bids: Vec<KrakenBookLevel>,
+    pub asks: Vec<KrakenBookLevel>,
+    /// CRC32 of the top 10 levels after the message is applied, see `checksum::checksum`
+    pub checksum: u32,
+    /// Missing in snapshots
+    #[serde(default, deserialize_with = "deserialize_optional_time")]
+    pub timestamp: Option<u64>,
+}
+
+impl KrakenBook {
+    pub fn to_internal_snapshot(&self, symbol: Symbol) -> L2Snapshot {
+        let to_lot = |l: &KrakenBookLevel| SingleLot { price: l.price, amount: l.qty };
+        L2Snapshot {
+            exchange_time: self.timestamp,
+            sequence_no: None,
+            exchange: Exchange::Kraken,
+            instrument: MdInstrument::Symbol(symbol),
+            bids: self.bids.iter().map(to_lot).collect(),
+            asks: self.asks.iter().map(to_lot).collect(),
+        }
+    }
+
+    /// One increment per changed level, the quantities are absolute and zero removes the level
+    pub fn to_md(&self, symbol: &Symbol) -> Vec<MdMessage> {
+        let total = self.bids.len() + self.asks.len();
+        self.bids
+            .iter()
+            .map(|l| (Side::Bid, l))
+            .chain(self.asks.iter().map(|l| (Side::Ask, l)))
+            .enumerate()
+            .map(|(i, (side, level))| MdMessage::L2Increment(L2Increment {
+                exchange_time: self.timestamp,
+                sequence_no: None,
+                exchange: Exchange::Kraken,
+                instrument: MdInstrument::Symbol(symbol.clone()),
+                side,
+                price: level.price,
+                amount: level.qty,
+                is_eot: i + 1 == total,
+            }))
+            .collect()
+    }
+}
+
+/// Snapshot of the `instrument` channel, only the pairs are of interest
+#[derive(Debug, Deserialize)]
+pub struct KrakenInstruments {
+    pub pairs: Vec<KrakenPair>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct KrakenPair {
+    pub symbol: CompactString,
+    pub price_precision: usize,
+    pub qty_precision: usize,
+}
+
+impl KrakenPair {
+    pub fn precision(&self) -> KrakenPrecision {
+        KrakenPrecision { price: self.price_precision, qty: self.qty_precision }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct KrakenWsRequest {
+    method: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    params: Option<KrakenSubscriptionParams>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct KrakenSubscriptionParams {
+    channel: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    symbol: Option<Vec<CompactString>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    depth: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    snapshot: Option<bool>,
+}
+
+impl KrakenWsRequest {
+    pub fn new_book_subscribe(symbols: Vec<CompactString>, depth: u16) -> Self {
+        Self::new_book("subscribe", symbols, depth, Some(true))
+    }
+
+    pub fn new_book_unsubscribe(symbols: Vec<CompactString>, depth: u16) -> Self {
+        Self::new_book("unsubscribe", symbols, depth, None)
+    }
+
+    /// Precisions of all the pairs, required to verify the book checksums
+    pub fn new_instrument_subscribe() -> Self {
+        Self {
+            method: "subscribe",
+            params: Some(KrakenSubscriptionParams { channel: "instrument", symbol: None, depth: None, snapshot: Some(true) }),
+        }
+    }
+
+    pub fn ping() -> Self {
+        Self { method: "ping", params: None }
+    }
+
+    fn new_book(method: &'static str, symbols: Vec<CompactString>, depth: u16, snapshot: Option<bool>) -> Self {
+        Self {
+            method,
+            params: Some(KrakenSubscriptionParams { channel: "book", symbol: Some(symbols), depth: Some(depth), snapshot }),
+        }
+    }
+}
+
+fn deserialize_optional_time<'de, D>(deserializer: D) -> Result<Option<u64>, D::Error>
+    where
+        D: Deserializer<'de>,
+{
+    deserialize_rfc3339_millis(deserializer).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::str::FromStr;
+
+    use crate::gates::kraken::md::model::{KrakenDataType, KrakenWsMessage, KrakenWsRequest};
+    use crate::utils::basic_types::Price;
+
+    #[test]
+    fn book_parsing() {
+        for (file, data_type) in [
+            ("tests/kraken_ws_book_snapshot.json", KrakenDataType::Snapshot),
+            ("tests/kraken_ws_book_update.json", KrakenDataType::Update),
+        ] {
+            let message: KrakenWsMessage = serde_json::from_str(&fs::read_to_string(file).unwrap()).unwrap();
+            let KrakenWsMessage::Book(message) = message else {
+                panic!("expected book, got {message:?}");
+            };
+            assert_eq!(message.data_type, data_type);
+            assert_eq!(message.data[0].symbol, "MATIC/USD");
+        }
+
+        let update_str = fs::read_to_string("tests/kraken_ws_book_update.json").unwrap();
+        let KrakenWsMessage::Book(update) = serde_json::from_str(&update_str).unwrap() else {
+            panic!("expected book");
+        };
+        let book = &update.data[0];
+        assert_eq!(book.bids[0].price, Price::from_str("0.5657").unwrap());
+        assert_eq!(book.bids[0].qty, Price::from_str("1098.3947558").unwrap());
+        assert_eq!(book.timestamp, Some(1696613755440));
+    }
+
+    #[test]
+    fn instrument_and_responses_parsing() {
+        let instrument_str = r#"{"channel":"instrument","type":"snapshot","data":{"assets":[],"pairs":[{"symbol":"BTC/USD","base":"BTC","quote":"USD","status":"online","qty_precision":8,"qty_increment":0.00000001,"price_precision":1,"cost_precision":5,"marginable":true,"has_index":true,"cost_min":0.5,"tick_size":0.1,"price_increment":0.1,"qty_min":0.0001}]}}"#;
+        let message: KrakenWsMessage = serde_json::from_str(instrument_str).unwrap();
+        assert!(matches!(message, KrakenWsMessage::Instrument(m) if m.data.pairs[0].price_precision == 1));
+
+        let error_str = r#"{"error":"Currency pair not supported BTC/XXX","method":"subscribe","success":false,"symbol":"BTC/XXX","time_in":"2023-10-06T17:35:55.440295Z","time_out":"2023-10-06T17:35:55.440347Z"}"#;
+        let message: KrakenWsMessage = serde_json::from_str(error_str).unwrap();
+        assert!(matches!(message, KrakenWsMessage::MethodResponse(r) if r.success == Some(false)));
+
+        let message: KrakenWsMessage = serde_json::from_str(r#"{"channel":"heartbeat"}"#).unwrap();
+        assert!(matches!(message, KrakenWsMessage::Channel(c) if c.channel == "heartbeat"));
+
+        assert_eq!(
+            serde_json::to_string(&KrakenWsRequest::new_book_subscribe(vec!["BTC/USD".into()], 10)).unwrap(),
+            r#"{"method":"subscribe","params":{"channel":"book","symbol":["BTC/USD"],"depth":10,"snapshot":true}}"#,
+        );
+        assert_eq!(serde_json::to_string(&KrakenWsRequest::ping()).unwrap(), r#"{"method":"ping"}"#);
+    }
+}
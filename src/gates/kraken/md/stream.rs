@@ -0,0 +1,33 @@
+use compact_str::CompactString;
+
+use crate::gates::kraken::md::model::KrakenWsRequest;
+use crate::model::stream::WsStream;
+
+/// book: `snapshot` of the subscribed depth followed by `update` messages, both carrying the checksum.
+/// Kraken doesn't remove the levels pushed out of the subscribed depth, the book has to be truncated locally.
+///
+/// https://docs.kraken.com/api/docs/websocket-v2/book
+#[derive(Clone)]
+pub struct KrakenStream {
+    /// Native symbols, e.g. BTC/USD
+    pub symbols: Vec<CompactString>,
+    pub depth: u16,
+}
+
+impl WsStream for KrakenStream {
+    type Kind = u16;
+    type Subscribe = KrakenWsRequest;
+    type Login = ();
+
+    fn kind(&self) -> Self::Kind {
+        self.depth
+    }
+
+    /// The instrument snapshot goes first, so that the pair precisions are known by the time the books arrive
+    fn subscribe_requests(&self) -> Vec<Self::Subscribe> {
+        vec![
+            KrakenWsRequest::new_instrument_subscribe(),
+            KrakenWsRequest::new_book_subscribe(self.symbols.clone(), self.depth),
+        ]
+    }
+}
@@ -1 +1,6 @@
-mod okex;
\ No newline at end of file
+pub mod binance;
+pub mod bybit;
+pub mod coinbase;
+pub mod deribit;
+pub mod kraken;
+pub mod okex;
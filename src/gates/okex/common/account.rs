@@ -0,0 +1,329 @@
+use compact_str::CompactString;
+use fixnum::ops::Zero;
+use serde::{Deserialize, Serialize};
+
+use crate::gates::okex::common::error::OkexErrorResponse;
+use crate::gates::okex::common::symbol::OkexSymbols;
+use crate::model::account::{Balance, Position, PositionSide};
+use crate::model::exchange::Exchange;
+use crate::model::internal::Side;
+use crate::model::order::{Fill, OrderAck, OrderStatus, OrderType, OrderUpdate, TimeInForce};
+use crate::model::symbol::SymbolMapping;
+use crate::utils::basic_types::{Amount, deserialize_optional_decimal, deserialize_u64, Price};
+
+/// Order of `orders` channel, `/api/v5/trade/orders-pending` and the other order endpoints
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OkexOrder {
+    pub inst_id: CompactString,
+    pub ord_id: CompactString,
+    /// Empty if not set by the client
+    pub cl_ord_id: CompactString,
+    #[serde(deserialize_with = "deserialize_optional_decimal")]
+    pub px: Option<Price>,
+    pub sz: Amount,
+    pub ord_type: OkexOrderType,
+    pub side: OkexOrderSide,
+    /// Last fill, empty if the update isn't caused by a fill
+    pub trade_id: CompactString,
+    #[serde(deserialize_with = "deserialize_optional_decimal")]
+    pub fill_px: Option<Price>,
+    #[serde(deserialize_with = "deserialize_optional_decimal")]
+    pub fill_sz: Option<Amount>,
+    /// Negative when charged, positive for rebates. Pushed by the websocket only
+    #[serde(default, deserialize_with = "deserialize_optional_decimal")]
+    pub fill_fee: Option<Amount>,
+    #[serde(default)]
+    pub fill_fee_ccy: CompactString,
+    pub acc_fill_sz: Amount,
+    #[serde(deserialize_with = "deserialize_optional_decimal")]
+    pub avg_px: Option<Price>,
+    pub state: OkexOrderState,
+    /// Update time, Unix timestamp format in milliseconds
+    #[serde(deserialize_with = "deserialize_u64")]
+    pub u_time: u64,
+}
+
+#[derive(Debug, Deserialize, Serialize, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum OkexOrderType {
+    Market,
+    Limit,
+    PostOnly,
+    Fok,
+    Ioc,
+    /// Market order with immediate-or-cancel, futures and perpetual swaps only
+    OptimalLimitIoc,
+    /// Market maker protection order
+    Mmp,
+    /// Market maker protection and post only order
+    MmpAndPostOnly,
+}
+
+impl OkexOrderType {
+    pub fn to_internal(&self) -> (OrderType, TimeInForce) {
+        match self {
+            OkexOrderType::Market => (OrderType::Market, TimeInForce::Ioc),
+            OkexOrderType::Limit | OkexOrderType::Mmp => (OrderType::Limit, TimeInForce::Gtc),
+            OkexOrderType::PostOnly | OkexOrderType::MmpAndPostOnly => (OrderType::Limit, TimeInForce::PostOnly),
+            OkexOrderType::Fok => (OrderType::Limit, TimeInForce::Fok),
+            OkexOrderType::Ioc => (OrderType::Limit, TimeInForce::Ioc),
+            OkexOrderType::OptimalLimitIoc => (OrderType::Market, TimeInForce::Ioc),
+        }
+    }
+
+    pub fn from_internal(order_type: OrderType, time_in_force: TimeInForce) -> Self {
+        match (order_type, time_in_force) {
+            (OrderType::Market, _) => OkexOrderType::Market,
+            (OrderType::Limit, TimeInForce::Gtc) => OkexOrderType::Limit,
+            (OrderType::Limit, TimeInForce::PostOnly) => OkexOrderType::PostOnly,
+            (OrderType::Limit, TimeInForce::Ioc) => OkexOrderType::Ioc,
+            (OrderType::Limit, TimeInForce::Fok) => OkexOrderType::Fok,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OkexOrderSide {
+    Buy,
+    Sell,
+}
+
+impl From<OkexOrderSide> for Side {
+    fn from(side: OkexOrderSide) -> Self {
+        match side {
+            OkexOrderSide::Buy => Side::Bid,
+            OkexOrderSide::Sell => Side::Ask,
+        }
+    }
+}
+
+impl From<Side> for OkexOrderSide {
+    fn from(side: Side) -> Self {
+        match side {
+            Side::Bid => OkexOrderSide::Buy,
+            Side::Ask => OkexOrderSide::Sell,
+        }
+    }
+}
+
+/// Margin mode of an order, `cash` for non-margin spot trading
+#[derive(Debug, Deserialize, Serialize, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OkexTradeMode {
+    Cash,
+    Cross,
+    Isolated,
+}
+
+/// Result of a single place, cancel or amend operation, also reported per item for the batch ones
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OkexOrderAck {
+    pub ord_id: CompactString,
+    pub cl_ord_id: CompactString,
+    /// `0` for success
+    pub s_code: CompactString,
+    pub s_msg: CompactString,
+}
+
+impl OkexOrderAck {
+    pub fn to_internal(&self) -> eyre::Result<OrderAck> {
+        if self.s_code != "0" {
+            return Err(OkexErrorResponse {
+                code: self.s_code.clone(),
+                msg: self.s_msg.clone(),
+            }.into());
+        }
+        Ok(OrderAck {
+            order_id: self.ord_id.clone(),
+            client_order_id: Some(self.cl_ord_id.clone()).filter(|id| !id.is_empty()),
+        })
+    }
+}
+
+#[derive(Debug, Deserialize, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum OkexOrderState {
+    Live,
+    PartiallyFilled,
+    Filled,
+    Canceled,
+    /// Canceled automatically by market maker protection
+    MmpCanceled,
+}
+
+impl From<OkexOrderState> for OrderStatus {
+    fn from(state: OkexOrderState) -> Self {
+        match state {
+            OkexOrderState::Live => OrderStatus::New,
+            OkexOrderState::PartiallyFilled => OrderStatus::PartiallyFilled,
+            OkexOrderState::Filled => OrderStatus::Filled,
+            OkexOrderState::Canceled | OkexOrderState::MmpCanceled => OrderStatus::Canceled,
+        }
+    }
+}
+
+impl OkexOrder {
+    pub fn to_internal(&self) -> eyre::Result<OrderUpdate> {
+        let (order_type, time_in_force) = self.ord_type.to_internal();
+        let fill = match (self.fill_px, self.fill_sz) {
+            (Some(price), Some(amount)) if !self.trade_id.is_empty() && amount > Amount::ZERO => Some(Fill {
+                trade_id: self.trade_id.clone(),
+                price,
+                amount,
+                fee: self.fill_fee.unwrap_or(Amount::ZERO).cneg()?,
+                fee_currency: self.fill_fee_ccy.clone(),
+            }),
+            _ => None,
+        };
+        Ok(OrderUpdate {
+            exchange_time: Some(self.u_time),
+            exchange: Exchange::Okex,
+            symbol: OkexSymbols::from_native(&self.inst_id)?,
+            instrument_id: None,
+            order_id: self.ord_id.clone(),
+            client_order_id: Some(self.cl_ord_id.clone()).filter(|id| !id.is_empty()),
+            side: self.side.into(),
+            order_type,
+            time_in_force,
+            price: self.px,
+            amount: self.sz,
+            filled_amount: self.acc_fill_sz,
+            avg_fill_price: self.avg_px.filter(|px| *px > Price::ZERO),
+            status: self.state.into(),
+            fill,
+        })
+    }
+}
+
+#[derive(Debug, Deserialize, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OkexPositionSide {
+    Net,
+    Long,
+    Short,
+}
+
+impl From<OkexPositionSide> for PositionSide {
+    fn from(side: OkexPositionSide) -> Self {
+        match side {
+            OkexPositionSide::Net => PositionSide::Net,
+            OkexPositionSide::Long => PositionSide::Long,
+            OkexPositionSide::Short => PositionSide::Short,
+        }
+    }
+}
+
+/// Position of `positions` channel and `/api/v5/account/positions`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OkexPosition {
+    pub inst_id: CompactString,
+    pub pos_id: CompactString,
+    pub pos_side: OkexPositionSide,
+    /// Signed for `net` positions, empty when the position is closed
+    #[serde(deserialize_with = "deserialize_optional_decimal")]
+    pub pos: Option<Amount>,
+    #[serde(deserialize_with = "deserialize_optional_decimal")]
+    pub avg_px: Option<Price>,
+    #[serde(default, deserialize_with = "deserialize_optional_decimal")]
+    pub upl: Option<Amount>,
+    #[serde(deserialize_with = "deserialize_u64")]
+    pub u_time: u64,
+}
+
+impl OkexPosition {
+    pub fn to_internal(&self) -> eyre::Result<Position> {
+        Ok(Position {
+            exchange_time: Some(self.u_time),
+            exchange: Exchange::Okex,
+            symbol: OkexSymbols::from_native(&self.inst_id)?,
+            instrument_id: None,
+            side: self.pos_side.into(),
+            amount: self.pos.unwrap_or(Amount::ZERO),
+            avg_price: self.avg_px,
+            unrealized_pnl: self.upl,
+        })
+    }
+}
+
+/// Account of `account` channel and `/api/v5/account/balance`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OkexAccount {
+    pub details: Vec<OkexBalanceDetails>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OkexBalanceDetails {
+    pub ccy: CompactString,
+    #[serde(deserialize_with = "deserialize_optional_decimal")]
+    pub eq: Option<Amount>,
+    #[serde(deserialize_with = "deserialize_optional_decimal")]
+    pub avail_bal: Option<Amount>,
+    #[serde(deserialize_with = "deserialize_optional_decimal")]
+    pub frozen_bal: Option<Amount>,
+    #[serde(deserialize_with = "deserialize_u64")]
+    pub u_time: u64,
+}
+
+impl OkexBalanceDetails {
+    pub fn to_internal(&self) -> Balance {
+        Balance {
+            exchange_time: Some(self.u_time),
+            exchange: Exchange::Okex,
+            currency: self.ccy.clone(),
+            total: self.eq.unwrap_or(Amount::ZERO),
+            available: self.avail_bal,
+            frozen: self.frozen_bal,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::str::FromStr;
+
+    use crate::gates::okex::common::account::{OkexOrder, OkexOrderAck};
+    use crate::gates::okex::common::response::OkexResponse;
+    use crate::model::order::OrderStatus;
+    use crate::utils::basic_types::Amount;
+
+    #[test]
+    fn pending_orders_parsing() {
+        let orders_str = fs::read_to_string("tests/okex_orders_pending.json").unwrap();
+        let response: OkexResponse<OkexOrder> = serde_json::from_str(&orders_str).unwrap();
+        let orders = response.into_result().unwrap();
+
+        let [order] = orders.as_slice() else {
+            panic!("expected single order");
+        };
+        let update = order.to_internal().unwrap();
+        assert_eq!(update.client_order_id.as_deref(), Some("b1"));
+        assert_eq!(update.status, OrderStatus::PartiallyFilled);
+        assert_eq!(update.filled_amount, Amount::from_str("0.0005").unwrap());
+        // the REST endpoints don't report the fee of the last fill
+        assert_eq!(update.fill.unwrap().fee, Amount::from_str("0").unwrap());
+    }
+
+    #[test]
+    fn batch_ack_parsing() {
+        let response: OkexResponse<OkexOrderAck> = serde_json::from_str(r#"{
+            "code": "2",
+            "msg": "Bulk operation partially succeeded.",
+            "data": [
+                {"clOrdId": "b1", "ordId": "12345689", "tag": "", "sCode": "0", "sMsg": ""},
+                {"clOrdId": "b2", "ordId": "", "tag": "", "sCode": "51008", "sMsg": "Insufficient balance"}
+            ]
+        }"#).unwrap();
+        let acks: Vec<_> = response.into_batch_result().unwrap().iter().map(OkexOrderAck::to_internal).collect();
+
+        let ack = acks[0].as_ref().unwrap();
+        assert_eq!((ack.order_id.as_str(), ack.client_order_id.as_deref()), ("12345689", Some("b1")));
+        assert_eq!(acks[1].as_ref().unwrap_err().to_string(), "51008:Insufficient balance");
+    }
+}
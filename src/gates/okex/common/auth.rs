@@ -0,0 +1,142 @@
+use std::fmt::{Debug, Formatter};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use compact_str::{CompactString, ToCompactString};
+use hmac::{Hmac, Mac};
+use http::{HeaderMap, HeaderValue, Method};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+use crate::api::api::RequestSigner;
+use crate::utils::basic_types::format_rfc3339_millis;
+
+/// Path signed on websocket login
+const WS_LOGIN_PATH: &str = "/users/self/verify";
+
+#[derive(Deserialize, Clone)]
+pub struct OkexCredentials {
+    pub api_key: CompactString,
+    pub secret_key: CompactString,
+    pub passphrase: CompactString,
+}
+
+impl Debug for OkexCredentials {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OkexCredentials")
+            .field("api_key", &self.api_key)
+            .finish_non_exhaustive()
+    }
+}
+
+impl OkexCredentials {
+    /// Base64 encoded HMAC SHA256 of `timestamp + method + request_path + body`
+    ///
+    /// https://www.okx.com/docs-v5/en/#overview-rest-authentication-signature
+    pub fn sign(&self, timestamp: &str, method: &str, request_path: &str, body: &str) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(self.secret_key.as_bytes())
+            .expect("HMAC accepts keys of any size");
+        mac.update(timestamp.as_bytes());
+        mac.update(method.as_bytes());
+        mac.update(request_path.as_bytes());
+        mac.update(body.as_bytes());
+        STANDARD.encode(mac.finalize().into_bytes())
+    }
+
+    /// Arguments of the websocket `login` op, signed with the current time
+    ///
+    /// https://www.okx.com/docs-v5/en/#overview-websocket-login
+    pub fn login_args(&self) -> OkexLoginArgs {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs()
+            .to_compact_string();
+        self.login_args_at(timestamp)
+    }
+
+    fn login_args_at(&self, timestamp: CompactString) -> OkexLoginArgs {
+        let sign = self.sign(&timestamp, "GET", WS_LOGIN_PATH, "").into();
+        OkexLoginArgs {
+            api_key: self.api_key.clone(),
+            passphrase: self.passphrase.clone(),
+            timestamp,
+            sign,
+        }
+    }
+}
+
+impl RequestSigner for OkexCredentials {
+    /// https://www.okx.com/docs-v5/en/#overview-rest-authentication-making-requests
+    fn sign(&self, method: &Method, request_path: &str, body: &str) -> eyre::Result<HeaderMap> {
+        let millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        self.rest_headers_at(&format_rfc3339_millis(millis), method, request_path, body)
+    }
+}
+
+impl OkexCredentials {
+    fn rest_headers_at(&self, timestamp: &str, method: &Method, request_path: &str, body: &str) -> eyre::Result<HeaderMap> {
+        let sign = self.sign(timestamp, method.as_str(), request_path, body);
+        let mut headers = HeaderMap::new();
+        headers.insert("OK-ACCESS-KEY", HeaderValue::from_str(&self.api_key)?);
+        headers.insert("OK-ACCESS-SIGN", HeaderValue::from_str(&sign)?);
+        headers.insert("OK-ACCESS-TIMESTAMP", HeaderValue::from_str(timestamp)?);
+        headers.insert("OK-ACCESS-PASSPHRASE", HeaderValue::from_str(&self.passphrase)?);
+        Ok(headers)
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OkexLoginArgs {
+    api_key: CompactString,
+    passphrase: CompactString,
+    /// Unix timestamp in seconds
+    timestamp: CompactString,
+    sign: CompactString,
+}
+
+#[cfg(test)]
+mod tests {
+    use http::Method;
+
+    use crate::gates::okex::common::auth::OkexCredentials;
+
+    fn credentials() -> OkexCredentials {
+        OkexCredentials {
+            api_key: "985d5b66-57ce-40fb-b714-afc0b9787083".into(),
+            secret_key: "22582BD0CFF14C41EDBF1AB98506286D".into(),
+            passphrase: "123456".into(),
+        }
+    }
+
+    #[test]
+    fn login_signature() {
+        let args = credentials().login_args_at("1538054050".into());
+        assert_eq!(args.sign, "+LdIr8lkkvhr5hoA3g9TMC0+uQJ849ftAcocA/ouu4M=");
+    }
+
+    #[test]
+    fn rest_headers() {
+        let credentials = credentials();
+        let timestamp = "2020-12-08T09:08:57.715Z";
+        let headers = credentials
+            .rest_headers_at(timestamp, &Method::GET, "/api/v5/account/balance?ccy=BTC", "")
+            .unwrap();
+        assert_eq!(headers["OK-ACCESS-KEY"], "985d5b66-57ce-40fb-b714-afc0b9787083");
+        assert_eq!(headers["OK-ACCESS-TIMESTAMP"], timestamp);
+        assert_eq!(headers["OK-ACCESS-PASSPHRASE"], "123456");
+        assert_eq!(headers["OK-ACCESS-SIGN"], "HiZhvSfMtWJA3uUIVXV3a/bSXNPCWvYFXoGCVS8V4zY=");
+    }
+
+    #[test]
+    fn debug_hides_secrets() {
+        let debug = format!("{:?}", credentials());
+        assert!(!debug.contains("22582BD0CFF14C41EDBF1AB98506286D"));
+        assert!(!debug.contains("123456"));
+    }
+}
@@ -1,2 +1,6 @@
+pub mod account;
+pub mod auth;
+pub mod model;
 pub mod response;
-mod error;
+pub mod symbol;
+pub mod error;
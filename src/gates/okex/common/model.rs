@@ -0,0 +1,433 @@
+use compact_str::CompactString;
+use eyre::OptionExt;
+use serde::{Deserialize, Serialize};
+
+use crate::gates::okex::common::symbol::OkexSymbols;
+use crate::model::exchange::Exchange;
+use crate::model::instrument::{Instrument, InstrumentKind, InstrumentState};
+use crate::model::internal::{
+    Candle,
+    CandleInterval,
+    FundingRate,
+    IndexPrice,
+    MarkPrice,
+    MdInstrument,
+    OpenInterest,
+    Ticker,
+};
+use crate::model::symbol::{Symbol, SymbolKind, SymbolMapping};
+use crate::utils::basic_types::{Amount, deserialize_optional_decimal, deserialize_optional_u64, deserialize_u64, Price};
+
+#[derive(Debug, Deserialize, Serialize, Copy, Clone, PartialEq, Eq, Hash)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum OkexInstType {
+    Spot,
+    Margin,
+    Swap,
+    Futures,
+    Option,
+}
+
+impl OkexInstType {
+    /// Spot symbols are mapped to `SPOT`, though margin trading uses the same instruments as `MARGIN`.
+    /// Indices have no instrument type and are mapped like spot.
+    pub fn from_symbol(symbol: &Symbol) -> Self {
+        match symbol.kind {
+            SymbolKind::Spot | SymbolKind::Index => OkexInstType::Spot,
+            SymbolKind::Perpetual => OkexInstType::Swap,
+            SymbolKind::Future { .. } => OkexInstType::Futures,
+            SymbolKind::Option { .. } => OkexInstType::Option,
+        }
+    }
+}
+
+/// Payload of both `tickers` websocket channel and `/api/v5/market/ticker(s)` endpoints
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OkexTicker {
+    pub inst_type: OkexInstType,
+    pub inst_id: CompactString,
+    /// Last traded price
+    #[serde(deserialize_with = "deserialize_optional_decimal")]
+    pub last: Option<Price>,
+    /// Last traded size
+    #[serde(deserialize_with = "deserialize_optional_decimal")]
+    pub last_sz: Option<Amount>,
+    #[serde(deserialize_with = "deserialize_optional_decimal")]
+    pub ask_px: Option<Price>,
+    #[serde(deserialize_with = "deserialize_optional_decimal")]
+    pub ask_sz: Option<Amount>,
+    #[serde(deserialize_with = "deserialize_optional_decimal")]
+    pub bid_px: Option<Price>,
+    #[serde(deserialize_with = "deserialize_optional_decimal")]
+    pub bid_sz: Option<Amount>,
+    /// 24h trading volume, with a unit of currency.
+    /// If it is a derivatives contract, the value is the number of base currency.
+    /// If it is SPOT/MARGIN, the value is the quantity in quote currency.
+    #[serde(deserialize_with = "deserialize_optional_decimal")]
+    pub vol_ccy24h: Option<Amount>,
+    /// 24h trading volume, with a unit of contract.
+    /// If it is a derivatives contract, the value is the number of contracts.
+    /// If it is SPOT/MARGIN, the value is the quantity in base currency.
+    #[serde(deserialize_with = "deserialize_optional_decimal")]
+    pub vol24h: Option<Amount>,
+    /// Ticker data generation time, Unix timestamp format in milliseconds
+    #[serde(deserialize_with = "deserialize_u64")]
+    pub ts: u64,
+}
+
+impl OkexTicker {
+    pub fn to_internal(&self) -> eyre::Result<Ticker> {
+        Ok(Ticker {
+            exchange_time: Some(self.ts),
+            exchange: Exchange::Okex,
+            instrument: MdInstrument::Symbol(OkexSymbols::from_native(&self.inst_id)?),
+            bid_price: self.bid_px,
+            bid_amount: self.bid_sz,
+            ask_price: self.ask_px,
+            ask_amount: self.ask_sz,
+            last_price: self.last,
+            last_amount: self.last_sz,
+            volume_24h: self.vol24h,
+            currency_volume_24h: self.vol_ccy24h,
+        })
+    }
+}
+
+/// Payload of `funding-rate` channel and `/api/v5/public/funding-rate` endpoint
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OkexFundingRate {
+    pub inst_type: OkexInstType,
+    pub inst_id: CompactString,
+    pub funding_rate: Price,
+    /// Settlement time, Unix timestamp format in milliseconds
+    #[serde(deserialize_with = "deserialize_u64")]
+    pub funding_time: u64,
+    /// Forecasted funding rate for the next period, empty for the current-period method
+    #[serde(default, deserialize_with = "deserialize_optional_decimal")]
+    pub next_funding_rate: Option<Price>,
+    #[serde(default, deserialize_with = "deserialize_optional_u64")]
+    pub next_funding_time: Option<u64>,
+    /// Data return time, Unix timestamp format in milliseconds
+    #[serde(default, deserialize_with = "deserialize_optional_u64")]
+    pub ts: Option<u64>,
+}
+
+impl OkexFundingRate {
+    pub fn to_internal(&self) -> eyre::Result<FundingRate> {
+        Ok(FundingRate {
+            exchange_time: self.ts,
+            exchange: Exchange::Okex,
+            instrument: MdInstrument::Symbol(OkexSymbols::from_native(&self.inst_id)?),
+            rate: self.funding_rate,
+            funding_time: self.funding_time,
+            next_rate: self.next_funding_rate,
+            next_funding_time: self.next_funding_time,
+        })
+    }
+}
+
+/// Payload of `mark-price` channel and `/api/v5/public/mark-price` endpoint
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OkexMarkPrice {
+    pub inst_type: OkexInstType,
+    pub inst_id: CompactString,
+    pub mark_px: Price,
+    #[serde(deserialize_with = "deserialize_u64")]
+    pub ts: u64,
+}
+
+impl OkexMarkPrice {
+    pub fn to_internal(&self) -> eyre::Result<MarkPrice> {
+        Ok(MarkPrice {
+            exchange_time: Some(self.ts),
+            exchange: Exchange::Okex,
+            instrument: MdInstrument::Symbol(OkexSymbols::from_native(&self.inst_id)?),
+            price: self.mark_px,
+        })
+    }
+}
+
+/// Payload of `index-tickers` channel and `/api/v5/market/index-tickers` endpoint
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OkexIndexTicker {
+    /// Index, e.g. BTC-USD
+    pub inst_id: CompactString,
+    /// Latest index price
+    pub idx_px: Price,
+    #[serde(deserialize_with = "deserialize_u64")]
+    pub ts: u64,
+}
+
+impl OkexIndexTicker {
+    pub fn to_internal(&self) -> eyre::Result<IndexPrice> {
+        Ok(IndexPrice {
+            exchange_time: Some(self.ts),
+            exchange: Exchange::Okex,
+            instrument: MdInstrument::Symbol(OkexSymbols::index_from_native(&self.inst_id)?),
+            price: self.idx_px,
+        })
+    }
+}
+
+/// Payload of `open-interest` channel and `/api/v5/public/open-interest` endpoint
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OkexOpenInterest {
+    pub inst_type: OkexInstType,
+    pub inst_id: CompactString,
+    /// Open interest in number of contracts
+    pub oi: Amount,
+    /// Open interest in number of coin
+    #[serde(default, deserialize_with = "deserialize_optional_decimal")]
+    pub oi_ccy: Option<Amount>,
+    #[serde(deserialize_with = "deserialize_u64")]
+    pub ts: u64,
+}
+
+impl OkexOpenInterest {
+    pub fn to_internal(&self) -> eyre::Result<OpenInterest> {
+        Ok(OpenInterest {
+            exchange_time: Some(self.ts),
+            exchange: Exchange::Okex,
+            instrument: MdInstrument::Symbol(OkexSymbols::from_native(&self.inst_id)?),
+            open_interest: self.oi,
+            open_interest_currency: self.oi_ccy,
+        })
+    }
+}
+
+/// Payload of `/api/v5/public/instruments` endpoint
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OkexInstrument {
+    pub inst_type: OkexInstType,
+    pub inst_id: CompactString,
+    /// Underlying, e.g. BTC-USD, only applicable to derivatives
+    pub uly: CompactString,
+    /// Base currency, only applicable to SPOT/MARGIN
+    pub base_ccy: CompactString,
+    /// Quote currency, only applicable to SPOT/MARGIN
+    pub quote_ccy: CompactString,
+    /// Settlement and margin currency, only applicable to derivatives
+    pub settle_ccy: CompactString,
+    /// Contract value, only applicable to derivatives
+    #[serde(deserialize_with = "deserialize_optional_decimal")]
+    pub ct_val: Option<Amount>,
+    /// Expiry time, Unix timestamp format in milliseconds, only applicable to FUTURES/OPTION
+    #[serde(deserialize_with = "deserialize_optional_u64")]
+    pub exp_time: Option<u64>,
+    pub tick_sz: Price,
+    /// Lot size, in base currency for SPOT/MARGIN and in contracts for derivatives
+    pub lot_sz: Amount,
+    pub min_sz: Amount,
+    pub state: OkexInstState,
+}
+
+#[derive(Debug, Deserialize, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OkexInstState {
+    Live,
+    Suspend,
+    Preopen,
+    /// Test trading, not available to regular users
+    Test,
+}
+
+impl OkexInstrument {
+    pub fn to_internal(&self) -> eyre::Result<Instrument> {
+        let (base_currency, quote_currency) = if self.base_ccy.is_empty() {
+            let (base, quote) = self.uly
+                .split_once('-')
+                .ok_or_eyre(format!("Okex instrument {} has neither base currency nor underlying", self.inst_id))?;
+            (base.into(), quote.into())
+        } else {
+            (self.base_ccy.clone(), self.quote_ccy.clone())
+        };
+        let kind = match self.inst_type {
+            OkexInstType::Spot => InstrumentKind::Spot,
+            OkexInstType::Margin => InstrumentKind::Margin,
+            OkexInstType::Swap => InstrumentKind::Perpetual,
+            OkexInstType::Futures => InstrumentKind::Future,
+            OkexInstType::Option => InstrumentKind::Option,
+        };
+        let state = match self.state {
+            OkexInstState::Live => InstrumentState::Live,
+            OkexInstState::Suspend => InstrumentState::Suspended,
+            OkexInstState::Preopen => InstrumentState::PreOpen,
+            OkexInstState::Test => InstrumentState::Test,
+        };
+
+        Ok(Instrument {
+            exchange: Exchange::Okex,
+            symbol: self.inst_id.clone(),
+            internal_symbol: OkexSymbols::from_native(&self.inst_id)?,
+            kind,
+            base_currency,
+            quote_currency,
+            settle_currency: Some(self.settle_ccy.clone()).filter(|c| !c.is_empty()),
+            tick_size: self.tick_sz,
+            lot_size: self.lot_sz,
+            min_size: self.min_sz,
+            contract_value: self.ct_val,
+            expiry: self.exp_time,
+            state,
+        })
+    }
+}
+
+/// Bar size, used both in `candle{bar}` channel names and in the `bar` parameter of the candles endpoints
+pub fn okex_bar(interval: CandleInterval) -> &'static str {
+    match interval {
+        CandleInterval::Minute1 => "1m",
+        CandleInterval::Minute3 => "3m",
+        CandleInterval::Minute5 => "5m",
+        CandleInterval::Minute15 => "15m",
+        CandleInterval::Minute30 => "30m",
+        CandleInterval::Hour1 => "1H",
+        CandleInterval::Hour2 => "2H",
+        CandleInterval::Hour4 => "4H",
+        CandleInterval::Hour6 => "6H",
+        CandleInterval::Hour12 => "12H",
+        CandleInterval::Day1 => "1D",
+        CandleInterval::Week1 => "1W",
+        CandleInterval::Month1 => "1M",
+    }
+}
+
+pub fn from_okex_bar(bar: &str) -> Option<CandleInterval> {
+    [
+        CandleInterval::Minute1,
+        CandleInterval::Minute3,
+        CandleInterval::Minute5,
+        CandleInterval::Minute15,
+        CandleInterval::Minute30,
+        CandleInterval::Hour1,
+        CandleInterval::Hour2,
+        CandleInterval::Hour4,
+        CandleInterval::Hour6,
+        CandleInterval::Hour12,
+        CandleInterval::Day1,
+        CandleInterval::Week1,
+        CandleInterval::Month1,
+    ]
+        .into_iter()
+        .find(|interval| okex_bar(*interval) == bar)
+}
+
+/// Payload of `candle{bar}` channels and `/api/v5/market/(history-)candles` endpoints,
+/// sent as an array of strings
+#[derive(Debug, Deserialize)]
+pub struct OkexCandle {
+    /// Opening time of the candlestick, Unix timestamp format in milliseconds
+    #[serde(deserialize_with = "deserialize_u64")]
+    pub ts: u64,
+    pub open: Price,
+    pub high: Price,
+    pub low: Price,
+    pub close: Price,
+    /// Trading volume, in contracts for derivatives and in base currency for spot
+    pub vol: Amount,
+    /// Trading volume, in base currency for derivatives and in quote currency for spot
+    pub vol_ccy: Amount,
+    /// Trading volume, in quote currency
+    pub vol_ccy_quote: Amount,
+    /// "0" represents that it is uncompleted, "1" represents that it is completed
+    pub confirm: CompactString,
+}
+
+impl OkexCandle {
+    pub fn to_internal(&self, symbol: Symbol, interval: CandleInterval) -> Candle {
+        Candle {
+            exchange: Exchange::Okex,
+            instrument: MdInstrument::Symbol(symbol),
+            interval,
+            open_time: self.ts,
+            open: self.open,
+            high: self.high,
+            low: self.low,
+            close: self.close,
+            volume: self.vol,
+            quote_volume: self.vol_ccy_quote,
+            confirmed: self.confirm == "1",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::gates::okex::common::model::{from_okex_bar, okex_bar, OkexCandle, OkexInstrument, OkexTicker};
+    use crate::gates::okex::common::response::OkexResponse;
+    use crate::model::instrument::{InstrumentKind, InstrumentState};
+    use crate::model::internal::{CandleInterval, MdInstrument};
+    use crate::model::symbol::Symbol;
+
+    #[test]
+    fn tickers_parsing() {
+        let tickers_str = fs::read_to_string("tests/tickers.json").unwrap();
+        let response: OkexResponse<OkexTicker> = serde_json::from_str(&tickers_str).unwrap();
+        let tickers = response.into_result().unwrap();
+
+        assert_eq!(tickers.len(), 2);
+        let ticker = tickers[1].to_internal().unwrap();
+        assert_eq!(ticker.instrument, MdInstrument::Symbol(Symbol::perpetual("BTC", "USDT")));
+        assert_eq!(ticker.bid_price, None);
+        assert!(ticker.ask_price.is_some());
+    }
+
+    #[test]
+    fn candles_parsing() {
+        let candles_str = fs::read_to_string("tests/candles.json").unwrap();
+        let response: OkexResponse<OkexCandle> = serde_json::from_str(&candles_str).unwrap();
+        let candles = response.into_result().unwrap();
+
+        assert_eq!(candles.len(), 2);
+        let candle = candles[0].to_internal(Symbol::spot("BTC", "USDT"), CandleInterval::Minute1);
+        assert_eq!(candle.open_time, 1597026383085);
+        assert!(!candle.confirmed);
+        assert!(candles[1].to_internal(Symbol::spot("BTC", "USDT"), CandleInterval::Minute1).confirmed);
+    }
+
+    #[test]
+    fn instruments_parsing() {
+        let instruments_str = fs::read_to_string("tests/instruments.json").unwrap();
+        let response: OkexResponse<OkexInstrument> = serde_json::from_str(&instruments_str).unwrap();
+        let instruments: Vec<_> = response.into_result().unwrap()
+            .iter()
+            .map(|i| i.to_internal().unwrap())
+            .collect();
+
+        assert_eq!(instruments.len(), 3);
+        let spot = &instruments[0];
+        assert_eq!(spot.kind, InstrumentKind::Spot);
+        assert_eq!((spot.base_currency.as_str(), spot.quote_currency.as_str()), ("BTC", "USDT"));
+        assert_eq!(spot.settle_currency, None);
+        assert_eq!(spot.contract_value, None);
+        assert_eq!(spot.tick_size.to_string(), "0.1");
+
+        let swap = &instruments[1];
+        assert_eq!(swap.kind, InstrumentKind::Perpetual);
+        assert_eq!((swap.base_currency.as_str(), swap.quote_currency.as_str()), ("BTC", "USDT"));
+        assert_eq!(swap.settle_currency.as_deref(), Some("USDT"));
+        assert_eq!(swap.contract_value.unwrap().to_string(), "0.01");
+        assert_eq!(swap.expiry, None);
+
+        let future = &instruments[2];
+        assert_eq!(future.kind, InstrumentKind::Future);
+        assert_eq!(future.expiry, Some(1703232000000));
+        assert_eq!(future.state, InstrumentState::PreOpen);
+    }
+
+    #[test]
+    fn bar_mapping() {
+        assert_eq!(okex_bar(CandleInterval::Hour4), "4H");
+        assert_eq!(from_okex_bar("1M"), Some(CandleInterval::Month1));
+        assert_eq!(from_okex_bar("1m"), Some(CandleInterval::Minute1));
+        assert_eq!(from_okex_bar("7m"), None);
+    }
+}
@@ -21,4 +21,19 @@ impl<R> OkexResponse<R> {
             })
         }
     }
+
+    /// Data of the trade operations, see `batch_result`
+    pub fn into_batch_result(self) -> Result<Vec<R>, OkexErrorResponse> {
+        batch_result(self.code, self.msg, self.data)
+    }
+}
+
+/// Data of the trade operations of REST and websocket, which report the failures of the orders
+/// in the data items with code `1` if all of them failed and `2` if some of them did
+pub fn batch_result<R>(code: CompactString, msg: CompactString, data: Vec<R>) -> Result<Vec<R>, OkexErrorResponse> {
+    match code.as_str() {
+        "0" => Ok(data),
+        "1" | "2" if !data.is_empty() => Ok(data),
+        _ => Err(OkexErrorResponse { code, msg }),
+    }
 }
@@ -0,0 +1,83 @@
+use std::str::FromStr;
+
+use compact_str::{CompactString, format_compact};
+use eyre::eyre;
+
+use crate::model::symbol::{Expiry, format_strike, OptionType, Symbol, SymbolKind, SymbolMapping};
+use crate::utils::basic_types::Price;
+
+/// Okex instrument ids: `BTC-USDT` for spot, `BTC-USDT-SWAP` for perpetual swaps,
+/// `BTC-USD-231222` for futures and `BTC-USD-231222-30000-C` for options.
+/// Index ids, e.g. `BTC-USDT`, look like spot ones, so they are parsed by `index_from_native`.
+pub struct OkexSymbols;
+
+impl OkexSymbols {
+    pub fn index_from_native(native: &str) -> eyre::Result<Symbol> {
+        match native.split_once('-') {
+            Some((base, quote)) if !base.is_empty() && !quote.is_empty() && !quote.contains('-') => {
+                Ok(Symbol::index(base, quote))
+            }
+            _ => Err(eyre!("unknown Okex index id format {native}")),
+        }
+    }
+}
+
+impl SymbolMapping for OkexSymbols {
+    fn to_native(symbol: &Symbol) -> eyre::Result<CompactString> {
+        let Symbol { base, quote, kind } = symbol;
+        let native = match kind {
+            SymbolKind::Spot | SymbolKind::Index => format_compact!("{base}-{quote}"),
+            SymbolKind::Perpetual => format_compact!("{base}-{quote}-SWAP"),
+            SymbolKind::Future { expiry } => format_compact!("{base}-{quote}-{}", expiry.to_yymmdd()),
+            SymbolKind::Option { expiry, strike, option_type } => format_compact!(
+                "{base}-{quote}-{}-{}-{}",
+                expiry.to_yymmdd(),
+                format_strike(*strike),
+                option_type.letter(),
+            ),
+        };
+        Ok(native)
+    }
+
+    fn from_native(native: &str) -> eyre::Result<Symbol> {
+        let parts: Vec<_> = native.split('-').collect();
+        match parts[..] {
+            [base, quote] => Ok(Symbol::spot(base, quote)),
+            [base, quote, "SWAP"] => Ok(Symbol::perpetual(base, quote)),
+            [base, quote, expiry] => Ok(Symbol::future(base, quote, Expiry::from_yymmdd(expiry)?)),
+            [base, quote, expiry, strike, option_type] => Ok(Symbol::option(
+                base,
+                quote,
+                Expiry::from_yymmdd(expiry)?,
+                Price::from_str(strike).map_err(|_| eyre!("non-decimal strike in Okex instrument {native}"))?,
+                OptionType::from_letter(option_type)?,
+            )),
+            _ => Err(eyre!("unknown Okex instrument id format {native}")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::gates::okex::common::symbol::OkexSymbols;
+    use crate::model::symbol::{Symbol, SymbolMapping};
+
+    #[test]
+    fn symbol_mapping() {
+        let ids = ["BTC-USDT", "BTC-USDT-SWAP", "BTC-USD-231222", "BTC-USD-231222-30000-C", "ETH-USD-240329-2500-P"];
+        for id in ids {
+            let symbol = OkexSymbols::from_native(id).unwrap();
+            assert_eq!(OkexSymbols::to_native(&symbol).unwrap(), id);
+        }
+        assert_eq!(OkexSymbols::from_native("BTC-USD-231222-30000-C").unwrap().to_string(), "BTC/USD-20231222-30000-C");
+        assert_eq!(OkexSymbols::from_native("BTC-USDT-SWAP").unwrap(), Symbol::perpetual("BTC", "USDT"));
+        assert!(OkexSymbols::from_native("BTCUSDT").is_err());
+
+        let index = OkexSymbols::index_from_native("BTC-USD").unwrap();
+        assert_eq!(index, Symbol::index("BTC", "USD"));
+        assert_ne!(index, OkexSymbols::from_native("BTC-USD").unwrap());
+        assert_eq!(OkexSymbols::to_native(&index).unwrap(), "BTC-USD");
+        assert!(OkexSymbols::index_from_native("BTC-USD-SWAP").is_err());
+        assert!(OkexSymbols::from_native("BTC-USD-2312").is_err());
+    }
+}
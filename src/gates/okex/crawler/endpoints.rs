@@ -1,9 +1,28 @@
 use http::Method;
 
 use crate::api::endpoint::Endpoint;
+use crate::gates::okex::common::model::{
+    OkexCandle,
+    OkexFundingRate,
+    OkexIndexTicker,
+    OkexInstrument,
+    OkexMarkPrice,
+    OkexOpenInterest,
+    OkexTicker,
+};
 use crate::gates::okex::common::response::OkexResponse;
 use crate::gates::okex::crawler::model::OkexOrderBookSnapshot;
-use crate::gates::okex::crawler::request::GetOrderBookRequest;
+use crate::gates::okex::crawler::request::{
+    GetCandlesRequest,
+    GetFundingRateRequest,
+    GetIndexTickersRequest,
+    GetInstrumentsRequest,
+    GetMarkPriceRequest,
+    GetOpenInterestRequest,
+    GetOrderBookRequest,
+    GetTickerRequest,
+    GetTickersRequest,
+};
 
 pub struct GetOrderBook;
 
@@ -14,3 +33,95 @@ impl Endpoint for GetOrderBook {
     const METHOD: Method = Method::GET;
     const PATH: &'static str = "/api/v5/market/books";
 }
+
+pub struct GetTicker;
+
+impl Endpoint for GetTicker {
+    type Request = GetTickerRequest;
+    type Response = OkexResponse<OkexTicker>;
+
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "/api/v5/market/ticker";
+}
+
+pub struct GetTickers;
+
+impl Endpoint for GetTickers {
+    type Request = GetTickersRequest;
+    type Response = OkexResponse<OkexTicker>;
+
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "/api/v5/market/tickers";
+}
+
+pub struct GetFundingRate;
+
+impl Endpoint for GetFundingRate {
+    type Request = GetFundingRateRequest;
+    type Response = OkexResponse<OkexFundingRate>;
+
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "/api/v5/public/funding-rate";
+}
+
+pub struct GetMarkPrice;
+
+impl Endpoint for GetMarkPrice {
+    type Request = GetMarkPriceRequest;
+    type Response = OkexResponse<OkexMarkPrice>;
+
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "/api/v5/public/mark-price";
+}
+
+pub struct GetIndexTickers;
+
+impl Endpoint for GetIndexTickers {
+    type Request = GetIndexTickersRequest;
+    type Response = OkexResponse<OkexIndexTicker>;
+
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "/api/v5/market/index-tickers";
+}
+
+pub struct GetOpenInterest;
+
+impl Endpoint for GetOpenInterest {
+    type Request = GetOpenInterestRequest;
+    type Response = OkexResponse<OkexOpenInterest>;
+
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "/api/v5/public/open-interest";
+}
+
+/// Recent candles, up to 1440 latest ones
+pub struct GetCandles;
+
+impl Endpoint for GetCandles {
+    type Request = GetCandlesRequest;
+    type Response = OkexResponse<OkexCandle>;
+
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "/api/v5/market/candles";
+}
+
+/// Candles from recent years
+pub struct GetHistoryCandles;
+
+impl Endpoint for GetHistoryCandles {
+    type Request = GetCandlesRequest;
+    type Response = OkexResponse<OkexCandle>;
+
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "/api/v5/market/history-candles";
+}
+
+pub struct GetInstruments;
+
+impl Endpoint for GetInstruments {
+    type Request = GetInstrumentsRequest;
+    type Response = OkexResponse<OkexInstrument>;
+
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "/api/v5/public/instruments";
+}
@@ -2,4 +2,4 @@ pub mod config;
 pub mod endpoints;
 pub mod model;
 pub mod poller;
-mod request;
\ No newline at end of file
+pub mod request;
\ No newline at end of file
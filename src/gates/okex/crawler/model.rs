@@ -7,20 +7,20 @@ use crate::utils::basic_types::{Amount, Price};
 #[derive(Debug, Deserialize)]
 pub struct OkexOrderBookSnapshot {
     /// Order book on sell side
-    asks: Vec<OkexSingleLot>,
+    pub asks: Vec<OkexSingleLot>,
     /// Order book on buy side
-    bids: Vec<OkexSingleLot>,
+    pub bids: Vec<OkexSingleLot>,
     /// Order book generation time
-    ts: CompactString
+    pub ts: CompactString
 }
 
-impl Into<OrderBook> for OkexOrderBookSnapshot {
-    fn into(self) -> OrderBook {
-        let bids = self.bids
+impl From<OkexOrderBookSnapshot> for OrderBook {
+    fn from(snapshot: OkexOrderBookSnapshot) -> Self {
+        let bids = snapshot.bids
             .into_iter()
             .map(|l| (l.price, l.amount))
             .collect();
-        let asks = self.asks
+        let asks = snapshot.asks
             .into_iter()
             .map(|l| (l.price, l.amount))
             .collect();
@@ -31,13 +31,13 @@ impl Into<OrderBook> for OkexOrderBookSnapshot {
 #[derive(Debug, Deserialize)]
 pub struct OkexSingleLot {
     /// depth price
-    price: Price,
+    pub price: Price,
     /// quantity at the price (number of contracts for derivatives, quantity in base currency for Spot and Spot Margin)
-    amount: Amount,
+    pub amount: Amount,
     /// part of a deprecated feature and it is always "0"
-    deprecated: CompactString,
+    pub deprecated: CompactString,
     /// the number of orders at the price.
-    orders_number: CompactString,
+    pub orders_number: CompactString,
 }
 
 #[cfg(test)]
@@ -3,11 +3,51 @@ use compact_str::CompactString;
 use eyre::OptionExt;
 
 use crate::api::api;
+use crate::api::endpoint::Endpoint;
 use crate::api::poller::ExchangePoller;
+use crate::gates::okex::common::model::{
+    OkexIndexTicker,
+    OkexInstrument,
+    OkexInstType,
+    OkexMarkPrice,
+    OkexOpenInterest,
+    OkexTicker,
+};
+use crate::gates::okex::common::response::OkexResponse;
+use crate::gates::okex::common::symbol::OkexSymbols;
 use crate::gates::okex::crawler::config::OkexPollerConfig;
-use crate::gates::okex::crawler::endpoints::GetOrderBook;
-use crate::gates::okex::crawler::request::GetOrderBookRequest;
+use crate::gates::okex::crawler::endpoints::{
+    GetCandles,
+    GetFundingRate,
+    GetHistoryCandles,
+    GetIndexTickers,
+    GetInstruments,
+    GetMarkPrice,
+    GetOpenInterest,
+    GetOrderBook,
+    GetTicker,
+    GetTickers,
+};
+use crate::gates::okex::crawler::request::{
+    GetCandlesRequest,
+    GetFundingRateRequest,
+    GetIndexTickersRequest,
+    GetInstrumentsRequest,
+    GetMarkPriceRequest,
+    GetOpenInterestRequest,
+    GetOrderBookRequest,
+    GetTickerRequest,
+    GetTickersRequest,
+};
+use crate::model::instrument::{Instrument, InstrumentRegistry};
+use crate::model::internal::{Candle, CandleInterval, FundingRate, IndexPrice, MarkPrice, OpenInterest, Ticker};
 use crate::model::order_book::OrderBook;
+use crate::model::symbol::{Symbol, SymbolMapping};
+
+/// Maximum page size of `/api/v5/market/candles`
+const CANDLES_LIMIT: u16 = 300;
+/// Maximum page size of `/api/v5/market/history-candles`
+const HISTORY_CANDLES_LIMIT: u16 = 100;
 
 #[derive(Debug, Default)]
 pub struct OkexExchangePoller {
@@ -16,8 +56,8 @@ pub struct OkexExchangePoller {
 
 #[async_trait]
 impl ExchangePoller for OkexExchangePoller {
-    async fn get_order_book(&self, symbol: CompactString) -> eyre::Result<OrderBook> {
-        let request = GetOrderBookRequest::new(symbol, None);
+    async fn get_order_book(&self, symbol: &Symbol) -> eyre::Result<OrderBook> {
+        let request = GetOrderBookRequest::new(OkexSymbols::to_native(symbol)?, None);
 
         let response = api::http_urlencoded_query_request::<GetOrderBook>(
             &self.config.http_url,
@@ -45,27 +85,230 @@ impl OkexExchangePoller {
     pub fn with_config(config: OkexPollerConfig) -> Self {
         Self { config }
     }
+
+    pub async fn get_ticker(&self, symbol: &Symbol) -> eyre::Result<Ticker> {
+        let ticker = self.query::<GetTicker, _>(&GetTickerRequest::new(OkexSymbols::to_native(symbol)?))
+            .await?
+            .first()
+            .ok_or_eyre("There was no ticker returned from Okex API")?
+            .to_internal()?;
+
+        Ok(ticker)
+    }
+
+    /// Tickers of all the instruments of the type, `inst_family` narrows derivatives down, e.g. BTC-USD
+    pub async fn get_tickers(
+        &self,
+        inst_type: OkexInstType,
+        inst_family: Option<CompactString>,
+    ) -> eyre::Result<Vec<Ticker>> {
+        let tickers = self.query::<GetTickers, _>(&GetTickersRequest::new(inst_type, inst_family))
+            .await?
+            .iter()
+            .map(OkexTicker::to_internal)
+            .collect::<eyre::Result<_>>()?;
+
+        Ok(tickers)
+    }
+
+    /// Funding rate of a perpetual swap, e.g. BTC/USD-PERP
+    pub async fn get_funding_rate(&self, symbol: &Symbol) -> eyre::Result<FundingRate> {
+        let funding_rate = self.query::<GetFundingRate, _>(&GetFundingRateRequest::new(OkexSymbols::to_native(symbol)?))
+            .await?
+            .first()
+            .ok_or_eyre("There was no funding rate returned from Okex API")?
+            .to_internal()?;
+
+        Ok(funding_rate)
+    }
+
+    /// Mark prices of all the instruments of the type or of the single instrument
+    pub async fn get_mark_prices(
+        &self,
+        inst_type: OkexInstType,
+        symbol: Option<&Symbol>,
+    ) -> eyre::Result<Vec<MarkPrice>> {
+        let request = GetMarkPriceRequest::new(inst_type, symbol.map(OkexSymbols::to_native).transpose()?);
+        let mark_prices = self.query::<GetMarkPrice, _>(&request)
+            .await?
+            .iter()
+            .map(OkexMarkPrice::to_internal)
+            .collect::<eyre::Result<_>>()?;
+
+        Ok(mark_prices)
+    }
+
+    /// Index prices by index, e.g. BTC/USD-INDEX, or by quote currency, e.g. USDT
+    pub async fn get_index_prices(
+        &self,
+        index: Option<&Symbol>,
+        quote_currency: Option<CompactString>,
+    ) -> eyre::Result<Vec<IndexPrice>> {
+        let request = GetIndexTickersRequest::new(index.map(OkexSymbols::to_native).transpose()?, quote_currency);
+        let index_prices = self.query::<GetIndexTickers, _>(&request)
+            .await?
+            .iter()
+            .map(OkexIndexTicker::to_internal)
+            .collect::<eyre::Result<_>>()?;
+
+        Ok(index_prices)
+    }
+
+    /// Open interest of all the instruments of the type or of the single instrument
+    pub async fn get_open_interest(
+        &self,
+        inst_type: OkexInstType,
+        symbol: Option<&Symbol>,
+    ) -> eyre::Result<Vec<OpenInterest>> {
+        let request = GetOpenInterestRequest::new(inst_type, symbol.map(OkexSymbols::to_native).transpose()?);
+        let open_interest = self.query::<GetOpenInterest, _>(&request)
+            .await?
+            .iter()
+            .map(OkexOpenInterest::to_internal)
+            .collect::<eyre::Result<_>>()?;
+
+        Ok(open_interest)
+    }
+
+    /// Instruments of the type, `inst_family` is required for options, e.g. BTC-USD
+    pub async fn get_instruments(
+        &self,
+        inst_type: OkexInstType,
+        inst_family: Option<CompactString>,
+    ) -> eyre::Result<Vec<Instrument>> {
+        self.query::<GetInstruments, _>(&GetInstrumentsRequest::new(inst_type, inst_family))
+            .await?
+            .iter()
+            .map(OkexInstrument::to_internal)
+            .collect()
+    }
+
+    /// Loads spot, swap and futures instruments and the options of `option_families` into the registry.
+    /// Already registered instruments are updated in place and keep their ids.
+    pub async fn load_instruments(
+        &self,
+        registry: &mut InstrumentRegistry,
+        option_families: &[CompactString],
+    ) -> eyre::Result<()> {
+        for inst_type in [OkexInstType::Spot, OkexInstType::Swap, OkexInstType::Futures] {
+            registry.extend(self.get_instruments(inst_type, None).await?);
+        }
+        for family in option_families {
+            registry.extend(self.get_instruments(OkexInstType::Option, Some(family.clone())).await?);
+        }
+
+        Ok(())
+    }
+
+    /// Candles with the opening time within `[from, to)`, sorted by the opening time.
+    /// Pages back from `to` through `/candles` and continues with `/history-candles`
+    /// once the recent candles are exhausted.
+    pub async fn get_candles(
+        &self,
+        symbol: &Symbol,
+        interval: CandleInterval,
+        from: u64,
+        to: u64,
+    ) -> eyre::Result<Vec<Candle>> {
+        let inst_id = OkexSymbols::to_native(symbol)?;
+        let mut candles = Vec::new();
+        let mut cursor = to;
+        let mut history = false;
+        while cursor > from {
+            let page = if history {
+                let request = GetCandlesRequest::new(
+                    inst_id.clone(), interval, Some(cursor), from.checked_sub(1), Some(HISTORY_CANDLES_LIMIT),
+                );
+                self.query::<GetHistoryCandles, _>(&request).await?
+            } else {
+                let request = GetCandlesRequest::new(
+                    inst_id.clone(), interval, Some(cursor), from.checked_sub(1), Some(CANDLES_LIMIT),
+                );
+                self.query::<GetCandles, _>(&request).await?
+            };
+
+            let Some(oldest) = page.iter().map(|c| c.ts).min() else {
+                if history {
+                    break;
+                }
+                history = true;
+                continue;
+            };
+            candles.extend(
+                page.iter()
+                    .filter(|c| c.ts >= from && c.ts < to)
+                    .map(|c| c.to_internal(symbol.clone(), interval))
+            );
+            cursor = oldest;
+        }
+
+        candles.sort_by_key(|c| c.open_time);
+        candles.dedup_by_key(|c| c.open_time);
+        Ok(candles)
+    }
+
+    /// Sends the request and unwraps the data from the Okex response
+    async fn query<E, R>(&self, request: &E::Request) -> eyre::Result<Vec<R>>
+        where
+            E: Endpoint<Response = OkexResponse<R>>,
+    {
+        let response = api::http_urlencoded_query_request::<E>(
+            &self.config.http_url,
+            request,
+            Default::default(),
+        ).await?;
+
+        Ok(response.into_result()?)
+    }
 }
 
 #[cfg(test)]
 mod api_tests {
     use crate::api::poller::ExchangePoller;
     use crate::gates::okex::crawler::poller::OkexExchangePoller;
+    use crate::model::exchange::Exchange;
+    use crate::model::instrument::{InstrumentKind, InstrumentRegistry};
+    use crate::model::internal::CandleInterval;
+    use crate::model::symbol::Symbol;
 
     #[tokio::test]
     async fn get_ob_test() {
         let poller = OkexExchangePoller::new();
-        let ob = poller.get_order_book("BTC-USDT".into()).await;
+        let ob = poller.get_order_book(&Symbol::spot("BTC", "USDT")).await;
         assert!(ob.is_ok());
         let ob = ob.unwrap();
         assert_ne!(ob.bids.len(), 0);
         assert_ne!(ob.asks.len(), 0);
     }
 
+    #[ignore]
+    #[tokio::test]
+    async fn get_candles_test() {
+        let poller = OkexExchangePoller::new();
+        let to = 1_700_000_000_000;
+        let from = to - 2 * 24 * 60 * 60 * 1000;
+        let candles = poller.get_candles(&Symbol::spot("BTC", "USDT"), CandleInterval::Minute15, from, to).await.unwrap();
+        assert_eq!(candles.len(), 2 * 24 * 4);
+        assert!(candles.windows(2).all(|w| w[0].open_time < w[1].open_time));
+    }
+
+    #[ignore]
+    #[tokio::test]
+    async fn load_instruments_test() {
+        let poller = OkexExchangePoller::new();
+        let mut registry = InstrumentRegistry::new();
+        poller.load_instruments(&mut registry, &["BTC-USD".into()]).await.unwrap();
+
+        let swap = registry.by_symbol(Exchange::Okex, "BTC-USDT-SWAP").unwrap();
+        assert_eq!(swap.kind, InstrumentKind::Perpetual);
+        assert!(swap.contract_value.is_some());
+        assert!(registry.iter().any(|(_, i)| i.kind == InstrumentKind::Option));
+    }
+
     #[tokio::test]
     async fn get_ob_test_instrument_not_exist() {
         let poller = OkexExchangePoller::new();
-        let ob = poller.get_order_book("BTCUSDT".into()).await;
+        let ob = poller.get_order_book(&Symbol::spot("BTC", "USDX")).await;
         assert!(ob.is_err());
     }
 }
\ No newline at end of file
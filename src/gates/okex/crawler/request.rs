@@ -1,6 +1,9 @@
 use compact_str::CompactString;
 use serde::Serialize;
 
+use crate::gates::okex::common::model::{okex_bar, OkexInstType};
+use crate::model::internal::CandleInterval;
+
 #[derive(Debug, Serialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct GetOrderBookRequest {
@@ -18,4 +21,148 @@ impl GetOrderBookRequest {
             sz: limit,
         }
     }
-}
\ No newline at end of file
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GetTickerRequest {
+    /// Instrument ID, e.g. BTC-USD-SWAP
+    inst_id: CompactString,
+}
+
+impl GetTickerRequest {
+    pub fn new(symbol: CompactString) -> Self {
+        Self { inst_id: symbol }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GetTickersRequest {
+    inst_type: OkexInstType,
+    /// Instrument family, applicable to FUTURES/SWAP/OPTION, e.g. BTC-USD
+    inst_family: Option<CompactString>,
+}
+
+impl GetTickersRequest {
+    pub fn new(inst_type: OkexInstType, inst_family: Option<CompactString>) -> Self {
+        Self {
+            inst_type,
+            inst_family,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GetFundingRateRequest {
+    /// Instrument ID, e.g. BTC-USD-SWAP, only applicable to SWAP
+    inst_id: CompactString,
+}
+
+impl GetFundingRateRequest {
+    pub fn new(symbol: CompactString) -> Self {
+        Self { inst_id: symbol }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GetMarkPriceRequest {
+    /// MARGIN, SWAP, FUTURES or OPTION
+    inst_type: OkexInstType,
+    inst_id: Option<CompactString>,
+}
+
+impl GetMarkPriceRequest {
+    pub fn new(inst_type: OkexInstType, symbol: Option<CompactString>) -> Self {
+        Self {
+            inst_type,
+            inst_id: symbol,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GetIndexTickersRequest {
+    /// Index, e.g. BTC-USD
+    inst_id: Option<CompactString>,
+    /// Quote currency, e.g. USD, USDT, BTC
+    quote_ccy: Option<CompactString>,
+}
+
+impl GetIndexTickersRequest {
+    pub fn new(index: Option<CompactString>, quote_currency: Option<CompactString>) -> Self {
+        Self {
+            inst_id: index,
+            quote_ccy: quote_currency,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GetOpenInterestRequest {
+    /// SWAP, FUTURES or OPTION
+    inst_type: OkexInstType,
+    inst_id: Option<CompactString>,
+}
+
+impl GetOpenInterestRequest {
+    pub fn new(inst_type: OkexInstType, symbol: Option<CompactString>) -> Self {
+        Self {
+            inst_type,
+            inst_id: symbol,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GetCandlesRequest {
+    inst_id: CompactString,
+    /// Bar size, e.g. 1m, 4H, 1D
+    bar: &'static str,
+    /// Return records earlier than the requested ts
+    after: Option<u64>,
+    /// Return records newer than the requested ts
+    before: Option<u64>,
+    /// Number of results per request, 300 at most for candles and 100 at most for history candles
+    limit: Option<u16>,
+}
+
+impl GetCandlesRequest {
+    pub fn new(
+        symbol: CompactString,
+        interval: CandleInterval,
+        after: Option<u64>,
+        before: Option<u64>,
+        limit: Option<u16>,
+    ) -> Self {
+        Self {
+            inst_id: symbol,
+            bar: okex_bar(interval),
+            after,
+            before,
+            limit,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GetInstrumentsRequest {
+    inst_type: OkexInstType,
+    /// Instrument family, e.g. BTC-USD, required for OPTION
+    inst_family: Option<CompactString>,
+}
+
+impl GetInstrumentsRequest {
+    pub fn new(inst_type: OkexInstType, inst_family: Option<CompactString>) -> Self {
+        Self {
+            inst_type,
+            inst_family,
+        }
+    }
+}
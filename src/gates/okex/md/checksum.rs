@@ -0,0 +1,118 @@
+use std::collections::BTreeMap;
+
+use compact_str::CompactString;
+use fixnum::ops::Zero;
+
+use crate::gates::okex::md::model::{OkexBookLevel, OkexOrderBookSnapshot};
+use crate::model::internal::Side;
+use crate::utils::basic_types::{Amount, Price};
+
+/// Amount of levels per side which take part in the checksum calculation
+const CHECKSUM_DEPTH: usize = 25;
+
+/// Order book keeping the price and size strings as Okex sent them, so that the checksum string
+/// doesn't depend on how the values are formatted back, e.g. "0.10" stays "0.10"
+#[derive(Debug, Default)]
+pub struct ChecksumBook {
+    bids: BTreeMap<Price, (CompactString, CompactString)>,
+    asks: BTreeMap<Price, (CompactString, CompactString)>,
+}
+
+impl ChecksumBook {
+    pub fn from_snapshot(snapshot: &OkexOrderBookSnapshot) -> Self {
+        let mut book = Self::default();
+        for level in &snapshot.bids {
+            book.set_level(Side::Bid, level);
+        }
+        for level in &snapshot.asks {
+            book.set_level(Side::Ask, level);
+        }
+        book
+    }
+
+    /// Zero amount removes the level
+    pub fn set_level(&mut self, side: Side, level: &OkexBookLevel) {
+        let levels = match side {
+            Side::Bid => &mut self.bids,
+            Side::Ask => &mut self.asks,
+        };
+        if level.amount == Amount::ZERO {
+            levels.remove(&level.price);
+        } else {
+            levels.insert(level.price, (level.raw_price.clone(), level.raw_amount.clone()));
+        }
+    }
+
+    /// Builds the checksum string from the first 25 bids and asks of the book
+    /// in the `bid1_price:bid1_amount:ask1_price:ask1_amount:bid2_price:...` format.
+    /// If there are less than 25 levels on one of the sides, the missing levels are skipped.
+    ///
+    /// https://www.okx.com/docs-v5/en/#order-book-trading-market-data-ws-order-book-channel
+    pub fn checksum_string(&self) -> String {
+        let mut bids = self.bids.values().rev().take(CHECKSUM_DEPTH);
+        let mut asks = self.asks.values().take(CHECKSUM_DEPTH);
+
+        let mut parts = Vec::with_capacity(CHECKSUM_DEPTH * 4);
+        loop {
+            let bid = bids.next();
+            let ask = asks.next();
+            if bid.is_none() && ask.is_none() {
+                break;
+            }
+            for (price, amount) in bid.into_iter().chain(ask) {
+                parts.push(price.as_str());
+                parts.push(amount.as_str());
+            }
+        }
+        parts.join(":")
+    }
+
+    /// CRC32 of the checksum string as a signed 32-bit integer, the same way Okex sends it
+    pub fn checksum(&self) -> i32 {
+        crc32fast::hash(self.checksum_string().as_bytes()) as i32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::gates::okex::md::checksum::ChecksumBook;
+    use crate::gates::okex::md::model::OkexBookLevel;
+    use crate::model::internal::Side;
+
+    fn level(price: &str, amount: &str) -> OkexBookLevel {
+        OkexBookLevel::try_from([price.into(), amount.into(), "0".into(), "1".into()]).unwrap()
+    }
+
+    fn book(bids: &[(&str, &str)], asks: &[(&str, &str)]) -> ChecksumBook {
+        let mut book = ChecksumBook::default();
+        for (price, amount) in bids {
+            book.set_level(Side::Bid, &level(price, amount));
+        }
+        for (price, amount) in asks {
+            book.set_level(Side::Ask, &level(price, amount));
+        }
+        book
+    }
+
+    #[test]
+    fn checksum_string_interleaves_sides() {
+        let ob = book(&[("3366.1", "7"), ("3366", "6")], &[("3366.8", "9"), ("3368", "8")]);
+        assert_eq!(ob.checksum_string(), "3366.1:7:3366.8:9:3366:6:3368:8");
+        assert_eq!(ob.checksum(), -1881014294);
+    }
+
+    #[test]
+    fn checksum_string_skips_missing_levels() {
+        let ob = book(&[("3366.1", "7"), ("3366", "6")], &[("3366.8", "9")]);
+        assert_eq!(ob.checksum_string(), "3366.1:7:3366.8:9:3366:6");
+        assert_eq!(ob.checksum(), 1164732920);
+    }
+
+    #[test]
+    fn checksum_string_keeps_raw_strings() {
+        let mut ob = book(&[("3366.10", "7.0")], &[("3366.8", "0.50")]);
+        assert_eq!(ob.checksum_string(), "3366.10:7.0:3366.8:0.50");
+        ob.set_level(Side::Bid, &level("3366.1", "0"));
+        assert_eq!(ob.checksum_string(), "3366.8:0.50");
+    }
+}
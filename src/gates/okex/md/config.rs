@@ -1,21 +1,44 @@
 use compact_str::CompactString;
 use serde::Deserialize;
 
-#[derive(Debug, Deserialize)]
+use crate::api::backoff::BackoffConfig;
+use crate::gates::okex::common::auth::OkexCredentials;
+use crate::gates::okex::md::stream::OkexStreamKind;
+
+#[derive(Debug, Deserialize, Clone)]
 pub struct OkexMdConnectionConfig {
+    /// Maximum amount of instruments per websocket connection, used by `OkexMdConnectionPool`
     pub channel_tickers_amount: usize,
     pub ws_url: CompactString,
-    pub ping_frequency_seconds: u64,
+    /// Order book channel to subscribe to
+    pub stream_kind: OkexStreamKind,
+    /// Required by the tick-by-tick channels, which are available only after login
+    #[serde(default)]
+    pub credentials: Option<OkexCredentials>,
+    /// Send the text `ping` if no message was received for this amount of seconds, should be less than 30
+    pub idle_timeout_seconds: u64,
+    /// Reconnect if no message arrives within this amount of seconds after `ping` was sent
+    pub pong_timeout_seconds: u64,
     pub subscribe_interval_ms: u64,
+    /// Verify the local order book against the checksum sent with every `books` update
+    pub validate_checksum: bool,
+    /// Retry policy for connecting, reconnecting and resubscribing
+    #[serde(default)]
+    pub reconnect: BackoffConfig,
 }
 
 impl Default for OkexMdConnectionConfig {
     fn default() -> Self {
         Self {
-            channel_tickers_amount: 60,
+            channel_tickers_amount: 25,
             ws_url: "wss://ws.okx.com:8443/ws/v5/public".into(),
-            ping_frequency_seconds: 3,
+            stream_kind: OkexStreamKind::L2Update,
+            credentials: None,
+            idle_timeout_seconds: 20,
+            pong_timeout_seconds: 5,
             subscribe_interval_ms: 100,
+            validate_checksum: true,
+            reconnect: BackoffConfig::default(),
         }
     }
 }
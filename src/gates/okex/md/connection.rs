@@ -1,62 +1,418 @@
-use std::collections::VecDeque;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
 use std::time::Duration;
 
 use async_trait::async_trait;
-use compact_str::ToCompactString;
+use compact_str::CompactString;
 use eyre::{eyre, Result};
 use log::{error, trace, warn};
-use tokio::sync::mpsc::{self, Receiver, Sender};
 
 use crate::api::connection::MdConnection;
-use crate::api::ws::WebSocket;
+use crate::api::heartbeat::Heartbeat;
+use crate::api::ws::{WebSocket, WsEvent};
+use crate::gates::okex::common::symbol::OkexSymbols;
+use crate::gates::okex::md::checksum::ChecksumBook;
 use crate::gates::okex::md::config::OkexMdConnectionConfig;
-use crate::gates::okex::md::model::{EventType, OkexWsDataMessage, OkexWsMessage};
+use crate::gates::okex::md::model::{
+    EventType,
+    OkexOrderBookSnapshot,
+    OkexSubEvent,
+    OkexWsDataMessage,
+    OkexWsMessage,
+    Stream,
+    WsRequest,
+};
+use crate::gates::okex::md::sequence::SequenceStatus;
 use crate::gates::okex::md::stream::{OkexStream, OkexStreamKind};
-use crate::model::internal::{MdMessage, Side};
+use crate::model::exchange::Exchange;
+use crate::model::instrument::InstrumentRegistry;
+use crate::model::internal::{BookInvalid, BookInvalidReason, MdInstrument, MdMessage, Side};
+use crate::model::symbol::{Symbol, SymbolMapping};
 
 pub struct OkexMdConnection {
     ws: WebSocket<OkexStream, OkexWsMessage>,
     increment_queue: VecDeque<MdMessage>,
-    rx: Receiver<()>,
+    /// Canonical symbols of the subscribed Okex instrument ids
+    symbols: HashMap<CompactString, Symbol>,
+    /// Subscription state per instrument, updated from subscribe/unsubscribe acknowledgements
+    subscriptions: HashMap<CompactString, SubscriptionState>,
+    /// Local copies of the order books, used for checksum validation
+    books: HashMap<CompactString, ChecksumBook>,
+    /// Last received `seqId` per instrument
+    last_seq_ids: HashMap<CompactString, u64>,
+    /// Instruments waiting for a fresh snapshot after resubscription, their increments are dropped
+    resyncing: HashSet<CompactString>,
+    validate_checksum: bool,
+    stats: OkexMdStats,
+    heartbeat: Heartbeat,
+    /// Resolves the instrument ids of the emitted messages
+    registry: Option<Arc<InstrumentRegistry>>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SubscriptionState {
+    /// Subscribe request was sent, waiting for the acknowledgement
+    Pending,
+    Subscribed,
+    /// Unsubscribe request was sent, waiting for the acknowledgement
+    Unsubscribing,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct OkexMdStats {
+    /// Amount of order book messages which passed through checksum validation
+    pub checksum_checks: u64,
+    /// Amount of order book messages whose checksum didn't match the local book
+    pub checksum_mismatches: u64,
+    /// Amount of incremental messages which didn't continue the previous sequence
+    pub sequence_gaps: u64,
+    /// Amount of incremental messages received more than once
+    pub sequence_duplicates: u64,
+    /// Amount of sequence resets made by Okex
+    pub sequence_resets: u64,
+}
+
+impl OkexMdStats {
+    pub fn checksum_mismatch_rate(&self) -> f64 {
+        if self.checksum_checks == 0 {
+            0.0
+        } else {
+            self.checksum_mismatches as f64 / self.checksum_checks as f64
+        }
+    }
 }
 
 impl OkexMdConnection {
     /// If you need to subscribe to many 50 or 400 depth level channels,
     /// it is recommended to subscribe through multiple websocket connections, with each of less than 30 channels.
     /// https://www.okx.com/docs-v5/en/#order-book-trading-market-data-ws-order-book-channel
-    pub async fn new(tickers: Vec<impl ToCompactString>, config: OkexMdConnectionConfig) -> Self {
-        let tickers: Vec<_> = tickers.into_iter().map(|s| s.to_compact_string()).collect();
-        let stream = OkexStream { tickers, kind: OkexStreamKind::L2Update, };
-        let mut ws = WebSocket::try_establish_connection(
+    ///
+    /// `OkexMdConnectionPool` does this splitting automatically.
+    pub async fn new(symbols: Vec<Symbol>, config: OkexMdConnectionConfig) -> Result<Self> {
+        let tickers = symbols.iter().map(OkexSymbols::to_native).collect::<Result<Vec<_>>>()?;
+        let symbols: HashMap<_, _> = tickers.iter().cloned().zip(symbols).collect();
+        let subscriptions = tickers
+            .iter()
+            .map(|t| (t.clone(), SubscriptionState::Pending))
+            .collect();
+        let kind = config.stream_kind;
+        if kind.requires_login() && config.credentials.is_none() {
+            return Err(eyre!("Okex {} channel requires login, but no credentials were provided", kind.channel()));
+        }
+        let stream = OkexStream { tickers, kind, credentials: config.credentials.clone() };
+        let ws = WebSocket::connect_and_subscribe(
             &config.ws_url,
             stream,
-            config.subscribe_interval_ms
-        ).await
-            .expect("Failed to connect to Okex websocket");
-        ws.subscribe().await
-            .expect("Failed to subscribe to Okex md");
+            config.subscribe_interval_ms,
+            config.reconnect.clone(),
+        ).await?;
 
-        let (tx, rx) = mpsc::channel(1);
-        tokio::spawn(Self::ping_task(tx, config.ping_frequency_seconds));
-
-        Self {
+        Ok(Self {
             ws,
             increment_queue: VecDeque::new(),
-            rx,
+            symbols,
+            subscriptions,
+            books: HashMap::new(),
+            last_seq_ids: HashMap::new(),
+            resyncing: HashSet::new(),
+            validate_checksum: config.validate_checksum,
+            stats: OkexMdStats::default(),
+            heartbeat: Heartbeat::new(
+                Duration::from_secs(config.idle_timeout_seconds),
+                Duration::from_secs(config.pong_timeout_seconds),
+            ),
+            registry: None,
+        })
+    }
+
+    /// Emitted messages will carry `MdInstrument::Id` of the instruments known to the registry
+    pub fn with_registry(mut self, registry: Arc<InstrumentRegistry>) -> Self {
+        self.registry = Some(registry);
+        self
+    }
+
+    pub fn stats(&self) -> &OkexMdStats {
+        &self.stats
+    }
+
+    /// Instruments the connection is currently subscribed to or subscribing to
+    pub fn instruments(&self) -> Vec<Symbol> {
+        self.ws.stream().tickers.iter().filter_map(|t| self.symbols.get(t).cloned()).collect()
+    }
+
+    pub fn subscription_state(&self, instrument: &Symbol) -> Option<SubscriptionState> {
+        self.subscriptions.get(&OkexSymbols::to_native(instrument).ok()?).copied()
+    }
+
+    /// Canonical symbol of the Okex instrument id
+    fn symbol(&self, inst_id: &str) -> Result<Symbol> {
+        match self.symbols.get(inst_id) {
+            Some(symbol) => Ok(symbol.clone()),
+            None => OkexSymbols::from_native(inst_id),
         }
     }
 
-    async fn ping_task(tx: Sender<()>, frequency: u64) {
-        let mut interval = tokio::time::interval(Duration::from_secs(frequency));
-        interval.tick().await;
-        loop {
-            interval.tick().await;
-            let res = tx.send(()).await;
-            if res.is_err() {
-                error!("Failed to send Ping to Okex channel");
+    /// Subscribes to the instruments which are not subscribed yet.
+    /// The subscription is confirmed once Okex acknowledges it, see `subscription_state`.
+    pub async fn add_instruments(&mut self, symbols: Vec<Symbol>) -> Result<()> {
+        let mut added: Vec<CompactString> = Vec::new();
+        for symbol in symbols {
+            let ticker = OkexSymbols::to_native(&symbol)?;
+            if !self.ws.stream().tickers.contains(&ticker) && !added.contains(&ticker) {
+                self.symbols.insert(ticker.clone(), symbol);
+                added.push(ticker);
+            }
+        }
+        if added.is_empty() {
+            return Ok(());
+        }
+
+        let request = WsRequest::new_subscribe(self.ws.stream().streams(&added));
+        for ticker in &added {
+            self.subscriptions.insert(ticker.clone(), SubscriptionState::Pending);
+        }
+        self.ws.stream_mut().tickers.extend(added);
+        self.ws.send(&request).await
+    }
+
+    /// Unsubscribes from the instruments and drops their local state
+    pub async fn remove_instruments(&mut self, symbols: Vec<Symbol>) -> Result<()> {
+        let tickers = symbols.iter().map(OkexSymbols::to_native).collect::<Result<Vec<_>>>()?;
+        let mut removed = Vec::new();
+        self.ws.stream_mut().tickers.retain(|t| {
+            let remove = tickers.contains(t);
+            if remove {
+                removed.push(t.clone());
+            }
+            !remove
+        });
+        if removed.is_empty() {
+            return Ok(());
+        }
+
+        for ticker in &removed {
+            self.subscriptions.insert(ticker.clone(), SubscriptionState::Unsubscribing);
+            self.books.remove(ticker);
+            self.last_seq_ids.remove(ticker);
+            self.resyncing.remove(ticker);
+            self.symbols.remove(ticker);
+        }
+        let request = WsRequest::new_unsubscribe(self.ws.stream().streams(&removed));
+        self.ws.send(&request).await
+    }
+
+    fn on_sub_event(&mut self, sub: OkexSubEvent<Stream>) {
+        match (&sub.event, &sub.arg) {
+            (EventType::Subscribe, Some(arg)) => {
+                // acknowledgements for the removed instruments may still be in flight
+                if self.ws.stream().tickers.contains(&arg.inst_id) {
+                    self.subscriptions.insert(arg.inst_id.clone(), SubscriptionState::Subscribed);
+                }
+                trace!("subscribed to {arg:?}");
+            }
+            (EventType::Unsubscribe, Some(arg)) => {
+                // instruments which are still in the stream are being resubscribed
+                if !self.ws.stream().tickers.contains(&arg.inst_id) {
+                    self.subscriptions.remove(&arg.inst_id);
+                }
+                trace!("unsubscribed from {arg:?}");
+            }
+            (EventType::Error, _) => {
+                warn!("received error subscribe event {sub:?}")
+            }
+            _ => {
+                trace!("sub event");
             }
         }
     }
+
+    async fn on_book_message(&mut self, stream: &Stream, snapshot: &OkexOrderBookSnapshot) -> Result<()> {
+        let inst_id = &stream.inst_id;
+        if !self.ws.stream().tickers.contains(inst_id) {
+            // in-flight message of a removed instrument
+            return Ok(());
+        }
+        let symbol = self.symbol(inst_id)?;
+
+        let Some(kind) = OkexStreamKind::from_channel(&stream.channel) else {
+            warn!("received order book message from unknown Okex channel {stream:?}");
+            return Ok(());
+        };
+        if !kind.is_incremental() {
+            let snapshot = snapshot.to_internal_snapshot(symbol.clone());
+            self.increment_queue.push_back(MdMessage::L2Snapshot(snapshot));
+            return Ok(());
+        }
+
+        let messages = match snapshot.prev_seq_id {
+            Some(prev_seq_id) if prev_seq_id != -1 => {
+                if self.resyncing.contains(inst_id) {
+                    return Ok(());
+                }
+                let last_seq_id = self.last_seq_ids.get(inst_id).copied();
+                match SequenceStatus::classify(last_seq_id, prev_seq_id as u64, snapshot.seq_id) {
+                    SequenceStatus::InOrder | SequenceStatus::Heartbeat => {}
+                    SequenceStatus::Reset => {
+                        self.stats.sequence_resets += 1;
+                        warn!("Okex sequence reset for {inst_id}: {prev_seq_id} -> {}", snapshot.seq_id);
+                    }
+                    SequenceStatus::Duplicate => {
+                        self.stats.sequence_duplicates += 1;
+                        trace!("Okex duplicate update for {inst_id}, seq_id {}", snapshot.seq_id);
+                        return Ok(());
+                    }
+                    SequenceStatus::Gap { expected, received } => {
+                        self.stats.sequence_gaps += 1;
+                        warn!("Okex sequence gap for {inst_id}: expected {expected:?}, received {received}; resubscribing");
+                        let reason = BookInvalidReason::SequenceGap { expected, received };
+                        return self.invalidate(stream, snapshot, reason).await;
+                    }
+                }
+                self.last_seq_ids.insert(inst_id.clone(), snapshot.seq_id);
+                let bids = snapshot.bids
+                    .iter()
+                    .enumerate()
+                    .map(|(i, b)| b.to_md(
+                        Some(snapshot.ts),
+                        symbol.clone(),
+                        Side::Bid,
+                        snapshot.seq_id,
+                        i + 1 == snapshot.bids.len() && snapshot.asks.is_empty(),
+                    ));
+                let asks = snapshot.asks
+                    .iter()
+                    .enumerate()
+                    .map(|(i, a)| a.to_md(
+                        Some(snapshot.ts),
+                        symbol.clone(),
+                        Side::Ask,
+                        snapshot.seq_id,
+                        i + 1 == snapshot.asks.len()
+                    ));
+                let increments: Vec<_> = bids.chain(asks).collect();
+                let book = self.books.entry(inst_id.clone()).or_default();
+                for level in &snapshot.bids {
+                    book.set_level(Side::Bid, level);
+                }
+                for level in &snapshot.asks {
+                    book.set_level(Side::Ask, level);
+                }
+                increments
+            }
+            _ => {
+                self.resyncing.remove(inst_id);
+                self.last_seq_ids.insert(inst_id.clone(), snapshot.seq_id);
+                self.books.insert(inst_id.clone(), ChecksumBook::from_snapshot(snapshot));
+                vec![MdMessage::L2Snapshot(snapshot.to_internal_snapshot(symbol.clone()))]
+            }
+        };
+
+        if let (true, Some(expected), Some(book)) = (self.validate_checksum, snapshot.checksum, self.books.get(inst_id)) {
+            self.stats.checksum_checks += 1;
+            let actual = book.checksum();
+            if actual != expected {
+                self.stats.checksum_mismatches += 1;
+                warn!(
+                    "Okex checksum mismatch for {inst_id}: expected {expected}, actual {actual}, mismatch rate {:.6}; resubscribing",
+                    self.stats.checksum_mismatch_rate(),
+                );
+                let reason = BookInvalidReason::ChecksumMismatch { expected: expected.into(), actual: actual.into() };
+                return self.invalidate(stream, snapshot, reason).await;
+            }
+        }
+
+        self.increment_queue.extend(messages);
+        Ok(())
+    }
+
+    async fn on_message(&mut self, message: OkexWsMessage) -> Result<()> {
+        match message {
+            OkexWsMessage::Combined(combined) => {
+                if combined.message.is_empty() {
+                    return Err(eyre!("Failed to deserialize: The incoming message is empty. {combined:?}"));
+                }
+                for message in &combined.message {
+                    match message {
+                        OkexWsDataMessage::BookSnapshot(snapshot) => {
+                            self.on_book_message(&combined.arg, snapshot).await?;
+                        }
+                        OkexWsDataMessage::Trade(trade) => self.enqueue(trade.to_internal().map(MdMessage::Trade)),
+                        OkexWsDataMessage::Ticker(ticker) => self.enqueue(ticker.to_internal().map(MdMessage::Ticker)),
+                        OkexWsDataMessage::FundingRate(funding_rate) => {
+                            self.enqueue(funding_rate.to_internal().map(MdMessage::FundingRate));
+                        }
+                        OkexWsDataMessage::MarkPrice(mark_price) => {
+                            self.enqueue(mark_price.to_internal().map(MdMessage::MarkPrice));
+                        }
+                        OkexWsDataMessage::IndexTicker(index_ticker) => {
+                            self.enqueue(index_ticker.to_internal().map(MdMessage::IndexPrice));
+                        }
+                        OkexWsDataMessage::OpenInterest(open_interest) => {
+                            self.enqueue(open_interest.to_internal().map(MdMessage::OpenInterest));
+                        }
+                        OkexWsDataMessage::Candle(candle) => {
+                            let Some(OkexStreamKind::Candle(interval)) = OkexStreamKind::from_channel(&combined.arg.channel) else {
+                                warn!("received candle from unexpected Okex channel {:?}", combined.arg);
+                                continue;
+                            };
+                            let symbol = self.symbol(&combined.arg.inst_id);
+                            self.enqueue(symbol.map(|symbol| MdMessage::Candle(candle.to_internal(symbol, interval))));
+                        }
+                    }
+                }
+            }
+            OkexWsMessage::SubEvent(sub) => self.on_sub_event(sub),
+            OkexWsMessage::Pong => {}
+        }
+        Ok(())
+    }
+
+    /// Items which fail to convert, e.g. because of an unknown instrument id format,
+    /// are skipped without dropping the rest of the message
+    fn enqueue(&mut self, message: Result<MdMessage>) {
+        match message {
+            Ok(message) => self.increment_queue.push_back(message),
+            Err(err) => warn!("skipping Okex md message, {err}"),
+        }
+    }
+
+    /// Drops the local book, notifies the consumer and requests a fresh snapshot
+    async fn invalidate(
+        &mut self,
+        stream: &Stream,
+        snapshot: &OkexOrderBookSnapshot,
+        reason: BookInvalidReason,
+    ) -> Result<()> {
+        self.books.remove(&stream.inst_id);
+        self.last_seq_ids.remove(&stream.inst_id);
+        self.increment_queue.push_back(MdMessage::BookInvalid(BookInvalid {
+            exchange_time: Some(snapshot.ts),
+            sequence_no: Some(snapshot.seq_id),
+            exchange: Exchange::Okex,
+            instrument: MdInstrument::Symbol(self.symbol(&stream.inst_id)?),
+            reason,
+        }));
+        self.resubscribe(stream.clone()).await
+    }
+
+    /// The subscriptions were sent again and Okex starts over with the snapshots
+    fn on_reconnected(&mut self) {
+        for ticker in &self.ws.stream().tickers {
+            self.subscriptions.insert(ticker.clone(), SubscriptionState::Pending);
+        }
+        self.books.clear();
+        self.last_seq_ids.clear();
+        self.resyncing.clear();
+    }
+
+    /// Unsubscribes from the stream and subscribes back, so that Okex sends a fresh snapshot
+    async fn resubscribe(&mut self, stream: Stream) -> Result<()> {
+        self.resyncing.insert(stream.inst_id.clone());
+        self.subscriptions.insert(stream.inst_id.clone(), SubscriptionState::Pending);
+        self.ws.send(&WsRequest::new_unsubscribe(vec![stream.clone()])).await?;
+        self.ws.send(&WsRequest::new_subscribe(vec![stream])).await?;
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -75,74 +431,20 @@ impl MdConnection for OkexMdConnection {
         // https://www.okx.com/docs-v5/en/#overview-websocket-connect
 
         while self.increment_queue.is_empty() {
-            tokio::select! {
-                res = self.ws.next() => {
-                    let ws_message = res?;
-
-                    match ws_message {
-                        OkexWsMessage::Combined(combined) => {
-                            if combined.message.len() != 1 {
-                                return Err(eyre!("Failed to deserialize: The incoming message length does not equal 1. {combined:?}"));
-                            }
-                            return match combined.message.get(0).unwrap() {
-                                OkexWsDataMessage::BookSnapshot(snapshot) => {
-                                    // maybe this logic should be extracted to model.rs
-                                    let instrument_id = combined.arg.inst_id;
-                                    if let Some(prev_seq_id) = snapshot.prev_seq_id {
-                                        if prev_seq_id != -1 {
-                                            let bids = snapshot.bids
-                                                .iter()
-                                                .enumerate()
-                                                .map(|(i, b)| b.to_md(
-                                                    Some(snapshot.ts),
-                                                    instrument_id.clone(),
-                                                    Side::Bid,
-                                                    snapshot.seq_id,
-                                                    i + 1 == snapshot.bids.len() && snapshot.asks.len() == 0,
-                                                ));
-                                            let asks = snapshot.asks
-                                                .iter()
-                                                .enumerate()
-                                                .map(|(i, a)| a.to_md(
-                                                    Some(snapshot.ts),
-                                                    instrument_id.clone(),
-                                                    Side::Ask,
-                                                    snapshot.seq_id,
-                                                    i + 1 == snapshot.asks.len()
-                                                ));
-                                            self.increment_queue.extend(bids);
-                                            self.increment_queue.extend(asks);
-                                            break;
-                                        }
-                                    }
-                                    Ok(MdMessage::L2Snapshot(snapshot.to_internal_snapshot(instrument_id)))
-                                }
-                            }
-                        },
-                        OkexWsMessage::SubEvent(sub) => {
-                            if matches!(sub.event, EventType::Error) {
-                                warn!("received error subscribe event {sub:?}")
-                            } else {
-                                trace!("sub event");
-                            }
-                            continue;
-                        }
-                        OkexWsMessage::Pong => {
-                            continue;
-                        }
-                    }
-                }
-                ping = self.rx.recv() => {
-                    if let Some(_ping_msg) = ping {
-                        let res = self.ws.ping(vec![]).await;
-                        if res.is_err() {
-                            error!("Failed to send Ping to Okex MD stream");
-                        }
+            match self.ws.next_event(&mut self.heartbeat).await? {
+                WsEvent::Message(message) => self.on_message(message).await?,
+                WsEvent::Ping => {
+                    if let Err(err) = self.ws.ping_text("ping").await {
+                        error!("Failed to send ping to Okex MD stream, {err}");
                     }
                 }
+                WsEvent::Reconnected => self.on_reconnected(),
             }
         }
-        let update = self.increment_queue.pop_front().expect("should be some");
+        let mut update = self.increment_queue.pop_front().expect("should be some");
+        if let Some(registry) = &self.registry {
+            update.resolve_instrument(registry);
+        }
         Ok(update)
     }
 }
@@ -155,6 +457,7 @@ mod md_integration_tests {
     use crate::gates::okex::md::config::OkexMdConnectionConfig;
     use crate::gates::okex::md::connection::OkexMdConnection;
     use crate::model::storage::Storage;
+    use crate::model::symbol::Symbol;
 
     #[ignore]
     #[tokio::test]
@@ -164,9 +467,9 @@ mod md_integration_tests {
         let mut storage = Storage::new();
 
         let mut connection = OkexMdConnection::new(
-            vec!["BTC-USDT", "ETH-USDT"],
+            vec![Symbol::spot("BTC", "USDT"), Symbol::spot("ETH", "USDT")],
             OkexMdConnectionConfig::default(),
-        ).await;
+        ).await.unwrap();
 
         loop {
             let m = connection.next().await.unwrap();
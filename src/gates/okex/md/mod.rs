@@ -1,4 +1,7 @@
+pub mod checksum;
 pub mod config;
 pub mod model;
+pub mod pool;
 pub mod connection;
-mod stream;
\ No newline at end of file
+pub mod sequence;
+pub mod stream;
\ No newline at end of file
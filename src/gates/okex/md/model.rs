@@ -1,8 +1,21 @@
+use std::str::FromStr;
+
 use compact_str::CompactString;
 use serde::{Deserialize, Serialize};
 
 use crate::api::connection::WsMessage;
-use crate::model::internal::{L2Increment, L2Snapshot, MdMessage, Side, SingleLot};
+use crate::gates::okex::common::model::{
+    OkexCandle,
+    OkexFundingRate,
+    OkexIndexTicker,
+    OkexMarkPrice,
+    OkexOpenInterest,
+    OkexTicker,
+};
+use crate::gates::okex::common::symbol::OkexSymbols;
+use crate::model::exchange::Exchange;
+use crate::model::internal::{L2Increment, L2Snapshot, MdInstrument, MdMessage, Side, SingleLot, Trade};
+use crate::model::symbol::{Symbol, SymbolMapping};
 use crate::utils::basic_types::{Amount, deserialize_u64, Price};
 
 #[derive(Debug, Deserialize)]
@@ -14,9 +27,23 @@ pub enum OkexWsMessage {
 }
 
 impl WsMessage for OkexWsMessage {
+    const PONG_TEXT: Option<&'static str> = Some("pong");
+
     fn pong() -> Self {
         Self::Pong
     }
+
+    fn login_result(&self) -> Option<eyre::Result<()>> {
+        let OkexWsMessage::SubEvent(event) = self else {
+            return None;
+        };
+        match event.event {
+            EventType::Login => Some(Ok(())),
+            // login failures are reported as plain error events
+            EventType::Error => Some(Err(eyre::eyre!("Okex login failed: {event:?}"))),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -30,16 +57,24 @@ pub struct OkexWsCombinedMessage {
 #[serde(untagged)]
 pub enum OkexWsDataMessage {
     BookSnapshot(OkexOrderBookSnapshot),
+    Trade(OkexTrade),
+    Ticker(Box<OkexTicker>),
+    FundingRate(OkexFundingRate),
+    MarkPrice(OkexMarkPrice),
+    IndexTicker(OkexIndexTicker),
+    OpenInterest(OkexOpenInterest),
+    /// Candles are sent as arrays, so this variant must stay the last one
+    Candle(OkexCandle),
 }
 
 #[derive(Debug, Deserialize)]
 pub struct OkexSubEvent<R> {
     pub event: EventType,
-    arg: Option<R>,
-    code: Option<CompactString>,
-    msg: Option<CompactString>,
+    pub arg: Option<R>,
+    pub code: Option<CompactString>,
+    pub msg: Option<CompactString>,
     #[serde(rename = "connId")]
-    conn_id: CompactString,
+    pub conn_id: CompactString,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -71,7 +106,7 @@ pub struct OkexOrderBookSnapshot {
     #[serde(deserialize_with = "deserialize_u64")]
     pub ts: u64,
     /// Checksum, implementation details below
-    checksum: Option<i64>,
+    pub checksum: Option<i32>,
     /// Sequence ID of the last sent message. Only applicable to books, books-l2-tbt, books50-l2-tbt
     pub prev_seq_id: Option<i64>,
     /// Sequence ID of the current message, implementation details below
@@ -79,7 +114,7 @@ pub struct OkexOrderBookSnapshot {
 }
 
 impl OkexOrderBookSnapshot {
-    pub fn to_internal_snapshot(&self, symbol: CompactString) -> L2Snapshot {
+    pub fn to_internal_snapshot(&self, symbol: Symbol) -> L2Snapshot {
         let bids = self.bids
             .iter()
             .map(OkexBookLevel::to_single_lot)
@@ -91,26 +126,88 @@ impl OkexOrderBookSnapshot {
         L2Snapshot {
             exchange_time: Some(self.ts),
             sequence_no: Some(self.seq_id),
-            symbol,
+            exchange: Exchange::Okex,
+            instrument: MdInstrument::Symbol(symbol),
             bids,
             asks,
         }
     }
 }
 
+/// Message of `trades` and `trades-all` channels
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OkexTrade {
+    pub inst_id: CompactString,
+    pub trade_id: CompactString,
+    #[serde(rename = "px")]
+    pub price: Price,
+    #[serde(rename = "sz")]
+    pub amount: Amount,
+    /// Trade direction of the taker
+    pub side: OkexTradeSide,
+    /// Trade time, Unix timestamp format in milliseconds
+    #[serde(deserialize_with = "deserialize_u64")]
+    pub ts: u64,
+}
+
+#[derive(Debug, Deserialize, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OkexTradeSide {
+    Buy,
+    Sell,
+}
+
+impl OkexTrade {
+    pub fn to_internal(&self) -> eyre::Result<Trade> {
+        Ok(Trade {
+            exchange_time: Some(self.ts),
+            exchange: Exchange::Okex,
+            instrument: MdInstrument::Symbol(OkexSymbols::from_native(&self.inst_id)?),
+            trade_id: self.trade_id.clone(),
+            price: self.price,
+            amount: self.amount,
+            side: match self.side {
+                OkexTradeSide::Buy => Side::Bid,
+                OkexTradeSide::Sell => Side::Ask,
+            },
+        })
+    }
+}
+
+/// Level of the `[price, size, deprecated, orders number]` form
 #[derive(Debug, Deserialize, Clone)]
+#[serde(try_from = "[CompactString; 4]")]
 pub struct OkexBookLevel {
     pub price: Price,
     pub amount: Amount,
+    /// Price and size as sent by Okex, the checksum is calculated from these strings
+    pub raw_price: CompactString,
+    pub raw_amount: CompactString,
     pub deprecated: CompactString,
     pub orders_number: CompactString,
 }
 
+impl TryFrom<[CompactString; 4]> for OkexBookLevel {
+    type Error = fixnum::ConvertError;
+
+    fn try_from([raw_price, raw_amount, deprecated, orders_number]: [CompactString; 4]) -> Result<Self, Self::Error> {
+        Ok(Self {
+            price: Price::from_str(&raw_price)?,
+            amount: Amount::from_str(&raw_amount)?,
+            raw_price,
+            raw_amount,
+            deprecated,
+            orders_number,
+        })
+    }
+}
+
 impl OkexBookLevel {
     pub fn to_md(
         &self,
         exchange_time: Option<u64>,
-        symbol: CompactString,
+        symbol: Symbol,
         side: Side,
         last_update_id: u64,
         is_eot: bool,
@@ -118,7 +215,8 @@ impl OkexBookLevel {
         MdMessage::L2Increment(L2Increment {
             exchange_time,
             sequence_no: Some(last_update_id),
-            symbol,
+            exchange: Exchange::Okex,
+            instrument: MdInstrument::Symbol(symbol),
             side,
             price: self.price,
             amount: self.amount,
@@ -134,11 +232,11 @@ impl OkexBookLevel {
     }
 }
 
-impl Into<SingleLot> for OkexBookLevel {
-    fn into(self) -> SingleLot {
+impl From<OkexBookLevel> for SingleLot {
+    fn from(level: OkexBookLevel) -> Self {
         SingleLot {
-            price: self.price,
-            amount: self.amount,
+            price: level.price,
+            amount: level.amount,
         }
     }
 }
@@ -156,13 +254,29 @@ impl <R: Serialize> WsRequest<R> {
             args: channels,
         }
     }
+
+    pub fn new_login(args: Vec<R>) -> Self {
+        Self {
+            op: EventType::Login,
+            args,
+        }
+    }
+
+    pub fn new_unsubscribe(channels: Vec<R>) -> Self {
+        Self {
+            op: EventType::Unsubscribe,
+            args: channels,
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use std::fs;
 
-    use crate::gates::okex::md::model::OkexWsMessage;
+    use crate::gates::okex::md::model::{EventType, OkexWsDataMessage, OkexWsMessage, Stream, WsRequest};
+    use crate::model::internal::{MdInstrument, Side};
+    use crate::model::symbol::Symbol;
 
     #[test]
     fn order_book_parsing() {
@@ -179,4 +293,79 @@ mod tests {
 
         assert!(matches!(symbol, OkexWsMessage::Combined { .. }));
     }
+
+    #[test]
+    fn subscribe_event_parsing() {
+        let event_str = fs::read_to_string("tests/ws_subscribe_event.json").unwrap();
+        let event: OkexWsMessage = serde_json::from_str(&event_str).unwrap();
+
+        let OkexWsMessage::SubEvent(event) = event else {
+            panic!("expected SubEvent, got {event:?}");
+        };
+        assert!(matches!(event.event, EventType::Unsubscribe));
+        assert_eq!(event.arg.unwrap().inst_id, "BTC-USDT");
+    }
+
+    #[test]
+    fn unsubscribe_request_serialization() {
+        let request = WsRequest::new_unsubscribe(vec![Stream { channel: "books".into(), inst_id: "BTC-USDT".into() }]);
+        assert_eq!(
+            serde_json::to_string(&request).unwrap(),
+            r#"{"op":"unsubscribe","args":[{"channel":"books","instId":"BTC-USDT"}]}"#,
+        );
+    }
+
+    #[test]
+    fn trades_parsing() {
+        let trades_str = fs::read_to_string("tests/ws_trades.json").unwrap();
+        let message: OkexWsMessage = serde_json::from_str(&trades_str).unwrap();
+
+        let OkexWsMessage::Combined(combined) = message else {
+            panic!("expected Combined, got {message:?}");
+        };
+        let [OkexWsDataMessage::Trade(trade)] = combined.message.as_slice() else {
+            panic!("expected single trade, got {combined:?}");
+        };
+        let trade = trade.to_internal().unwrap();
+        assert_eq!(trade.instrument, MdInstrument::Symbol(Symbol::spot("BTC", "USDT")));
+        assert_eq!(trade.trade_id, "130639474");
+        assert_eq!(trade.side, Side::Bid);
+        assert_eq!(trade.exchange_time, Some(1630048897897));
+    }
+
+    #[test]
+    fn derivatives_parsing() {
+        let fixtures = [
+            "tests/ws_funding_rate.json",
+            "tests/ws_mark_price.json",
+            "tests/ws_index_tickers.json",
+            "tests/ws_open_interest.json",
+        ];
+        for fixture in fixtures {
+            let message_str = fs::read_to_string(fixture).unwrap();
+            let message: OkexWsMessage = serde_json::from_str(&message_str).unwrap();
+            let OkexWsMessage::Combined(combined) = message else {
+                panic!("expected Combined in {fixture}, got {message:?}");
+            };
+            let parsed_as_expected = match combined.message.first().unwrap() {
+                OkexWsDataMessage::FundingRate(f) => fixture.contains("funding_rate") && f.next_funding_rate.is_none(),
+                OkexWsDataMessage::MarkPrice(_) => fixture.contains("mark_price"),
+                OkexWsDataMessage::IndexTicker(_) => fixture.contains("index_tickers"),
+                OkexWsDataMessage::OpenInterest(o) => fixture.contains("open_interest") && o.oi_ccy.is_some(),
+                _ => false,
+            };
+            assert!(parsed_as_expected, "unexpected parsing of {fixture}: {combined:?}");
+        }
+    }
+
+    #[test]
+    fn candle_parsing() {
+        let candle_str = fs::read_to_string("tests/ws_candle.json").unwrap();
+        let message: OkexWsMessage = serde_json::from_str(&candle_str).unwrap();
+
+        let OkexWsMessage::Combined(combined) = message else {
+            panic!("expected Combined, got {message:?}");
+        };
+        assert!(matches!(combined.message.as_slice(), [OkexWsDataMessage::Candle(_)]));
+    }
 }
@@ -0,0 +1,181 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use eyre::{OptionExt, Result};
+use log::{error, trace, warn};
+use tokio::sync::mpsc::{self, Receiver, Sender};
+use tokio::task::JoinHandle;
+
+use crate::api::connection::MdConnection;
+use crate::api::ws::ReconnectFailed;
+use crate::gates::okex::md::config::OkexMdConnectionConfig;
+use crate::gates::okex::md::connection::OkexMdConnection;
+use crate::model::instrument::InstrumentRegistry;
+use crate::model::internal::MdMessage;
+use crate::model::symbol::Symbol;
+
+/// Amount of messages buffered from all the shards before they wait for the consumer
+const POOL_BUFFER_SIZE: usize = 4096;
+
+/// Splits the tickers across several `OkexMdConnection`s, each of at most
+/// `OkexMdConnectionConfig::channel_tickers_amount` instruments, and merges their output.
+/// Every shard runs in its own task and reconnects independently of the others.
+pub struct OkexMdConnectionPool {
+    rx: Receiver<Result<MdMessage>>,
+    shards: Vec<JoinHandle<()>>,
+}
+
+impl OkexMdConnectionPool {
+    pub async fn new(symbols: Vec<Symbol>, config: OkexMdConnectionConfig) -> Result<Self> {
+        Self::start(symbols, config, None).await
+    }
+
+    /// Emitted messages will carry `MdInstrument::Id` of the instruments known to the registry
+    pub async fn with_registry(
+        symbols: Vec<Symbol>,
+        config: OkexMdConnectionConfig,
+        registry: Arc<InstrumentRegistry>,
+    ) -> Result<Self> {
+        Self::start(symbols, config, Some(registry)).await
+    }
+
+    async fn start(
+        symbols: Vec<Symbol>,
+        config: OkexMdConnectionConfig,
+        registry: Option<Arc<InstrumentRegistry>>,
+    ) -> Result<Self> {
+        let (tx, rx) = mpsc::channel(POOL_BUFFER_SIZE);
+
+        let mut shards = Vec::new();
+        for (shard, symbols) in split_symbols(symbols, config.channel_tickers_amount).into_iter().enumerate() {
+            trace!("Starting Okex md shard {shard} with {} instruments", symbols.len());
+            let connection = match OkexMdConnection::new(symbols, config.clone()).await {
+                Ok(connection) => match &registry {
+                    Some(registry) => connection.with_registry(registry.clone()),
+                    None => connection,
+                },
+                Err(err) => {
+                    shards.iter().for_each(JoinHandle::abort);
+                    return Err(err.wrap_err(format!("Failed to start Okex md shard {shard}")));
+                }
+            };
+            shards.push(tokio::spawn(Self::run_shard(shard, connection, tx.clone())));
+        }
+
+        Ok(Self { rx, shards })
+    }
+
+    pub fn shards_amount(&self) -> usize {
+        self.shards.len()
+    }
+
+    /// Forwards the messages and the errors of the shard until the pool is dropped
+    /// or the connection can't be reestablished anymore
+    async fn run_shard(shard: usize, mut connection: impl MdConnection, tx: Sender<Result<MdMessage>>) {
+        loop {
+            let message = connection.next().await;
+            let terminal = match &message {
+                Err(err) => {
+                    warn!("Okex md shard {shard} failure: {err}");
+                    err.downcast_ref::<ReconnectFailed>().is_some()
+                }
+                Ok(_) => false,
+            };
+            if tx.send(message).await.is_err() {
+                trace!("Okex md pool was dropped, stopping shard {shard}");
+                return;
+            }
+            if terminal {
+                error!("Okex md shard {shard} can't reconnect, stopping it");
+                return;
+            }
+        }
+    }
+}
+
+impl Drop for OkexMdConnectionPool {
+    fn drop(&mut self) {
+        self.shards.iter().for_each(JoinHandle::abort);
+    }
+}
+
+#[async_trait]
+impl MdConnection for OkexMdConnectionPool {
+    async fn next(&mut self) -> Result<MdMessage> {
+        self.rx.recv().await.ok_or_eyre("All Okex md shards have stopped")?
+    }
+}
+
+fn split_symbols(symbols: Vec<Symbol>, shard_size: usize) -> Vec<Vec<Symbol>> {
+    symbols
+        .chunks(shard_size.max(1))
+        .map(<[Symbol]>::to_vec)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use async_trait::async_trait;
+    use tokio::sync::mpsc;
+
+    use crate::api::connection::MdConnection;
+    use crate::api::ws::ReconnectFailed;
+    use crate::gates::okex::md::pool::{split_symbols, OkexMdConnectionPool};
+    use crate::model::internal::MdMessage;
+    use crate::model::symbol::Symbol;
+
+    struct DeadConnection;
+
+    #[async_trait]
+    impl MdConnection for DeadConnection {
+        async fn next(&mut self) -> eyre::Result<MdMessage> {
+            Err(ReconnectFailed { url: "ws://localhost".into(), attempts: 3 }.into())
+        }
+    }
+
+    #[tokio::test]
+    async fn shard_stops_after_reconnect_failure() {
+        let (tx, mut rx) = mpsc::channel(16);
+        OkexMdConnectionPool::run_shard(0, DeadConnection, tx).await;
+        assert!(rx.recv().await.unwrap().unwrap_err().downcast_ref::<ReconnectFailed>().is_some());
+        assert!(rx.recv().await.is_none());
+    }
+
+    #[test]
+    fn symbols_are_split_by_shard_size() {
+        let symbols: Vec<Symbol> = (0..7).map(|i| Symbol::spot(format!("T{i}"), "USDT")).collect();
+
+        let shards = split_symbols(symbols.clone(), 3);
+        assert_eq!(shards.iter().map(Vec::len).collect::<Vec<_>>(), [3, 3, 1]);
+        assert_eq!(shards.concat(), symbols);
+
+        assert_eq!(split_symbols(symbols, 0).len(), 7);
+    }
+}
+
+#[cfg(test)]
+mod pool_integration_tests {
+    use crate::api::connection::MdConnection;
+    use crate::gates::okex::md::config::OkexMdConnectionConfig;
+    use crate::gates::okex::md::pool::OkexMdConnectionPool;
+    use crate::model::storage::Storage;
+    use crate::model::symbol::Symbol;
+
+    #[ignore]
+    #[tokio::test]
+    async fn pool_test() {
+        let mut storage = Storage::new();
+
+        let config = OkexMdConnectionConfig {
+            channel_tickers_amount: 1,
+            ..Default::default()
+        };
+        let mut pool = OkexMdConnectionPool::new(vec![Symbol::spot("BTC", "USDT"), Symbol::spot("ETH", "USDT")], config).await.unwrap();
+        assert_eq!(pool.shards_amount(), 2);
+
+        for _ in 0..100 {
+            let m = pool.next().await.unwrap();
+            storage.on_ws_update(m);
+        }
+    }
+}
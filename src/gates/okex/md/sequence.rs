@@ -0,0 +1,61 @@
+/// Result of comparing the `prevSeqId`/`seqId` pair of an incremental message
+/// with the last `seqId` received for the instrument.
+///
+/// https://www.okx.com/docs-v5/en/#order-book-trading-market-data-ws-order-book-channel
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum SequenceStatus {
+    /// `prevSeqId` matches the last `seqId`
+    InOrder,
+    /// No book changes for a while, Okex repeats the last sequence: `prevSeqId == seqId`
+    Heartbeat,
+    /// Sequence was reset during maintenance, `seqId` is smaller than `prevSeqId`
+    Reset,
+    /// The message was already applied
+    Duplicate,
+    /// One or more messages were lost, the book has to be resubscribed
+    Gap { expected: Option<u64>, received: u64 },
+}
+
+impl SequenceStatus {
+    pub fn classify(last_seq_id: Option<u64>, prev_seq_id: u64, seq_id: u64) -> Self {
+        let Some(last_seq_id) = last_seq_id else {
+            return Self::Gap { expected: None, received: prev_seq_id };
+        };
+
+        if prev_seq_id == last_seq_id {
+            if seq_id == prev_seq_id {
+                Self::Heartbeat
+            } else if seq_id < prev_seq_id {
+                Self::Reset
+            } else {
+                Self::InOrder
+            }
+        } else if seq_id <= last_seq_id && prev_seq_id < last_seq_id {
+            Self::Duplicate
+        } else {
+            Self::Gap { expected: Some(last_seq_id), received: prev_seq_id }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::gates::okex::md::sequence::SequenceStatus;
+
+    #[test]
+    fn sequence_classification() {
+        assert_eq!(SequenceStatus::classify(Some(10), 10, 15), SequenceStatus::InOrder);
+        assert_eq!(SequenceStatus::classify(Some(10), 10, 10), SequenceStatus::Heartbeat);
+        assert_eq!(SequenceStatus::classify(Some(10), 10, 3), SequenceStatus::Reset);
+        assert_eq!(SequenceStatus::classify(Some(10), 7, 10), SequenceStatus::Duplicate);
+        assert_eq!(SequenceStatus::classify(Some(10), 7, 9), SequenceStatus::Duplicate);
+        assert_eq!(
+            SequenceStatus::classify(Some(10), 12, 15),
+            SequenceStatus::Gap { expected: Some(10), received: 12 },
+        );
+        assert_eq!(
+            SequenceStatus::classify(None, 12, 15),
+            SequenceStatus::Gap { expected: None, received: 12 },
+        );
+    }
+}
@@ -1,41 +1,203 @@
-use compact_str::{CompactString, ToCompactString};
+use compact_str::{CompactString, format_compact};
+use serde::Deserialize;
 
+use crate::gates::okex::common::auth::{OkexCredentials, OkexLoginArgs};
+use crate::gates::okex::common::model::{from_okex_bar, okex_bar};
 use crate::gates::okex::md::model::{Stream, WsRequest};
+use crate::model::internal::CandleInterval;
 use crate::model::stream::WsStream;
 
 #[derive(Clone)]
 pub struct OkexStream {
     pub tickers: Vec<CompactString>,
     pub kind: OkexStreamKind,
+    /// Required for the tick-by-tick channels only
+    pub credentials: Option<OkexCredentials>,
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize)]
+#[serde(try_from = "CompactString")]
 pub enum OkexStreamKind {
     /// books: 400 depth levels will be pushed in the initial full snapshot.
     /// Incremental data will be pushed every 100 ms for the changes in the order book during that period of time.
     ///
     /// https://www.okx.com/docs-v5/en/#order-book-trading-market-data-ws-order-book-channel
     L2Update,
+    /// books5: 5 depth levels snapshot will be pushed every 100 ms when there are changes in the order book.
+    Books5,
+    /// bbo-tbt: 1 depth level snapshot will be pushed every 10 ms when there are changes in the order book.
+    BboTbt,
+    /// books-l2-tbt: 400 depth levels will be pushed in the initial full snapshot.
+    /// Incremental data will be pushed every 10 ms for the changes in the order book during that period of time.
+    /// Requires login.
+    L2Tbt,
+    /// books50-l2-tbt: 50 depth levels will be pushed in the initial full snapshot.
+    /// Incremental data will be pushed every 10 ms for the changes in the order book during that period of time.
+    /// Requires login.
+    L2Tbt50,
+    /// trades: public trades, one update may aggregate several trades of the same taker order.
+    ///
+    /// https://www.okx.com/docs-v5/en/#order-book-trading-market-data-ws-trades-channel
+    Trades,
+    /// trades-all: every single public trade.
+    /// Available on the business websocket url `wss://ws.okx.com:8443/ws/v5/business` only.
+    ///
+    /// https://www.okx.com/docs-v5/en/#order-book-trading-market-data-ws-all-trades-channel
+    TradesAll,
+    /// tickers: last traded price, bid price, ask price and 24-hour trading volume,
+    /// pushed every 100 ms when there are changes.
+    ///
+    /// https://www.okx.com/docs-v5/en/#order-book-trading-market-data-ws-tickers-channel
+    Tickers,
+    /// funding-rate: funding rate of perpetual swaps, pushed every 30 to 90 seconds.
+    ///
+    /// https://www.okx.com/docs-v5/en/#public-data-websocket-funding-rate-channel
+    FundingRate,
+    /// mark-price: mark price of derivatives, pushed every 200 ms when the price changes.
+    ///
+    /// https://www.okx.com/docs-v5/en/#public-data-websocket-mark-price-channel
+    MarkPrice,
+    /// index-tickers: index price, pushed every 100 ms when there are changes. Subscribed by index, e.g. BTC-USDT.
+    ///
+    /// https://www.okx.com/docs-v5/en/#public-data-websocket-index-tickers-channel
+    IndexTickers,
+    /// open-interest: open interest of derivatives, pushed every 3 seconds when there are changes.
+    ///
+    /// https://www.okx.com/docs-v5/en/#public-data-websocket-open-interest-channel
+    OpenInterest,
+    /// candle{bar}: candlesticks of the interval, pushed every 500 ms.
+    /// Available on the business websocket url `wss://ws.okx.com:8443/ws/v5/business` only.
+    ///
+    /// https://www.okx.com/docs-v5/en/#order-book-trading-market-data-ws-candlesticks-channel
+    Candle(CandleInterval),
+}
+
+impl OkexStreamKind {
+    pub fn channel(&self) -> CompactString {
+        let channel = match self {
+            OkexStreamKind::L2Update => "books",
+            OkexStreamKind::Books5 => "books5",
+            OkexStreamKind::BboTbt => "bbo-tbt",
+            OkexStreamKind::L2Tbt => "books-l2-tbt",
+            OkexStreamKind::L2Tbt50 => "books50-l2-tbt",
+            OkexStreamKind::Trades => "trades",
+            OkexStreamKind::TradesAll => "trades-all",
+            OkexStreamKind::Tickers => "tickers",
+            OkexStreamKind::FundingRate => "funding-rate",
+            OkexStreamKind::MarkPrice => "mark-price",
+            OkexStreamKind::IndexTickers => "index-tickers",
+            OkexStreamKind::OpenInterest => "open-interest",
+            OkexStreamKind::Candle(interval) => return format_compact!("candle{}", okex_bar(*interval)),
+        };
+        channel.into()
+    }
+
+    pub fn from_channel(channel: &str) -> Option<Self> {
+        if let Some(bar) = channel.strip_prefix("candle") {
+            return from_okex_bar(bar).map(OkexStreamKind::Candle);
+        }
+        [
+            OkexStreamKind::L2Update,
+            OkexStreamKind::Books5,
+            OkexStreamKind::BboTbt,
+            OkexStreamKind::L2Tbt,
+            OkexStreamKind::L2Tbt50,
+            OkexStreamKind::Trades,
+            OkexStreamKind::TradesAll,
+            OkexStreamKind::Tickers,
+            OkexStreamKind::FundingRate,
+            OkexStreamKind::MarkPrice,
+            OkexStreamKind::IndexTickers,
+            OkexStreamKind::OpenInterest,
+        ]
+            .into_iter()
+            .find(|kind| kind.channel() == channel)
+    }
+
+    /// Incremental channels push a snapshot first and then only the changed levels,
+    /// the other channels push a full snapshot every time
+    pub fn is_incremental(&self) -> bool {
+        matches!(self, OkexStreamKind::L2Update | OkexStreamKind::L2Tbt | OkexStreamKind::L2Tbt50)
+    }
+
+    pub fn requires_login(&self) -> bool {
+        matches!(self, OkexStreamKind::L2Tbt | OkexStreamKind::L2Tbt50)
+    }
+}
+
+impl TryFrom<CompactString> for OkexStreamKind {
+    type Error = String;
+
+    fn try_from(channel: CompactString) -> Result<Self, Self::Error> {
+        Self::from_channel(&channel).ok_or_else(|| format!("unknown Okex channel {channel}"))
+    }
+}
+
+impl OkexStream {
+    pub fn streams(&self, tickers: &[CompactString]) -> Vec<Stream> {
+        let channel = self.kind.channel();
+        tickers
+            .iter()
+            .map(|inst_id| Stream { channel: channel.clone(), inst_id: inst_id.clone() })
+            .collect()
+    }
 }
 
 impl WsStream for OkexStream {
     type Kind = OkexStreamKind;
     type Subscribe = WsRequest<Stream>;
+    type Login = WsRequest<OkexLoginArgs>;
 
     fn kind(&self) -> Self::Kind {
         self.kind
     }
 
     fn subscribe_requests(&self) -> Vec<Self::Subscribe> {
-        let channel = match self.kind {
-            OkexStreamKind::L2Update => "books",
-        }.to_compact_string();
+        vec![WsRequest::new_subscribe(self.streams(&self.tickers))]
+    }
 
-        let requests = self.tickers
-            .iter()
-            .map(|inst_id| Stream { channel: channel.clone(), inst_id: inst_id.clone() })
-            .collect();
+    fn login_requests(&self) -> Vec<Self::Login> {
+        match &self.credentials {
+            Some(credentials) if self.kind.requires_login() => {
+                vec![WsRequest::new_login(vec![credentials.login_args()])]
+            }
+            _ => Vec::new(),
+        }
+    }
+}
 
-        vec![WsRequest::new_subscribe(requests)]
+#[cfg(test)]
+mod tests {
+    use crate::gates::okex::md::stream::OkexStreamKind;
+    use crate::model::internal::CandleInterval;
+
+    #[test]
+    fn channel_mapping() {
+        let channels = [
+            "books",
+            "books5",
+            "bbo-tbt",
+            "books-l2-tbt",
+            "books50-l2-tbt",
+            "trades",
+            "trades-all",
+            "tickers",
+            "funding-rate",
+            "mark-price",
+            "index-tickers",
+            "open-interest",
+            "candle1m",
+            "candle4H",
+            "candle1M",
+        ];
+        for channel in channels {
+            let kind = OkexStreamKind::from_channel(channel).unwrap();
+            assert_eq!(kind.channel(), channel);
+            let deserialized: OkexStreamKind = serde_json::from_str(&format!("\"{channel}\"")).unwrap();
+            assert_eq!(deserialized, kind);
+        }
+        assert_eq!(OkexStreamKind::from_channel("candle1m"), Some(OkexStreamKind::Candle(CandleInterval::Minute1)));
+        assert_eq!(OkexStreamKind::from_channel("candle7m"), None);
+        assert_eq!(OkexStreamKind::from_channel("status"), None);
     }
-}
\ No newline at end of file
+}
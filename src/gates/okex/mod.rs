@@ -1,3 +1,4 @@
-mod common;
-mod crawler;
-mod md;
\ No newline at end of file
+pub mod common;
+pub mod crawler;
+pub mod md;
+pub mod private;
@@ -0,0 +1,96 @@
+use eyre::eyre;
+
+use crate::gates::okex::common::account::OkexOrderAck;
+use crate::model::order::OrderAck;
+
+/// Maximum number of orders of the batch operations, both of REST and of websocket
+pub const BATCH_LIMIT: usize = 20;
+
+/// Requests of a batch operation, keeping the results in the order of the requests.
+/// The requests which failed to build get their errors right away, the rest is sent in chunks of `BATCH_LIMIT`.
+pub struct OrderBatch<R> {
+    results: Vec<Option<eyre::Result<OrderAck>>>,
+    /// Valid requests with their positions in `results`
+    requests: Vec<(usize, R)>,
+}
+
+impl<R: Clone> OrderBatch<R> {
+    pub fn new(requests: Vec<eyre::Result<R>>) -> Self {
+        let mut batch = Self { results: Vec::with_capacity(requests.len()), requests: Vec::new() };
+        for (i, request) in requests.into_iter().enumerate() {
+            match request {
+                Ok(request) => {
+                    batch.requests.push((i, request));
+                    batch.results.push(None);
+                }
+                Err(err) => batch.results.push(Some(Err(err))),
+            }
+        }
+        batch
+    }
+
+    pub fn chunks(&self) -> Vec<Vec<R>> {
+        self.requests
+            .chunks(BATCH_LIMIT)
+            .map(|chunk| chunk.iter().map(|(_, r)| r.clone()).collect())
+            .collect()
+    }
+
+    /// Takes the responses to `chunks` in the same order, the items of a failed chunk fail with its error
+    pub fn into_results(mut self, responses: Vec<eyre::Result<Vec<OkexOrderAck>>>) -> Vec<eyre::Result<OrderAck>> {
+        for (chunk, response) in self.requests.chunks(BATCH_LIMIT).zip(responses) {
+            match response {
+                Ok(acks) if acks.len() == chunk.len() => {
+                    for ((i, _), ack) in chunk.iter().zip(&acks) {
+                        self.results[*i] = Some(ack.to_internal());
+                    }
+                }
+                Ok(acks) => {
+                    for (i, _) in chunk {
+                        let err = eyre!("Okex returned {} acks for the batch of {} orders", acks.len(), chunk.len());
+                        self.results[*i] = Some(Err(err));
+                    }
+                }
+                Err(err) => {
+                    for (i, _) in chunk {
+                        self.results[*i] = Some(Err(eyre!("batch of {} orders failed, {err}", chunk.len())));
+                    }
+                }
+            }
+        }
+        self.results
+            .into_iter()
+            .map(|r| r.unwrap_or_else(|| Err(eyre!("no response to the batch"))))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use eyre::eyre;
+
+    use crate::gates::okex::common::account::OkexOrderAck;
+    use crate::gates::okex::private::batch::{BATCH_LIMIT, OrderBatch};
+
+    fn ack(id: usize) -> OkexOrderAck {
+        OkexOrderAck { ord_id: id.to_string().into(), cl_ord_id: "".into(), s_code: "0".into(), s_msg: "".into() }
+    }
+
+    #[test]
+    fn failed_chunk_keeps_results_of_others() {
+        let mut requests: Vec<eyre::Result<usize>> = (0..BATCH_LIMIT + 5).map(Ok).collect();
+        requests[1] = Err(eyre!("invalid request"));
+        let batch = OrderBatch::new(requests);
+
+        let chunks = batch.chunks();
+        assert_eq!(chunks.iter().map(Vec::len).collect::<Vec<_>>(), vec![BATCH_LIMIT, 4]);
+        let first = chunks[0].iter().map(|&i| ack(i)).collect();
+        let results = batch.into_results(vec![Ok(first), Err(eyre!("connection reset"))]);
+
+        assert_eq!(results.len(), BATCH_LIMIT + 5);
+        assert_eq!(results[0].as_ref().unwrap().order_id, "0");
+        assert_eq!(results[1].as_ref().unwrap_err().to_string(), "invalid request");
+        assert_eq!(results[BATCH_LIMIT].as_ref().unwrap().order_id, "20");
+        assert!(results[BATCH_LIMIT + 1..].iter().all(|r| r.as_ref().unwrap_err().to_string().contains("connection reset")));
+    }
+}
@@ -0,0 +1,393 @@
+use std::collections::HashSet;
+use std::sync::RwLock;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use compact_str::CompactString;
+use eyre::OptionExt;
+use http::{HeaderMap, HeaderValue};
+use log::warn;
+use tokio::time::MissedTickBehavior;
+
+use crate::api::api;
+use crate::api::endpoint::Endpoint;
+use crate::api::trader::ExchangeTrader;
+use crate::gates::okex::common::account::{OkexOrderAck, OkexPosition};
+use crate::gates::okex::common::model::OkexInstType;
+use crate::gates::okex::common::response::OkexResponse;
+use crate::gates::okex::common::symbol::OkexSymbols;
+use crate::gates::okex::private::batch::OrderBatch;
+use crate::gates::okex::private::config::OkexPrivateClientConfig;
+use crate::gates::okex::private::endpoints::{
+    AmendOrder,
+    AmendOrders,
+    CancelOrder,
+    CancelOrders,
+    GetBalance,
+    GetOrderHistory,
+    GetPendingOrders,
+    GetPositions,
+    PlaceOrder,
+    PlaceOrders,
+};
+use crate::gates::okex::private::request::{
+    AmendOrderRequest,
+    CancelOrderRequest,
+    GetBalanceRequest,
+    GetOrderHistoryRequest,
+    GetPendingOrdersRequest,
+    GetPositionsRequest,
+    PlaceOrderRequest,
+};
+use crate::model::account::{Balance, Position};
+use crate::model::account_store::AccountStore;
+use crate::model::exchange::Exchange;
+use crate::model::order::{AmendRequest, CancelRequest, OrderAck, OrderRef, OrderRequest, OrderUpdate};
+use crate::model::order_tracker::OrderTracker;
+use crate::model::symbol::{Symbol, SymbolMapping};
+
+/// Maximum page size of `/api/v5/trade/orders-pending`
+const PENDING_ORDERS_LIMIT: u16 = 100;
+/// Maximum page size of `/api/v5/trade/orders-history`
+const ORDER_HISTORY_LIMIT: u16 = 100;
+
+/// Signed requests to the private REST endpoints of the Okex account
+///
+/// https://www.okx.com/docs-v5/en/#overview-rest-authentication
+#[derive(Debug)]
+pub struct OkexPrivateClient {
+    pub config: OkexPrivateClientConfig,
+}
+
+impl OkexPrivateClient {
+    pub fn new(config: OkexPrivateClientConfig) -> Self {
+        Self { config }
+    }
+
+    /// Balances of the currencies, all the non-zero ones if `currencies` is empty
+    pub async fn get_balances(&self, currencies: &[CompactString]) -> eyre::Result<Vec<Balance>> {
+        let balances = self.query::<GetBalance, _>(&GetBalanceRequest::new(currencies))
+            .await?
+            .iter()
+            .flat_map(|account| account.details.iter().map(|d| d.to_internal()))
+            .collect();
+
+        Ok(balances)
+    }
+
+    /// Open positions of all the instruments of the type or of the single instrument
+    pub async fn get_positions(
+        &self,
+        inst_type: Option<OkexInstType>,
+        symbol: Option<&Symbol>,
+    ) -> eyre::Result<Vec<Position>> {
+        let request = GetPositionsRequest::new(inst_type, symbol.map(OkexSymbols::to_native).transpose()?);
+        self.query::<GetPositions, _>(&request)
+            .await?
+            .iter()
+            .map(OkexPosition::to_internal)
+            .collect()
+    }
+
+    /// All the incomplete orders of the instruments of the type or of the single instrument, newest first.
+    /// Pages back by order id until a page is not full.
+    pub async fn get_pending_orders(
+        &self,
+        inst_type: Option<OkexInstType>,
+        symbol: Option<&Symbol>,
+    ) -> eyre::Result<Vec<OrderUpdate>> {
+        let inst_id = symbol.map(OkexSymbols::to_native).transpose()?;
+        let mut orders = Vec::new();
+        let mut after = None;
+        loop {
+            let request = GetPendingOrdersRequest::new(inst_type, inst_id.clone(), after, Some(PENDING_ORDERS_LIMIT));
+            let page = self.query::<GetPendingOrders, _>(&request).await?;
+            for order in &page {
+                orders.push(order.to_internal()?);
+            }
+            match page.last() {
+                Some(oldest) if page.len() == PENDING_ORDERS_LIMIT as usize => after = Some(oldest.ord_id.clone()),
+                _ => break,
+            }
+        }
+
+        Ok(orders)
+    }
+
+    /// Cancel requests of all the pending orders, of the symbol only if set.
+    /// Okex has no cancel-all endpoint, so the orders are canceled by id.
+    pub async fn pending_cancels(&self, symbol: Option<&Symbol>) -> eyre::Result<Vec<CancelRequest>> {
+        let cancels = self.get_pending_orders(None, symbol)
+            .await?
+            .into_iter()
+            .map(|o| CancelRequest { symbol: o.symbol, order: OrderRef::OrderId(o.order_id) })
+            .collect();
+        Ok(cancels)
+    }
+
+    /// Completed orders of the instrument of the last 7 days, newest first.
+    /// Pages back by order id until a page is not full.
+    pub async fn get_order_history(&self, symbol: &Symbol) -> eyre::Result<Vec<OrderUpdate>> {
+        self.get_order_history_until(symbol, |_| false).await
+    }
+
+    /// Like `get_order_history`, but stops paging back once `done` returns true for the orders fetched so far
+    async fn get_order_history_until(
+        &self,
+        symbol: &Symbol,
+        mut done: impl FnMut(&[OrderUpdate]) -> bool,
+    ) -> eyre::Result<Vec<OrderUpdate>> {
+        let inst_id = OkexSymbols::to_native(symbol)?;
+        let mut orders = Vec::new();
+        let mut after = None;
+        loop {
+            let request = GetOrderHistoryRequest::new(
+                OkexInstType::from_symbol(symbol),
+                Some(inst_id.clone()),
+                after,
+                Some(ORDER_HISTORY_LIMIT),
+            );
+            let page = self.query::<GetOrderHistory, _>(&request).await?;
+            for order in &page {
+                orders.push(order.to_internal()?);
+            }
+            match page.last() {
+                Some(oldest) if page.len() == ORDER_HISTORY_LIMIT as usize && !done(&orders) => {
+                    after = Some(oldest.ord_id.clone());
+                }
+                _ => break,
+            }
+        }
+
+        Ok(orders)
+    }
+
+    /// Brings the tracker up to date after a reconnect: applies the pending orders
+    /// and looks up the orders which are open in the tracker, but not on the exchange anymore,
+    /// in the order history of their instruments.
+    ///
+    /// The tracker is locked only to apply the fetched orders, never across the requests,
+    /// so that the trading can go on meanwhile. The orders acknowledged after the pending orders
+    /// were fetched may be reported as missing from the history.
+    pub async fn recover_orders(&self, tracker: &RwLock<OrderTracker>) -> eyre::Result<()> {
+        let pending = self.get_pending_orders(None, None).await?;
+        let missing = tracker.write().unwrap().on_open_orders(&pending);
+
+        let mut histories = Vec::new();
+        let symbols: HashSet<_> = missing.iter().map(|o| o.symbol.clone()).collect();
+        for symbol in &symbols {
+            let mut wanted: Vec<_> = missing.iter().filter(|o| &o.symbol == symbol).collect();
+            let history = self.get_order_history_until(symbol, |orders| {
+                // the pages are fetched until every missing order of the symbol is found
+                let last_page = &orders[orders.len().saturating_sub(ORDER_HISTORY_LIMIT as usize)..];
+                wanted.retain(|o| {
+                    !last_page.iter().any(|update| {
+                        o.order_id.as_ref() == Some(&update.order_id)
+                            || o.client_order_id.is_some() && o.client_order_id == update.client_order_id
+                    })
+                });
+                wanted.is_empty()
+            }).await?;
+            histories.push(history);
+        }
+
+        let mut tracker = tracker.write().unwrap();
+        for update in histories.iter().flatten() {
+            tracker.on_update(update);
+        }
+        for order in missing {
+            let id = match (order.order_id, order.client_order_id) {
+                (Some(id), _) => OrderRef::OrderId(id),
+                (None, Some(id)) => OrderRef::ClientOrderId(id),
+                (None, None) => continue,
+            };
+            if tracker.get(&id).is_some_and(|o| o.state.is_open()) {
+                warn!("Order {id:?} of {} is neither pending nor in the recent history of Okex", order.symbol);
+            }
+        }
+        Ok(())
+    }
+
+    /// Reconciles the store with the balance and position snapshots, in addition to applying
+    /// the account events of the private websocket. The store is locked only once the snapshots are fetched.
+    pub async fn reconcile_account(&self, store: &RwLock<AccountStore>) -> eyre::Result<()> {
+        let (balances, positions) = tokio::try_join!(self.get_balances(&[]), self.get_positions(None, None))?;
+        let mut store = store.write().unwrap();
+        store.reconcile_balances(Exchange::Okex, balances);
+        store.reconcile_positions(Exchange::Okex, positions);
+        Ok(())
+    }
+
+    /// Calls `reconcile_account` every `period` until dropped, e.g. from a spawned task next to
+    /// the one applying the private websocket events. A failed reconciliation is logged and retried on the next tick.
+    pub async fn reconcile_account_periodically(&self, store: &RwLock<AccountStore>, period: Duration) {
+        let mut interval = tokio::time::interval(period);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            if let Err(err) = self.reconcile_account(store).await {
+                warn!("Failed to reconcile the Okex account, {err}");
+            }
+        }
+    }
+
+    /// Sends the batches of up to `BATCH_LIMIT` items one after another,
+    /// a failed batch doesn't affect the results of the others
+    async fn batch<E, R>(&self, requests: Vec<eyre::Result<R>>) -> Vec<eyre::Result<OrderAck>>
+        where
+            E: Endpoint<Request = Vec<R>, Response = OkexResponse<OkexOrderAck>>,
+            R: Clone,
+    {
+        let batch = OrderBatch::new(requests);
+        let mut responses = Vec::new();
+        for chunk in batch.chunks() {
+            let response = self.send::<E>(&chunk).await;
+            responses.push(response.and_then(|r| Ok(r.into_batch_result()?)));
+        }
+        batch.into_results(responses)
+    }
+
+    /// Single trade operation, failing with the code and the message of the item
+    async fn single<E>(&self, request: &E::Request) -> eyre::Result<OrderAck>
+        where
+            E: Endpoint<Response = OkexResponse<OkexOrderAck>>,
+    {
+        self.send::<E>(request)
+            .await?
+            .into_batch_result()?
+            .first()
+            .ok_or_eyre("There was no order returned from Okex API")?
+            .to_internal()
+    }
+
+    fn headers(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        if self.config.simulated_trading {
+            headers.insert("x-simulated-trading", HeaderValue::from_static("1"));
+        }
+        headers
+    }
+
+    /// Sends the signed request and unwraps the data from the Okex response
+    async fn query<E, R>(&self, request: &E::Request) -> eyre::Result<Vec<R>>
+        where
+            E: Endpoint<Response = OkexResponse<R>>,
+    {
+        Ok(self.send::<E>(request).await?.into_result()?)
+    }
+
+    async fn send<E: Endpoint>(&self, request: &E::Request) -> eyre::Result<E::Response> {
+        api::http_signed_request::<E>(
+            &self.config.http_url,
+            request,
+            &self.config.credentials,
+            self.headers(),
+        ).await
+    }
+}
+
+/// https://www.okx.com/docs-v5/en/#order-book-trading-trade
+///
+/// The batch methods split the requests into batches of 20 orders, the Okex limit.
+#[async_trait]
+impl ExchangeTrader for OkexPrivateClient {
+    async fn place_order(&self, order: &OrderRequest) -> eyre::Result<OrderAck> {
+        self.single::<PlaceOrder>(&PlaceOrderRequest::new(order, self.config.margin_mode)?).await
+    }
+
+    async fn place_orders(&self, orders: &[OrderRequest]) -> eyre::Result<Vec<eyre::Result<OrderAck>>> {
+        let requests = orders.iter().map(|o| PlaceOrderRequest::new(o, self.config.margin_mode)).collect();
+        Ok(self.batch::<PlaceOrders, _>(requests).await)
+    }
+
+    async fn cancel_order(&self, cancel: &CancelRequest) -> eyre::Result<OrderAck> {
+        self.single::<CancelOrder>(&CancelOrderRequest::new(cancel)?).await
+    }
+
+    async fn cancel_orders(&self, cancels: &[CancelRequest]) -> eyre::Result<Vec<eyre::Result<OrderAck>>> {
+        let requests = cancels.iter().map(CancelOrderRequest::new).collect();
+        Ok(self.batch::<CancelOrders, _>(requests).await)
+    }
+
+    async fn amend_order(&self, amend: &AmendRequest) -> eyre::Result<OrderAck> {
+        self.single::<AmendOrder>(&AmendOrderRequest::new(amend)?).await
+    }
+
+    async fn amend_orders(&self, amends: &[AmendRequest]) -> eyre::Result<Vec<eyre::Result<OrderAck>>> {
+        let requests = amends.iter().map(AmendOrderRequest::new).collect();
+        Ok(self.batch::<AmendOrders, _>(requests).await)
+    }
+
+    async fn cancel_all(&self, symbol: Option<&Symbol>) -> eyre::Result<Vec<eyre::Result<OrderAck>>> {
+        self.cancel_orders(&self.pending_cancels(symbol).await?).await
+    }
+}
+
+#[cfg(test)]
+mod api_tests {
+    use std::str::FromStr;
+
+    use fixnum::ops::Zero;
+
+    use crate::api::trader::ExchangeTrader;
+    use crate::gates::okex::common::auth::OkexCredentials;
+    use crate::gates::okex::private::client::OkexPrivateClient;
+    use crate::gates::okex::private::config::OkexPrivateClientConfig;
+    use crate::model::internal::Side;
+    use crate::model::order::{AmendRequest, CancelRequest, OrderRef, OrderRequest, TimeInForce};
+    use crate::model::symbol::Symbol;
+    use crate::utils::basic_types::{Amount, Price};
+
+    fn demo_client() -> OkexPrivateClient {
+        let credentials = OkexCredentials {
+            api_key: std::env::var("OKX_API_KEY").unwrap().into(),
+            secret_key: std::env::var("OKX_SECRET_KEY").unwrap().into(),
+            passphrase: std::env::var("OKX_PASSPHRASE").unwrap().into(),
+        };
+        let config = OkexPrivateClientConfig { simulated_trading: true, ..OkexPrivateClientConfig::new(credentials) };
+        OkexPrivateClient::new(config)
+    }
+
+    /// Requires `OKX_API_KEY`, `OKX_SECRET_KEY` and `OKX_PASSPHRASE` of a demo trading account
+    #[ignore]
+    #[tokio::test]
+    async fn account_test() {
+        let client = demo_client();
+
+        let balances = client.get_balances(&[]).await.unwrap();
+        assert!(!balances.is_empty());
+        assert!(balances.iter().all(|b| !b.currency.is_empty()));
+        let positions = client.get_positions(None, None).await.unwrap();
+        assert!(positions.iter().all(|p| p.amount != Amount::ZERO));
+        let orders = client.get_pending_orders(None, None).await.unwrap();
+        assert!(orders.iter().all(|o| !o.status.is_final()));
+    }
+
+    /// Requires the credentials of a demo trading account with some USDT
+    #[ignore]
+    #[tokio::test]
+    async fn order_entry_test() {
+        let client = demo_client();
+        let symbol = Symbol::spot("BTC", "USDT");
+        let order = OrderRequest::limit(
+            symbol.clone(),
+            Side::Bid,
+            Price::from_str("1000").unwrap(),
+            Amount::from_str("0.001").unwrap(),
+            TimeInForce::PostOnly,
+        )
+            .with_client_order_id("ectest1");
+
+        let ack = client.place_order(&order).await.unwrap();
+        assert_eq!(ack.client_order_id.as_deref(), Some("ectest1"));
+        let amend = AmendRequest {
+            symbol: symbol.clone(),
+            order: OrderRef::ClientOrderId("ectest1".into()),
+            new_price: Some(Price::from_str("1001").unwrap()),
+            new_amount: None,
+        };
+        client.amend_order(&amend).await.unwrap();
+        client.cancel_order(&CancelRequest { symbol: symbol.clone(), order: OrderRef::OrderId(ack.order_id) }).await.unwrap();
+        assert!(client.cancel_all(Some(&symbol)).await.unwrap().iter().all(|r| r.is_ok()));
+    }
+}
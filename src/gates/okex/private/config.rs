@@ -0,0 +1,98 @@
+use compact_str::CompactString;
+use serde::Deserialize;
+
+use crate::api::backoff::BackoffConfig;
+use crate::gates::okex::common::account::OkexTradeMode;
+use crate::gates::okex::common::auth::OkexCredentials;
+use crate::gates::okex::private::stream::OkexPrivateChannel;
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct OkexPrivateConnectionConfig {
+    /// `wss://wspap.okx.com:8443/ws/v5/private` for demo trading
+    pub ws_url: CompactString,
+    pub credentials: OkexCredentials,
+    #[serde(default = "OkexPrivateChannel::all")]
+    pub channels: Vec<OkexPrivateChannel>,
+    /// Send the text `ping` if no message was received for this amount of seconds, should be less than 30
+    pub idle_timeout_seconds: u64,
+    /// Reconnect if no message arrives within this amount of seconds after `ping` was sent
+    pub pong_timeout_seconds: u64,
+    pub subscribe_interval_ms: u64,
+    /// Retry policy for connecting, reconnecting and resubscribing
+    #[serde(default)]
+    pub reconnect: BackoffConfig,
+}
+
+impl OkexPrivateConnectionConfig {
+    /// Production url and all the channels
+    pub fn new(credentials: OkexCredentials) -> Self {
+        Self {
+            ws_url: "wss://ws.okx.com:8443/ws/v5/private".into(),
+            credentials,
+            channels: OkexPrivateChannel::all(),
+            idle_timeout_seconds: 20,
+            pong_timeout_seconds: 5,
+            subscribe_interval_ms: 0,
+            reconnect: BackoffConfig::default(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct OkexPrivateClientConfig {
+    pub http_url: CompactString,
+    pub credentials: OkexCredentials,
+    /// Sends `x-simulated-trading: 1` to use the demo trading account
+    #[serde(default)]
+    pub simulated_trading: bool,
+    /// Trade mode of the derivatives orders, spot orders are placed in `cash` mode
+    #[serde(default = "OkexPrivateClientConfig::default_margin_mode")]
+    pub margin_mode: OkexTradeMode,
+}
+
+impl OkexPrivateClientConfig {
+    /// Production account
+    pub fn new(credentials: OkexCredentials) -> Self {
+        Self {
+            http_url: "https://www.okx.com".into(),
+            credentials,
+            simulated_trading: false,
+            margin_mode: Self::default_margin_mode(),
+        }
+    }
+
+    fn default_margin_mode() -> OkexTradeMode {
+        OkexTradeMode::Cross
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct OkexOrderGatewayConfig {
+    /// `wss://wspap.okx.com:8443/ws/v5/private` for demo trading
+    pub ws_url: CompactString,
+    /// Credentials and the margin mode of the orders, the REST endpoints are used to find the orders for `cancel_all`
+    pub client: OkexPrivateClientConfig,
+    /// Fail the request if its response doesn't arrive within this amount of milliseconds
+    pub request_timeout_ms: u64,
+    /// Send the text `ping` if no message was received for this amount of seconds, should be less than 30
+    pub idle_timeout_seconds: u64,
+    /// Reconnect if no message arrives within this amount of seconds after `ping` was sent
+    pub pong_timeout_seconds: u64,
+    /// Retry policy for connecting and reconnecting
+    #[serde(default)]
+    pub reconnect: BackoffConfig,
+}
+
+impl OkexOrderGatewayConfig {
+    /// Production urls
+    pub fn new(credentials: OkexCredentials) -> Self {
+        Self {
+            ws_url: "wss://ws.okx.com:8443/ws/v5/private".into(),
+            client: OkexPrivateClientConfig::new(credentials),
+            request_timeout_ms: 5000,
+            idle_timeout_seconds: 20,
+            pong_timeout_seconds: 5,
+            reconnect: BackoffConfig::default(),
+        }
+    }
+}
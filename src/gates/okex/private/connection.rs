@@ -0,0 +1,274 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use eyre::Result;
+use log::{error, trace, warn};
+
+use crate::api::connection::AccountConnection;
+use crate::api::heartbeat::Heartbeat;
+use crate::api::ws::{WebSocket, WsEvent};
+use crate::gates::okex::md::model::{EventType, OkexSubEvent};
+use crate::gates::okex::private::client::OkexPrivateClient;
+use crate::gates::okex::private::config::OkexPrivateConnectionConfig;
+use crate::gates::okex::private::model::{OkexPrivateDataMessage, OkexPrivateWsMessage};
+use crate::gates::okex::private::stream::{OkexPrivateArg, OkexPrivateStream};
+use crate::model::account::AccountEvent;
+use crate::model::exchange::Exchange;
+use crate::model::order_tracker::OrderTracker;
+
+/// Balances, positions and orders of the Okex account from the private websocket channels.
+///
+/// The connection logs in before subscribing, which `WebSocket` repeats with a fresh signature on every reconnect.
+/// `account` and `positions` push the current state right after subscribing, `orders` pushes the changes only,
+/// so every reconnect is reported with `AccountEvent::Reconnected`, after recovering the orders if configured.
+pub struct OkexPrivateConnection {
+    ws: WebSocket<OkexPrivateStream, OkexPrivateWsMessage>,
+    queue: VecDeque<AccountEvent>,
+    heartbeat: Heartbeat,
+    recovery: Option<OrderRecovery>,
+}
+
+struct OrderRecovery {
+    client: OkexPrivateClient,
+    tracker: Arc<RwLock<OrderTracker>>,
+}
+
+impl OkexPrivateConnection {
+    pub async fn new(config: OkexPrivateConnectionConfig) -> Result<Self> {
+        let stream = OkexPrivateStream { channels: config.channels, credentials: config.credentials };
+        let ws = WebSocket::connect_and_subscribe(
+            &config.ws_url,
+            stream,
+            config.subscribe_interval_ms,
+            config.reconnect.clone(),
+        ).await?;
+
+        Ok(Self {
+            ws,
+            queue: VecDeque::new(),
+            heartbeat: Heartbeat::new(
+                Duration::from_secs(config.idle_timeout_seconds),
+                Duration::from_secs(config.pong_timeout_seconds),
+            ),
+            recovery: None,
+        })
+    }
+
+    /// Recovers the orders of the tracker through REST after every reconnect,
+    /// before the events received on the new connection are returned
+    pub fn with_order_recovery(mut self, client: OkexPrivateClient, tracker: Arc<RwLock<OrderTracker>>) -> Self {
+        self.recovery = Some(OrderRecovery { client, tracker });
+        self
+    }
+
+    async fn on_reconnected(&mut self) {
+        if let Some(recovery) = &self.recovery {
+            if let Err(err) = recovery.client.recover_orders(&recovery.tracker).await {
+                error!("Failed to recover the orders after reconnecting to Okex private stream, {err}");
+            }
+        }
+        self.queue.push_back(AccountEvent::Reconnected(Exchange::Okex));
+    }
+
+    fn on_sub_event(&self, sub: OkexSubEvent<OkexPrivateArg>) {
+        match sub.event {
+            EventType::Error => warn!("received error event from Okex private stream {sub:?}"),
+            _ => trace!("Okex private stream event {sub:?}"),
+        }
+    }
+
+    /// Malformed items are skipped, the rest of the message is still processed
+    fn on_data(&mut self, message: OkexPrivateDataMessage) {
+        let channel = message.arg.channel.clone();
+        let items = match message.events() {
+            Ok(items) => items,
+            Err(err) => {
+                warn!("skipping Okex private message, {err}");
+                return;
+            }
+        };
+        for item in items {
+            match item {
+                Ok(events) => self.queue.extend(events),
+                Err(err) => warn!("skipping Okex {channel} item, {err}"),
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl AccountConnection for OkexPrivateConnection {
+    /// The private channels may be silent for a long time, so the connection is kept alive with the text `ping`
+    /// like the public one.
+    ///
+    /// https://www.okx.com/docs-v5/en/#overview-websocket-connect
+    async fn next(&mut self) -> Result<AccountEvent> {
+        while self.queue.is_empty() {
+            match self.ws.next_event(&mut self.heartbeat).await? {
+                WsEvent::Message(message) => match message {
+                    OkexPrivateWsMessage::Data(message) => self.on_data(message),
+                    OkexPrivateWsMessage::SubEvent(sub) => self.on_sub_event(sub),
+                    OkexPrivateWsMessage::OpResponse(response) => {
+                        warn!("unexpected op response on Okex private stream {response:?}");
+                    }
+                    OkexPrivateWsMessage::Pong => {}
+                },
+                WsEvent::Ping => {
+                    if let Err(err) = self.ws.ping_text("ping").await {
+                        error!("Failed to send ping to Okex private stream, {err}");
+                    }
+                }
+                WsEvent::Reconnected => self.on_reconnected().await,
+            }
+        }
+        let event = self.queue.pop_front().expect("should be some");
+        Ok(event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use std::sync::{Arc, RwLock};
+
+    use futures_util::{SinkExt, StreamExt};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+    use tokio_tungstenite::tungstenite::Message;
+
+    use crate::api::backoff::BackoffConfig;
+    use crate::api::connection::AccountConnection;
+    use crate::gates::okex::common::auth::OkexCredentials;
+    use crate::gates::okex::private::client::OkexPrivateClient;
+    use crate::gates::okex::private::config::{OkexPrivateClientConfig, OkexPrivateConnectionConfig};
+    use crate::gates::okex::private::connection::OkexPrivateConnection;
+    use crate::model::account::AccountEvent;
+    use crate::model::exchange::Exchange;
+    use crate::model::internal::Side;
+    use crate::model::order::{OrderRef, OrderRequest, TimeInForce};
+    use crate::model::order_tracker::{OrderState, OrderTracker};
+    use crate::model::symbol::Symbol;
+    use crate::utils::basic_types::{Amount, Price};
+
+    /// Acknowledges the logins and drops the first connection right after it
+    async fn serve_ws(listener: TcpListener) {
+        for connection in 0.. {
+            let (tcp, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(tcp).await.unwrap();
+            while let Some(Ok(Message::Text(_))) = ws.next().await {
+                let login = r#"{"event":"login","code":"0","msg":"","connId":"a4d3ae55"}"#;
+                ws.send(Message::text(login)).await.unwrap();
+                if connection == 0 {
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Answers every request with the pending orders
+    async fn serve_http(listener: TcpListener) {
+        let body = std::fs::read_to_string("tests/okex_orders_pending.json").unwrap();
+        loop {
+            let (mut tcp, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0; 1024];
+            while !request.ends_with(b"\r\n\r\n") {
+                let n = tcp.read(&mut buf).await.unwrap();
+                request.extend_from_slice(&buf[..n]);
+            }
+            let response = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                body.len(),
+            );
+            tcp.write_all(response.as_bytes()).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn orders_are_recovered_after_reconnect() {
+        let ws_listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let http_listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let credentials = OkexCredentials {
+            api_key: "key".into(),
+            secret_key: "secret".into(),
+            passphrase: "passphrase".into(),
+        };
+        let config = OkexPrivateConnectionConfig {
+            ws_url: format!("ws://{}", ws_listener.local_addr().unwrap()).into(),
+            channels: Vec::new(),
+            reconnect: BackoffConfig { initial_delay_ms: 10, ..Default::default() },
+            ..OkexPrivateConnectionConfig::new(credentials.clone())
+        };
+        let client = OkexPrivateClient::new(OkexPrivateClientConfig {
+            http_url: format!("http://{}", http_listener.local_addr().unwrap()).into(),
+            ..OkexPrivateClientConfig::new(credentials)
+        });
+        tokio::spawn(serve_ws(ws_listener));
+        tokio::spawn(serve_http(http_listener));
+
+        // the order was sent, but its acknowledgement was lost with the connection
+        let tracker = Arc::new(RwLock::new(OrderTracker::new(Exchange::Okex, "t")));
+        let order = OrderRequest::limit(
+            Symbol::spot("BTC", "USDT"),
+            Side::Bid,
+            Price::from_str("30100").unwrap(),
+            Amount::from_str("0.001").unwrap(),
+            TimeInForce::Gtc,
+        ).with_client_order_id("b1");
+        tracker.write().unwrap().on_new(order);
+        tracker.write().unwrap().on_sent("b1");
+
+        let mut connection = OkexPrivateConnection::new(config)
+            .await
+            .unwrap()
+            .with_order_recovery(client, tracker.clone());
+        assert_eq!(connection.next().await.unwrap(), AccountEvent::Reconnected(Exchange::Okex));
+
+        let tracker = tracker.read().unwrap();
+        let order = tracker.get(&OrderRef::ClientOrderId("b1".into())).unwrap();
+        assert_eq!(order.state, OrderState::PartiallyFilled);
+        assert_eq!(order.order_id.as_deref(), Some("301835739059335168"));
+    }
+}
+
+#[cfg(test)]
+mod private_integration_tests {
+    use crate::api::connection::AccountConnection;
+    use crate::gates::okex::common::auth::OkexCredentials;
+    use crate::gates::okex::private::config::OkexPrivateConnectionConfig;
+    use crate::gates::okex::private::connection::OkexPrivateConnection;
+    use crate::model::account::AccountEvent;
+    use crate::model::exchange::Exchange;
+
+    /// Requires `OKX_API_KEY`, `OKX_SECRET_KEY` and `OKX_PASSPHRASE` of an account with a position or a balance
+    #[ignore]
+    #[tokio::test]
+    async fn account_test() {
+        let credentials = OkexCredentials {
+            api_key: std::env::var("OKX_API_KEY").unwrap().into(),
+            secret_key: std::env::var("OKX_SECRET_KEY").unwrap().into(),
+            passphrase: std::env::var("OKX_PASSPHRASE").unwrap().into(),
+        };
+        let mut connection = OkexPrivateConnection::new(OkexPrivateConnectionConfig::new(credentials)).await.unwrap();
+
+        let mut state_events = 0;
+        for _ in 0..10 {
+            match connection.next().await.unwrap() {
+                AccountEvent::Balance(balance) => {
+                    assert!(!balance.currency.is_empty());
+                    state_events += 1;
+                }
+                AccountEvent::Position(position) => {
+                    assert_eq!(position.exchange, Exchange::Okex);
+                    state_events += 1;
+                }
+                AccountEvent::Order(order) => assert!(!order.order_id.is_empty()),
+                AccountEvent::Reconnected(_) => {}
+            }
+        }
+        // `account` and `positions` push the current state right after subscribing
+        assert!(state_events > 0);
+    }
+}
@@ -0,0 +1,119 @@
+use http::Method;
+
+use crate::api::endpoint::Endpoint;
+use crate::gates::okex::common::account::{OkexAccount, OkexOrder, OkexOrderAck, OkexPosition};
+use crate::gates::okex::common::response::OkexResponse;
+use crate::gates::okex::private::request::{
+    AmendOrderRequest,
+    CancelOrderRequest,
+    GetBalanceRequest,
+    GetOrderHistoryRequest,
+    GetPendingOrdersRequest,
+    GetPositionsRequest,
+    PlaceOrderRequest,
+};
+
+pub struct GetBalance;
+
+impl Endpoint for GetBalance {
+    type Request = GetBalanceRequest;
+    type Response = OkexResponse<OkexAccount>;
+
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "/api/v5/account/balance";
+}
+
+pub struct GetPositions;
+
+impl Endpoint for GetPositions {
+    type Request = GetPositionsRequest;
+    type Response = OkexResponse<OkexPosition>;
+
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "/api/v5/account/positions";
+}
+
+/// Incomplete orders, newest first
+pub struct GetPendingOrders;
+
+impl Endpoint for GetPendingOrders {
+    type Request = GetPendingOrdersRequest;
+    type Response = OkexResponse<OkexOrder>;
+
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "/api/v5/trade/orders-pending";
+}
+
+/// Completed orders of the last 7 days and the canceled ones of the last 2 hours, newest first
+pub struct GetOrderHistory;
+
+impl Endpoint for GetOrderHistory {
+    type Request = GetOrderHistoryRequest;
+    type Response = OkexResponse<OkexOrder>;
+
+    const METHOD: Method = Method::GET;
+    const PATH: &'static str = "/api/v5/trade/orders-history";
+}
+
+pub struct PlaceOrder;
+
+impl Endpoint for PlaceOrder {
+    type Request = PlaceOrderRequest;
+    type Response = OkexResponse<OkexOrderAck>;
+
+    const METHOD: Method = Method::POST;
+    const PATH: &'static str = "/api/v5/trade/order";
+}
+
+/// Up to 20 orders
+pub struct PlaceOrders;
+
+impl Endpoint for PlaceOrders {
+    type Request = Vec<PlaceOrderRequest>;
+    type Response = OkexResponse<OkexOrderAck>;
+
+    const METHOD: Method = Method::POST;
+    const PATH: &'static str = "/api/v5/trade/batch-orders";
+}
+
+pub struct CancelOrder;
+
+impl Endpoint for CancelOrder {
+    type Request = CancelOrderRequest;
+    type Response = OkexResponse<OkexOrderAck>;
+
+    const METHOD: Method = Method::POST;
+    const PATH: &'static str = "/api/v5/trade/cancel-order";
+}
+
+/// Up to 20 orders
+pub struct CancelOrders;
+
+impl Endpoint for CancelOrders {
+    type Request = Vec<CancelOrderRequest>;
+    type Response = OkexResponse<OkexOrderAck>;
+
+    const METHOD: Method = Method::POST;
+    const PATH: &'static str = "/api/v5/trade/cancel-batch-orders";
+}
+
+pub struct AmendOrder;
+
+impl Endpoint for AmendOrder {
+    type Request = AmendOrderRequest;
+    type Response = OkexResponse<OkexOrderAck>;
+
+    const METHOD: Method = Method::POST;
+    const PATH: &'static str = "/api/v5/trade/amend-order";
+}
+
+/// Up to 20 orders
+pub struct AmendOrders;
+
+impl Endpoint for AmendOrders {
+    type Request = Vec<AmendOrderRequest>;
+    type Response = OkexResponse<OkexOrderAck>;
+
+    const METHOD: Method = Method::POST;
+    const PATH: &'static str = "/api/v5/trade/amend-batch-orders";
+}
@@ -0,0 +1,388 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use compact_str::ToCompactString;
+use eyre::{eyre, OptionExt, Result};
+use futures_util::future::join_all;
+use log::{error, trace, warn};
+use serde::Serialize;
+use tokio::sync::{mpsc, oneshot};
+use tokio::time::Instant;
+
+use crate::api::heartbeat::{Heartbeat, HeartbeatAction};
+use crate::api::trader::ExchangeTrader;
+use crate::api::ws::WebSocket;
+use crate::gates::okex::common::account::OkexOrderAck;
+use crate::gates::okex::md::model::EventType;
+use crate::gates::okex::private::batch::OrderBatch;
+use crate::gates::okex::private::client::OkexPrivateClient;
+use crate::gates::okex::private::config::OkexOrderGatewayConfig;
+use crate::gates::okex::private::model::{OkexOpResponse, OkexPrivateWsMessage};
+use crate::gates::okex::private::request::{
+    AmendOrderRequest,
+    CancelOrderRequest,
+    OkexOpRequest,
+    OkexOrderOp,
+    PlaceOrderRequest,
+};
+use crate::gates::okex::private::stream::OkexPrivateStream;
+use crate::model::order::{AmendRequest, CancelRequest, OrderAck, OrderRequest};
+use crate::model::symbol::Symbol;
+
+/// Order entry over the private websocket, saving the round trip of the REST endpoints.
+///
+/// The connection is owned by a background task, so that many requests can be in flight at once:
+/// every call sends the op with a new `id` and resolves when the response with the same `id` arrives.
+/// Requests fail if the response doesn't arrive within `request_timeout_ms` of the call
+/// or the connection is lost before that, in which case the outcome of the request is unknown
+/// and should be checked with the `orders` channel or the REST endpoints.
+/// Requests made while the connection is being restored are rejected without being sent.
+pub struct OkexOrderGateway {
+    commands: mpsc::UnboundedSender<Command>,
+    client: OkexPrivateClient,
+    request_timeout: Duration,
+}
+
+struct Command {
+    op: OkexOrderOp,
+    args: Vec<serde_json::Value>,
+    /// Stamped when the request is made, so that the time spent in the queue counts too
+    deadline: Instant,
+    respond: oneshot::Sender<Result<OkexOpResponse>>,
+}
+
+struct Pending {
+    respond: oneshot::Sender<Result<OkexOpResponse>>,
+    deadline: Instant,
+}
+
+impl OkexOrderGateway {
+    /// Connects and logs in, the connection is closed when the gateway is dropped
+    pub async fn new(config: OkexOrderGatewayConfig) -> Result<Self> {
+        let stream = OkexPrivateStream { channels: Vec::new(), credentials: config.client.credentials.clone() };
+        let ws = WebSocket::connect_and_subscribe(&config.ws_url, stream, 0, config.reconnect.clone()).await?;
+
+        let (commands, receiver) = mpsc::unbounded_channel();
+        let request_timeout = Duration::from_millis(config.request_timeout_ms);
+        let task = GatewayTask {
+            ws,
+            commands: receiver,
+            pending: HashMap::new(),
+            request_timeout,
+            heartbeat: Heartbeat::new(
+                Duration::from_secs(config.idle_timeout_seconds),
+                Duration::from_secs(config.pong_timeout_seconds),
+            ),
+        };
+        tokio::spawn(task.run());
+
+        Ok(Self {
+            commands,
+            client: OkexPrivateClient::new(config.client),
+            request_timeout,
+        })
+    }
+
+    /// Sends the op and waits for its response
+    async fn request<A: Serialize>(&self, op: OkexOrderOp, args: &[A]) -> Result<Vec<OkexOrderAck>> {
+        let args = args.iter().map(serde_json::to_value).collect::<serde_json::Result<_>>()?;
+        let (respond, response) = oneshot::channel();
+        let deadline = Instant::now() + self.request_timeout;
+        self.commands
+            .send(Command { op, args, deadline, respond })
+            .map_err(|_| eyre!("Okex order gateway is stopped"))?;
+        let response = tokio::time::timeout_at(deadline, response)
+            .await
+            .map_err(|_| eyre!("{op:?} request to Okex timed out after {:?}", self.request_timeout))?
+            .map_err(|_| eyre!("Okex order gateway stopped before the response to {op:?}"))??;
+
+        Ok(response.into_result()?)
+    }
+
+    async fn single<A: Serialize>(&self, op: OkexOrderOp, request: A) -> Result<OrderAck> {
+        self.request(op, &[request])
+            .await?
+            .first()
+            .ok_or_eyre("There was no order returned from Okex")?
+            .to_internal()
+    }
+
+    /// Sends the batches of up to `BATCH_LIMIT` items concurrently,
+    /// a failed batch doesn't affect the results of the others
+    async fn batch<A: Serialize + Clone>(&self, op: OkexOrderOp, requests: Vec<Result<A>>) -> Vec<Result<OrderAck>> {
+        let batch = OrderBatch::new(requests);
+        let chunks = batch.chunks();
+        let responses = join_all(chunks.iter().map(|chunk| self.request(op, chunk))).await;
+        batch.into_results(responses)
+    }
+}
+
+#[async_trait]
+impl ExchangeTrader for OkexOrderGateway {
+    async fn place_order(&self, order: &OrderRequest) -> Result<OrderAck> {
+        let request = PlaceOrderRequest::new(order, self.client.config.margin_mode)?;
+        self.single(OkexOrderOp::Order, request).await
+    }
+
+    async fn place_orders(&self, orders: &[OrderRequest]) -> Result<Vec<Result<OrderAck>>> {
+        let requests = orders.iter().map(|o| PlaceOrderRequest::new(o, self.client.config.margin_mode)).collect();
+        Ok(self.batch(OkexOrderOp::BatchOrders, requests).await)
+    }
+
+    async fn cancel_order(&self, cancel: &CancelRequest) -> Result<OrderAck> {
+        self.single(OkexOrderOp::CancelOrder, CancelOrderRequest::new(cancel)?).await
+    }
+
+    async fn cancel_orders(&self, cancels: &[CancelRequest]) -> Result<Vec<Result<OrderAck>>> {
+        let requests = cancels.iter().map(CancelOrderRequest::new).collect();
+        Ok(self.batch(OkexOrderOp::BatchCancelOrders, requests).await)
+    }
+
+    async fn amend_order(&self, amend: &AmendRequest) -> Result<OrderAck> {
+        self.single(OkexOrderOp::AmendOrder, AmendOrderRequest::new(amend)?).await
+    }
+
+    async fn amend_orders(&self, amends: &[AmendRequest]) -> Result<Vec<Result<OrderAck>>> {
+        let requests = amends.iter().map(AmendOrderRequest::new).collect();
+        Ok(self.batch(OkexOrderOp::BatchAmendOrders, requests).await)
+    }
+
+    /// The pending orders are queried through REST and canceled through the websocket
+    async fn cancel_all(&self, symbol: Option<&Symbol>) -> Result<Vec<Result<OrderAck>>> {
+        self.cancel_orders(&self.client.pending_cancels(symbol).await?).await
+    }
+}
+
+struct GatewayTask {
+    ws: WebSocket<OkexPrivateStream, OkexPrivateWsMessage>,
+    commands: mpsc::UnboundedReceiver<Command>,
+    pending: HashMap<u64, Pending>,
+    request_timeout: Duration,
+    heartbeat: Heartbeat,
+}
+
+impl GatewayTask {
+    async fn run(mut self) {
+        if let Err(err) = self.process().await {
+            error!("Okex order gateway to {} stopped, {err}", self.ws.ws_url);
+            self.fail_pending(&err.to_string());
+        }
+    }
+
+    /// Returns `Ok` when the gateway is dropped and fails when the connection can't be restored
+    async fn process(&mut self) -> Result<()> {
+        loop {
+            let deadline = self.pending
+                .values()
+                .map(|p| p.deadline)
+                .fold(self.heartbeat.deadline(), Instant::min);
+            tokio::select! {
+                command = self.commands.recv() => {
+                    let Some(command) = command else {
+                        return Ok(());
+                    };
+                    self.on_command(command).await;
+                }
+                res = self.ws.try_next() => {
+                    match res {
+                        Ok(Some(message)) => {
+                            self.heartbeat.on_message();
+                            self.on_message(message);
+                        }
+                        Ok(None) => self.reconnect("connection closed").await?,
+                        Err(err) => {
+                            warn!("Okex order gateway failed to receive from {}, {err}", self.ws.ws_url);
+                            self.reconnect(&err.to_string()).await?;
+                        }
+                    }
+                }
+                _ = tokio::time::sleep_until(deadline) => {
+                    self.expire_pending();
+                    if self.heartbeat.deadline() <= Instant::now() {
+                        self.on_heartbeat_deadline().await?;
+                    }
+                }
+            }
+        }
+    }
+
+    async fn on_command(&mut self, command: Command) {
+        if command.deadline <= Instant::now() {
+            let _ = command.respond.send(Err(eyre!("{:?} request to Okex timed out in the queue", command.op)));
+            return;
+        }
+        let id = self.ws.next_request_id();
+        let request = OkexOpRequest { id: id.to_compact_string(), op: command.op, args: command.args };
+        trace!("Sending {request:?} to Okex order gateway");
+        match self.ws.send(&request).await {
+            Ok(()) => {
+                self.pending.insert(id, Pending { respond: command.respond, deadline: command.deadline });
+            }
+            Err(err) => {
+                let _ = command.respond.send(Err(err.wrap_err(format!("failed to send {:?} request", command.op))));
+            }
+        }
+    }
+
+    fn on_message(&mut self, message: OkexPrivateWsMessage) {
+        match message {
+            OkexPrivateWsMessage::OpResponse(response) => {
+                let pending = response.id.parse().ok().and_then(|id| self.pending.remove(&id));
+                match pending {
+                    // the receiver may be gone if the caller stopped waiting
+                    Some(pending) => { let _ = pending.respond.send(Ok(response)); }
+                    None => warn!("Okex order gateway received the response to an unknown request {response:?}"),
+                }
+            }
+            OkexPrivateWsMessage::SubEvent(event) if matches!(event.event, EventType::Error) => {
+                warn!("received error event from Okex order gateway {event:?}");
+            }
+            message => trace!("Okex order gateway message {message:?}"),
+        }
+    }
+
+    async fn on_heartbeat_deadline(&mut self) -> Result<()> {
+        match self.heartbeat.on_deadline() {
+            HeartbeatAction::Reconnect => {
+                warn!(
+                    "No pong from Okex within {:?}, reconnecting to {}",
+                    self.heartbeat.pong_timeout(),
+                    self.ws.ws_url,
+                );
+                self.reconnect("no pong").await?;
+            }
+            HeartbeatAction::Ping => {
+                if let Err(err) = self.ws.ping_text("ping").await {
+                    error!("Failed to send ping to Okex order gateway, {err}");
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The responses to the requests in flight are lost with the connection,
+    /// the requests queued up meanwhile are rejected rather than placed late
+    async fn reconnect(&mut self, reason: &str) -> Result<()> {
+        self.fail_pending(reason);
+        self.ws.reconnect().await?;
+        self.heartbeat.on_message();
+        while let Ok(command) = self.commands.try_recv() {
+            let _ = command.respond.send(Err(eyre!("{:?} request arrived while reconnecting to Okex", command.op)));
+        }
+        Ok(())
+    }
+
+    fn expire_pending(&mut self) {
+        let now = Instant::now();
+        let expired: Vec<_> = self.pending
+            .iter()
+            .filter(|(_, p)| p.deadline <= now)
+            .map(|(id, _)| *id)
+            .collect();
+        for id in expired {
+            if let Some(pending) = self.pending.remove(&id) {
+                let _ = pending.respond.send(Err(eyre!("request {id} to Okex timed out after {:?}", self.request_timeout)));
+            }
+        }
+    }
+
+    fn fail_pending(&mut self, reason: &str) {
+        for (id, pending) in self.pending.drain() {
+            let _ = pending.respond.send(Err(eyre!("request {id} to Okex failed, {reason}")));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use std::time::Duration;
+
+    use futures_util::{SinkExt, StreamExt};
+    use tokio::net::TcpListener;
+    use tokio_tungstenite::tungstenite::Message;
+
+    use crate::api::backoff::BackoffConfig;
+    use crate::api::trader::ExchangeTrader;
+    use crate::gates::okex::common::auth::OkexCredentials;
+    use crate::gates::okex::private::config::OkexOrderGatewayConfig;
+    use crate::gates::okex::private::gateway::OkexOrderGateway;
+    use crate::model::internal::Side;
+    use crate::model::order::{AmendRequest, CancelRequest, OrderRef, OrderRequest, TimeInForce};
+    use crate::model::symbol::Symbol;
+    use crate::utils::basic_types::{Amount, Price};
+
+    /// Acknowledges logins and orders, ignores cancels and drops the connection on amends,
+    /// taking a while to accept the next connection
+    async fn serve(listener: TcpListener) {
+        loop {
+            let (tcp, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(tcp).await.unwrap();
+            while let Some(Ok(Message::Text(request))) = ws.next().await {
+                let request: serde_json::Value = serde_json::from_str(&request).unwrap();
+                let response = match request["op"].as_str().unwrap() {
+                    "login" => r#"{"event":"login","code":"0","msg":"","connId":"a4d3ae55"}"#.to_string(),
+                    "order" => format!(
+                        r#"{{"id":{},"op":"order","code":"0","msg":"","data":[{{"ordId":"1","clOrdId":{},"sCode":"0","sMsg":""}}]}}"#,
+                        request["id"],
+                        request["args"][0]["clOrdId"],
+                    ),
+                    "amend-order" => break,
+                    _ => continue,
+                };
+                ws.send(Message::text(response)).await.unwrap();
+            }
+            drop(ws);
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn requests_are_resolved_by_id() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let config = OkexOrderGatewayConfig {
+            ws_url: format!("ws://{}", listener.local_addr().unwrap()).into(),
+            request_timeout_ms: 200,
+            reconnect: BackoffConfig { initial_delay_ms: 10, ..Default::default() },
+            ..OkexOrderGatewayConfig::new(OkexCredentials {
+                api_key: "key".into(),
+                secret_key: "secret".into(),
+                passphrase: "passphrase".into(),
+            })
+        };
+        tokio::spawn(serve(listener));
+        let gateway = OkexOrderGateway::new(config).await.unwrap();
+
+        let symbol = Symbol::spot("BTC", "USDT");
+        let order = |id: &str| OrderRequest::limit(
+            symbol.clone(),
+            Side::Bid,
+            Price::from_str("1000").unwrap(),
+            Amount::from_str("1").unwrap(),
+            TimeInForce::Gtc,
+        ).with_client_order_id(id);
+        let (a1, a2) = (order("a1"), order("a2"));
+        let (first, second) = tokio::join!(gateway.place_order(&a1), gateway.place_order(&a2));
+        assert_eq!(first.unwrap().client_order_id.as_deref(), Some("a1"));
+        assert_eq!(second.unwrap().client_order_id.as_deref(), Some("a2"));
+
+        let cancel = CancelRequest { symbol: symbol.clone(), order: OrderRef::OrderId("1".into()) };
+        let err = gateway.cancel_order(&cancel).await.unwrap_err();
+        assert!(err.to_string().contains("timed out"), "{err}");
+
+        let amend = AmendRequest {
+            symbol: symbol.clone(),
+            order: OrderRef::OrderId("1".into()),
+            new_price: Some(Price::from_str("1001").unwrap()),
+            new_amount: None,
+        };
+        let err = gateway.amend_order(&amend).await.unwrap_err();
+        assert!(err.to_string().contains("connection closed"), "{err}");
+        let err = gateway.place_order(&order("late")).await.unwrap_err();
+        assert!(err.to_string().contains("while reconnecting"), "{err}");
+
+        // reconnected and logged in again
+        assert!(gateway.place_order(&order("a3")).await.is_ok());
+    }
+}
@@ -0,0 +1,9 @@
+pub mod batch;
+pub mod client;
+pub mod config;
+pub mod connection;
+pub mod endpoints;
+pub mod gateway;
+pub mod model;
+pub mod request;
+pub mod stream;
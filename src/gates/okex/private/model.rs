@@ -0,0 +1,296 @@
+use compact_str::CompactString;
+use eyre::eyre;
+use fixnum::ops::Zero;
+use serde::Deserialize;
+
+use crate::api::connection::WsMessage;
+use crate::gates::okex::common::account::{OkexAccount, OkexOrder, OkexOrderAck, OkexPosition, OkexPositionSide};
+use crate::gates::okex::common::error::OkexErrorResponse;
+use crate::gates::okex::common::response::batch_result;
+use crate::gates::okex::common::symbol::OkexSymbols;
+use crate::gates::okex::md::model::{EventType, OkexSubEvent};
+use crate::gates::okex::private::stream::{OkexPrivateArg, OkexPrivateChannel};
+use crate::model::account::{AccountEvent, Balance, Position};
+use crate::model::exchange::Exchange;
+use crate::model::symbol::SymbolMapping;
+use crate::utils::basic_types::{Amount, deserialize_optional_decimal, deserialize_u64, Price};
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum OkexPrivateWsMessage {
+    SubEvent(OkexSubEvent<OkexPrivateArg>),
+    Data(OkexPrivateDataMessage),
+    OpResponse(OkexOpResponse),
+    Pong,
+}
+
+impl WsMessage for OkexPrivateWsMessage {
+    const PONG_TEXT: Option<&'static str> = Some("pong");
+
+    fn pong() -> Self {
+        Self::Pong
+    }
+
+    fn login_result(&self) -> Option<eyre::Result<()>> {
+        let OkexPrivateWsMessage::SubEvent(event) = self else {
+            return None;
+        };
+        match event.event {
+            EventType::Login => Some(Ok(())),
+            // login failures are reported as plain error events
+            EventType::Error => Some(Err(eyre::eyre!("Okex login failed: {event:?}"))),
+            _ => None,
+        }
+    }
+
+    fn response_id(&self) -> Option<u64> {
+        match self {
+            OkexPrivateWsMessage::OpResponse(response) => response.id.parse().ok(),
+            _ => None,
+        }
+    }
+}
+
+/// Response to the `order`, `cancel-order`, `amend-order` ops and their batch variants,
+/// carrying the `id` of the request
+#[derive(Debug, Deserialize)]
+pub struct OkexOpResponse {
+    pub id: CompactString,
+    pub op: CompactString,
+    pub code: CompactString,
+    pub msg: CompactString,
+    #[serde(default)]
+    pub data: Vec<OkexOrderAck>,
+}
+
+impl OkexOpResponse {
+    /// Codes `1` and `2` report the failures in the data items, see `batch_result`
+    pub fn into_result(self) -> Result<Vec<OkexOrderAck>, OkexErrorResponse> {
+        batch_result(self.code, self.msg, self.data)
+    }
+}
+
+/// Items are kept raw until the channel of `arg` tells their type, so that a malformed item doesn't fail the others
+#[derive(Debug, Deserialize)]
+pub struct OkexPrivateDataMessage {
+    pub arg: OkexPrivateArg,
+    pub data: Vec<serde_json::Value>,
+}
+
+impl OkexPrivateDataMessage {
+    /// Events of every item in the order of the items
+    pub fn events(self) -> eyre::Result<Vec<eyre::Result<Vec<AccountEvent>>>> {
+        let channel = OkexPrivateChannel::from_name(&self.arg.channel)
+            .ok_or_else(|| eyre!("unknown Okex private channel {}", self.arg.channel))?;
+        Ok(self.data.into_iter().map(|item| item_events(channel, item)).collect())
+    }
+}
+
+fn item_events(channel: OkexPrivateChannel, item: serde_json::Value) -> eyre::Result<Vec<AccountEvent>> {
+    let events = match channel {
+        OkexPrivateChannel::Orders => {
+            let order: OkexOrder = serde_json::from_value(item)?;
+            vec![AccountEvent::Order(order.to_internal()?)]
+        }
+        OkexPrivateChannel::Positions => {
+            let position: OkexPosition = serde_json::from_value(item)?;
+            vec![AccountEvent::Position(position.to_internal()?)]
+        }
+        OkexPrivateChannel::Account => {
+            let account: OkexAccount = serde_json::from_value(item)?;
+            account.details.iter().map(|d| AccountEvent::Balance(d.to_internal())).collect()
+        }
+        OkexPrivateChannel::BalanceAndPosition => {
+            let update: OkexBalanceAndPosition = serde_json::from_value(item)?;
+            let positions = update.positions()?;
+            update.balances()
+                .map(AccountEvent::Balance)
+                .chain(positions.into_iter().map(AccountEvent::Position))
+                .collect()
+        }
+    };
+    Ok(events)
+}
+
+/// Message of `balance_and_position` channel, only the cash balances and positions changed by the event
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OkexBalanceAndPosition {
+    #[serde(deserialize_with = "deserialize_u64")]
+    pub p_time: u64,
+    pub bal_data: Vec<OkexCashBalance>,
+    pub pos_data: Vec<OkexPositionData>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OkexCashBalance {
+    pub ccy: CompactString,
+    pub cash_bal: Amount,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OkexPositionData {
+    pub inst_id: CompactString,
+    pub pos_side: OkexPositionSide,
+    #[serde(deserialize_with = "deserialize_optional_decimal")]
+    pub pos: Option<Amount>,
+    #[serde(deserialize_with = "deserialize_optional_decimal")]
+    pub avg_px: Option<Price>,
+}
+
+impl OkexBalanceAndPosition {
+    pub fn balances(&self) -> impl Iterator<Item = Balance> + '_ {
+        self.bal_data.iter().map(|b| Balance {
+            exchange_time: Some(self.p_time),
+            exchange: Exchange::Okex,
+            currency: b.ccy.clone(),
+            total: b.cash_bal,
+            available: None,
+            frozen: None,
+        })
+    }
+
+    pub fn positions(&self) -> eyre::Result<Vec<Position>> {
+        self.pos_data
+            .iter()
+            .map(|p| Ok(Position {
+                exchange_time: Some(self.p_time),
+                exchange: Exchange::Okex,
+                symbol: OkexSymbols::from_native(&p.inst_id)?,
+                instrument_id: None,
+                side: p.pos_side.into(),
+                amount: p.pos.unwrap_or(Amount::ZERO),
+                avg_price: p.avg_px,
+                unrealized_pnl: None,
+            }))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::str::FromStr;
+
+    use crate::api::connection::WsMessage;
+    use crate::gates::okex::private::model::OkexPrivateWsMessage;
+    use crate::model::account::{AccountEvent, PositionSide};
+    use crate::model::internal::Side;
+    use crate::model::order::{OrderStatus, OrderType, TimeInForce};
+    use crate::model::symbol::Symbol;
+    use crate::utils::basic_types::{Amount, Price};
+
+    fn parse_events(fixture: &str) -> Vec<AccountEvent> {
+        let message_str = fs::read_to_string(fixture).unwrap();
+        let message: OkexPrivateWsMessage = serde_json::from_str(&message_str).unwrap();
+        let OkexPrivateWsMessage::Data(message) = message else {
+            panic!("expected Data in {fixture}, got {message:?}");
+        };
+        message.events().unwrap().into_iter().flat_map(Result::unwrap).collect()
+    }
+
+    #[test]
+    fn order_parsing() {
+        let events = parse_events("tests/okex_ws_orders.json");
+        let [AccountEvent::Order(update)] = events.as_slice() else {
+            panic!("expected single order, got {events:?}");
+        };
+        assert_eq!(update.symbol, Symbol::spot("BTC", "USDT"));
+        assert_eq!(update.order_id, "452197707845865472");
+        assert_eq!(update.client_order_id, None);
+        assert_eq!(update.side, Side::Ask);
+        assert_eq!((update.order_type, update.time_in_force), (OrderType::Limit, TimeInForce::Gtc));
+        assert_eq!(update.status, OrderStatus::Filled);
+        assert_eq!(update.filled_amount, Amount::from_str("0.001").unwrap());
+        let fill = update.fill.as_ref().unwrap();
+        assert_eq!(fill.trade_id, "242589207");
+        assert_eq!(fill.price, Price::from_str("31527.1").unwrap());
+        assert_eq!(fill.fee, Amount::from_str("0.02522168").unwrap());
+    }
+
+    #[test]
+    fn position_parsing() {
+        let events = parse_events("tests/okex_ws_positions.json");
+        let [AccountEvent::Position(position)] = events.as_slice() else {
+            panic!("expected single position, got {events:?}");
+        };
+        assert_eq!(position.symbol, Symbol::perpetual("BTC", "USDT"));
+        assert_eq!(position.side, PositionSide::Net);
+        assert_eq!(position.amount, Amount::from_str("-2").unwrap());
+        assert_eq!(position.unrealized_pnl, Some(Amount::from_str("0.64").unwrap()));
+    }
+
+    #[test]
+    fn account_parsing() {
+        let events = parse_events("tests/okex_ws_account.json");
+        let [AccountEvent::Balance(usdt), AccountEvent::Balance(_)] = events.as_slice() else {
+            panic!("expected two balances, got {events:?}");
+        };
+        assert_eq!(usdt.currency, "USDT");
+        assert_eq!(usdt.total, Amount::from_str("1001.5").unwrap());
+        assert_eq!(usdt.available, Some(Amount::from_str("990.25").unwrap()));
+    }
+
+    #[test]
+    fn balance_and_position_parsing() {
+        let events = parse_events("tests/okex_ws_balance_and_position.json");
+        let [AccountEvent::Balance(usdt), AccountEvent::Position(position)] = events.as_slice() else {
+            panic!("expected a balance and a position, got {events:?}");
+        };
+        assert_eq!(usdt.currency, "USDT");
+        assert_eq!(usdt.total, Amount::from_str("1000.5").unwrap());
+        assert_eq!(position.symbol, Symbol::perpetual("BTC", "USDT"));
+        assert_eq!(position.amount, Amount::from_str("-2").unwrap());
+    }
+
+    #[test]
+    fn malformed_item_is_reported_separately() {
+        let message: OkexPrivateWsMessage = serde_json::from_str(r#"{
+            "arg": {"channel": "account"},
+            "data": [{"details": "unexpected"}, {"details": []}]
+        }"#).unwrap();
+        let OkexPrivateWsMessage::Data(message) = message else {
+            panic!("expected Data, got {message:?}");
+        };
+        let events = message.events().unwrap();
+        assert!(events[0].is_err());
+        assert!(events[1].as_ref().unwrap().is_empty());
+    }
+
+    #[test]
+    fn op_response_parsing() {
+        let message: OkexPrivateWsMessage = serde_json::from_str(r#"{
+            "id": "1512",
+            "op": "order",
+            "data": [{"clOrdId": "", "ordId": "12345689", "tag": "", "sCode": "0", "sMsg": ""}],
+            "code": "0",
+            "msg": "",
+            "inTime": "1695190491421339",
+            "outTime": "1695190491423240"
+        }"#).unwrap();
+        assert_eq!(message.response_id(), Some(1512));
+        let OkexPrivateWsMessage::OpResponse(response) = message else {
+            panic!("expected op response");
+        };
+        assert_eq!(response.into_result().unwrap()[0].ord_id, "12345689");
+
+        let message: OkexPrivateWsMessage =
+            serde_json::from_str(r#"{"id":"7","op":"order","code":"60013","msg":"Invalid args","data":[]}"#).unwrap();
+        let OkexPrivateWsMessage::OpResponse(response) = message else {
+            panic!("expected op response");
+        };
+        assert_eq!(response.into_result().unwrap_err().to_string(), "60013:Invalid args");
+    }
+
+    #[test]
+    fn login_event_parsing() {
+        let message: OkexPrivateWsMessage =
+            serde_json::from_str(r#"{"event":"login","code":"0","msg":"","connId":"a4d3ae55"}"#).unwrap();
+        assert!(matches!(message.login_result(), Some(Ok(()))));
+        let message: OkexPrivateWsMessage =
+            serde_json::from_str(r#"{"event":"error","code":"60009","msg":"Login failed.","connId":"a4d3ae55"}"#).unwrap();
+        assert!(matches!(message.login_result(), Some(Err(_))));
+    }
+}
@@ -0,0 +1,278 @@
+use compact_str::CompactString;
+use eyre::OptionExt;
+use serde::Serialize;
+
+use crate::gates::okex::common::account::{OkexOrderSide, OkexOrderType, OkexTradeMode};
+use crate::gates::okex::common::model::OkexInstType;
+use crate::gates::okex::common::symbol::OkexSymbols;
+use crate::model::order::{AmendRequest, CancelRequest, OrderRef, OrderRequest, OrderType};
+use crate::model::symbol::{SymbolKind, SymbolMapping};
+use crate::utils::basic_types::{Amount, Price};
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GetBalanceRequest {
+    /// Comma separated currencies, e.g. BTC,ETH, up to 20. All the non-zero balances if not set
+    ccy: Option<CompactString>,
+}
+
+impl GetBalanceRequest {
+    pub fn new(currencies: &[CompactString]) -> Self {
+        let ccy = currencies.join(",");
+        Self {
+            ccy: (!ccy.is_empty()).then(|| ccy.into()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GetPositionsRequest {
+    /// MARGIN, SWAP, FUTURES or OPTION
+    inst_type: Option<OkexInstType>,
+    inst_id: Option<CompactString>,
+}
+
+impl GetPositionsRequest {
+    pub fn new(inst_type: Option<OkexInstType>, symbol: Option<CompactString>) -> Self {
+        Self {
+            inst_type,
+            inst_id: symbol,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GetPendingOrdersRequest {
+    inst_type: Option<OkexInstType>,
+    inst_id: Option<CompactString>,
+    /// Return records earlier than the requested order id, for pagination
+    after: Option<CompactString>,
+    /// Number of results per request, 100 at most
+    limit: Option<u16>,
+}
+
+impl GetPendingOrdersRequest {
+    pub fn new(
+        inst_type: Option<OkexInstType>,
+        symbol: Option<CompactString>,
+        after: Option<CompactString>,
+        limit: Option<u16>,
+    ) -> Self {
+        Self {
+            inst_type,
+            inst_id: symbol,
+            after,
+            limit,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GetOrderHistoryRequest {
+    inst_type: OkexInstType,
+    inst_id: Option<CompactString>,
+    /// Return records earlier than the requested order id, for pagination
+    after: Option<CompactString>,
+    /// Number of results per request, 100 at most
+    limit: Option<u16>,
+}
+
+impl GetOrderHistoryRequest {
+    pub fn new(
+        inst_type: OkexInstType,
+        symbol: Option<CompactString>,
+        after: Option<CompactString>,
+        limit: Option<u16>,
+    ) -> Self {
+        Self {
+            inst_type,
+            inst_id: symbol,
+            after,
+            limit,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PlaceOrderRequest {
+    inst_id: CompactString,
+    td_mode: OkexTradeMode,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cl_ord_id: Option<CompactString>,
+    side: OkexOrderSide,
+    ord_type: OkexOrderType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    px: Option<Price>,
+    sz: Amount,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    reduce_only: bool,
+    /// Unit of `sz` of spot market orders, which is the quote currency for buy orders by default
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tgt_ccy: Option<&'static str>,
+}
+
+impl PlaceOrderRequest {
+    /// Spot orders are placed in `cash` mode and the other ones in `margin_mode`
+    pub fn new(order: &OrderRequest, margin_mode: OkexTradeMode) -> eyre::Result<Self> {
+        let spot = order.symbol.kind == SymbolKind::Spot;
+        let px = match order.order_type {
+            OrderType::Market => None,
+            OrderType::Limit => Some(order.price.ok_or_eyre("Limit order without a price")?),
+        };
+        Ok(Self {
+            inst_id: OkexSymbols::to_native(&order.symbol)?,
+            td_mode: if spot { OkexTradeMode::Cash } else { margin_mode },
+            cl_ord_id: order.client_order_id.clone(),
+            side: order.side.into(),
+            ord_type: OkexOrderType::from_internal(order.order_type, order.time_in_force),
+            px,
+            sz: order.amount,
+            reduce_only: order.reduce_only,
+            tgt_ccy: (spot && order.order_type == OrderType::Market).then_some("base_ccy"),
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelOrderRequest {
+    inst_id: CompactString,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ord_id: Option<CompactString>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cl_ord_id: Option<CompactString>,
+}
+
+impl CancelOrderRequest {
+    pub fn new(cancel: &CancelRequest) -> eyre::Result<Self> {
+        let (ord_id, cl_ord_id) = order_ids(&cancel.order);
+        Ok(Self {
+            inst_id: OkexSymbols::to_native(&cancel.symbol)?,
+            ord_id,
+            cl_ord_id,
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AmendOrderRequest {
+    inst_id: CompactString,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ord_id: Option<CompactString>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cl_ord_id: Option<CompactString>,
+    /// New quantity after amendment, including the filled one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    new_sz: Option<Amount>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    new_px: Option<Price>,
+}
+
+impl AmendOrderRequest {
+    pub fn new(amend: &AmendRequest) -> eyre::Result<Self> {
+        if amend.new_price.is_none() && amend.new_amount.is_none() {
+            return Err(eyre::eyre!("Amend request of {:?} changes neither price nor amount", amend.order));
+        }
+        let (ord_id, cl_ord_id) = order_ids(&amend.order);
+        Ok(Self {
+            inst_id: OkexSymbols::to_native(&amend.symbol)?,
+            ord_id,
+            cl_ord_id,
+            new_sz: amend.new_amount,
+            new_px: amend.new_price,
+        })
+    }
+}
+
+/// Trade operations of the private websocket
+///
+/// https://www.okx.com/docs-v5/en/#order-book-trading-trade-ws-place-order
+#[derive(Debug, Serialize, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum OkexOrderOp {
+    Order,
+    BatchOrders,
+    CancelOrder,
+    BatchCancelOrders,
+    AmendOrder,
+    BatchAmendOrders,
+}
+
+/// Trade operation request, the arguments are the same as the ones of the REST endpoints
+#[derive(Debug, Serialize, Clone)]
+pub struct OkexOpRequest<A: Serialize> {
+    /// Echoed in the response, alphanumeric up to 32 characters
+    pub id: CompactString,
+    pub op: OkexOrderOp,
+    pub args: Vec<A>,
+}
+
+fn order_ids(order: &OrderRef) -> (Option<CompactString>, Option<CompactString>) {
+    match order {
+        OrderRef::OrderId(id) => (Some(id.clone()), None),
+        OrderRef::ClientOrderId(id) => (None, Some(id.clone())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::gates::okex::common::account::OkexTradeMode;
+    use crate::gates::okex::private::request::{AmendOrderRequest, PlaceOrderRequest};
+    use crate::model::internal::Side;
+    use crate::model::order::{AmendRequest, OrderRef, OrderRequest, TimeInForce};
+    use crate::model::symbol::Symbol;
+    use crate::utils::basic_types::{Amount, Price};
+
+    #[test]
+    fn place_order_serialization() {
+        let order = OrderRequest::limit(
+            Symbol::perpetual("BTC", "USDT"),
+            Side::Ask,
+            Price::from_str("30000.5").unwrap(),
+            Amount::from_str("2").unwrap(),
+            TimeInForce::PostOnly,
+        )
+            .with_client_order_id("b15")
+            .with_reduce_only();
+        let request = PlaceOrderRequest::new(&order, OkexTradeMode::Cross).unwrap();
+        assert_eq!(
+            serde_json::to_string(&request).unwrap(),
+            r#"{"instId":"BTC-USDT-SWAP","tdMode":"cross","clOrdId":"b15","side":"sell","ordType":"post_only","px":"30000.5","sz":"2.0","reduceOnly":true}"#,
+        );
+
+        let order = OrderRequest::market(Symbol::spot("BTC", "USDT"), Side::Bid, Amount::from_str("0.01").unwrap());
+        let request = PlaceOrderRequest::new(&order, OkexTradeMode::Cross).unwrap();
+        assert_eq!(
+            serde_json::to_string(&request).unwrap(),
+            r#"{"instId":"BTC-USDT","tdMode":"cash","side":"buy","ordType":"market","sz":"0.01","tgtCcy":"base_ccy"}"#,
+        );
+
+        let order = OrderRequest { price: None, ..OrderRequest::limit(
+            Symbol::spot("BTC", "USDT"), Side::Bid, Price::from_str("1").unwrap(), Amount::from_str("1").unwrap(), TimeInForce::Gtc,
+        ) };
+        assert!(PlaceOrderRequest::new(&order, OkexTradeMode::Cross).is_err());
+    }
+
+    #[test]
+    fn amend_order_serialization() {
+        let amend = AmendRequest {
+            symbol: Symbol::spot("BTC", "USDT"),
+            order: OrderRef::ClientOrderId("b15".into()),
+            new_price: Some(Price::from_str("29000").unwrap()),
+            new_amount: None,
+        };
+        let request = AmendOrderRequest::new(&amend).unwrap();
+        assert_eq!(
+            serde_json::to_string(&request).unwrap(),
+            r#"{"instId":"BTC-USDT","clOrdId":"b15","newPx":"29000.0"}"#,
+        );
+        assert!(AmendOrderRequest::new(&AmendRequest { new_price: None, ..amend }).is_err());
+    }
+}
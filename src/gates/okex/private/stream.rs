@@ -0,0 +1,111 @@
+use compact_str::CompactString;
+use serde::{Deserialize, Serialize};
+
+use crate::gates::okex::common::auth::{OkexCredentials, OkexLoginArgs};
+use crate::gates::okex::md::model::WsRequest;
+use crate::model::stream::WsStream;
+
+#[derive(Clone)]
+pub struct OkexPrivateStream {
+    pub channels: Vec<OkexPrivateChannel>,
+    pub credentials: OkexCredentials,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OkexPrivateChannel {
+    /// account: equity of the currencies, pushed on changes and every 5 seconds
+    ///
+    /// https://www.okx.com/docs-v5/en/#trading-account-websocket-account-channel
+    Account,
+    /// positions: positions of all the instrument types, pushed on changes and every 5 seconds
+    ///
+    /// https://www.okx.com/docs-v5/en/#trading-account-websocket-positions-channel
+    Positions,
+    /// orders: order updates of all the instrument types, pushed on changes only
+    ///
+    /// https://www.okx.com/docs-v5/en/#order-book-trading-trade-ws-order-channel
+    Orders,
+    /// balance_and_position: cash balances and positions changed by the same event, e.g. a fill
+    ///
+    /// https://www.okx.com/docs-v5/en/#trading-account-websocket-balance-and-position-channel
+    BalanceAndPosition,
+}
+
+impl OkexPrivateChannel {
+    pub fn all() -> Vec<Self> {
+        vec![
+            OkexPrivateChannel::Account,
+            OkexPrivateChannel::Positions,
+            OkexPrivateChannel::Orders,
+            OkexPrivateChannel::BalanceAndPosition,
+        ]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            OkexPrivateChannel::Account => "account",
+            OkexPrivateChannel::Positions => "positions",
+            OkexPrivateChannel::Orders => "orders",
+            OkexPrivateChannel::BalanceAndPosition => "balance_and_position",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::all().into_iter().find(|c| c.name() == name)
+    }
+
+    /// Subscription to all the instruments of the channel
+    pub fn arg(&self) -> OkexPrivateArg {
+        let inst_type = match self {
+            OkexPrivateChannel::Positions | OkexPrivateChannel::Orders => Some("ANY".into()),
+            OkexPrivateChannel::Account | OkexPrivateChannel::BalanceAndPosition => None,
+        };
+        OkexPrivateArg { channel: self.name().into(), inst_type }
+    }
+}
+
+/// Argument of the private channel subscription, echoed in the pushed messages
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OkexPrivateArg {
+    pub channel: CompactString,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inst_type: Option<CompactString>,
+}
+
+impl WsStream for OkexPrivateStream {
+    type Kind = ();
+    type Subscribe = WsRequest<OkexPrivateArg>;
+    type Login = WsRequest<OkexLoginArgs>;
+
+    fn kind(&self) -> Self::Kind {}
+
+    /// None without channels, e.g. for the connection used for order entry only
+    fn subscribe_requests(&self) -> Vec<Self::Subscribe> {
+        if self.channels.is_empty() {
+            return Vec::new();
+        }
+        vec![WsRequest::new_subscribe(self.channels.iter().map(OkexPrivateChannel::arg).collect())]
+    }
+
+    /// Signed again on every connect, so that the reconnected stream logs in with a fresh timestamp
+    fn login_requests(&self) -> Vec<Self::Login> {
+        vec![WsRequest::new_login(vec![self.credentials.login_args()])]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::gates::okex::md::model::WsRequest;
+    use crate::gates::okex::private::stream::OkexPrivateChannel;
+
+    #[test]
+    fn subscribe_request_serialization() {
+        let request = WsRequest::new_subscribe(OkexPrivateChannel::all().iter().map(OkexPrivateChannel::arg).collect());
+        assert_eq!(
+            serde_json::to_string(&request).unwrap(),
+            r#"{"op":"subscribe","args":[{"channel":"account"},{"channel":"positions","instType":"ANY"},{"channel":"orders","instType":"ANY"},{"channel":"balance_and_position"}]}"#,
+        );
+    }
+}
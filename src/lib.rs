@@ -1,4 +1,4 @@
 pub mod api;
 pub mod model;
 pub mod utils;
-mod gates;
+pub mod gates;
@@ -0,0 +1,52 @@
+use compact_str::CompactString;
+use serde::{Deserialize, Serialize};
+
+use crate::model::exchange::Exchange;
+use crate::model::order::OrderUpdate;
+use crate::model::symbol::Symbol;
+use crate::utils::basic_types::{Amount, InstrumentId, Price};
+
+/// Events of the private exchange connections
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum AccountEvent {
+    Balance(Balance),
+    Position(Position),
+    Order(OrderUpdate),
+    /// The connection was restored, the order updates in between are lost
+    /// and the orders should be recovered, e.g. by `OkexPrivateClient::recover_orders`
+    Reconnected(Exchange),
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Balance {
+    pub exchange_time: Option<u64>,
+    pub exchange: Exchange,
+    pub currency: CompactString,
+    /// Equity of the currency
+    pub total: Amount,
+    pub available: Option<Amount>,
+    /// Reserved by open orders and margin
+    pub frozen: Option<Amount>,
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Hash, Copy, Clone)]
+pub enum PositionSide {
+    /// Single position per instrument, negative amount is short
+    Net,
+    Long,
+    Short,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Position {
+    pub exchange_time: Option<u64>,
+    pub exchange: Exchange,
+    pub symbol: Symbol,
+    /// Set when the symbol is known to the `InstrumentRegistry` of the connection
+    pub instrument_id: Option<InstrumentId>,
+    pub side: PositionSide,
+    /// Signed for `Net` positions, zero when the position is closed
+    pub amount: Amount,
+    pub avg_price: Option<Price>,
+    pub unrealized_pnl: Option<Amount>,
+}
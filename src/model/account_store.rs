@@ -0,0 +1,300 @@
+use std::collections::{HashMap, HashSet};
+
+use compact_str::CompactString;
+use fixnum::ArithmeticError;
+use fixnum::ops::{CheckedSub, One, RoundMode, RoundingMul, Zero};
+use log::{trace, warn};
+
+use crate::model::account::{AccountEvent, Balance, Position, PositionSide};
+use crate::model::exchange::Exchange;
+use crate::model::instrument::{Instrument, InstrumentRegistry};
+use crate::model::storage::Storage;
+use crate::model::symbol::Symbol;
+use crate::utils::basic_types::{Amount, Price};
+
+/// Balances and positions of the accounts, the counterpart of `Storage` for the private data.
+///
+/// Updated incrementally by the events of `AccountConnection`. The order events are ignored,
+/// since the balances and positions changed by the fills are pushed by the exchanges separately.
+/// The REST snapshots passed to `reconcile_balances` and `reconcile_positions` replace the state,
+/// logging the differences, which are expected to be caused by missed pushes only.
+#[derive(Debug, Default)]
+pub struct AccountStore {
+    balances: HashMap<(Exchange, CompactString), Balance>,
+    positions: HashMap<(Exchange, Symbol, PositionSide), Position>,
+}
+
+impl AccountStore {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn on_event(&mut self, event: &AccountEvent) {
+        match event {
+            AccountEvent::Balance(balance) => self.on_balance(balance),
+            AccountEvent::Position(position) => self.on_position(position),
+            // the current balances and positions are pushed again after resubscribing
+            AccountEvent::Order(_) | AccountEvent::Reconnected(_) => {}
+        }
+    }
+
+    /// The fields missing from the update, e.g. `available` of Okex `balance_and_position`, are kept
+    fn on_balance(&mut self, balance: &Balance) {
+        match self.balances.get_mut(&(balance.exchange, balance.currency.clone())) {
+            Some(current) if is_stale(current.exchange_time, balance.exchange_time) => {
+                trace!("stale balance update ignored {balance:?}");
+            }
+            Some(current) => {
+                current.exchange_time = balance.exchange_time;
+                current.total = balance.total;
+                current.available = balance.available.or(current.available);
+                current.frozen = balance.frozen.or(current.frozen);
+            }
+            None => {
+                self.balances.insert((balance.exchange, balance.currency.clone()), balance.clone());
+            }
+        }
+    }
+
+    /// Closed positions are removed, the unrealized PnL is kept if the update doesn't carry it
+    fn on_position(&mut self, position: &Position) {
+        let key = (position.exchange, position.symbol.clone(), position.side);
+        let current = self.positions.get(&key);
+        if current.is_some_and(|c| is_stale(c.exchange_time, position.exchange_time)) {
+            trace!("stale position update ignored {position:?}");
+            return;
+        }
+        if position.amount == Amount::ZERO {
+            self.positions.remove(&key);
+            return;
+        }
+        let unrealized_pnl = position.unrealized_pnl.or(current.and_then(|c| c.unrealized_pnl));
+        self.positions.insert(key, Position { unrealized_pnl, ..position.clone() });
+    }
+
+    /// Replaces the balances of the exchange with the snapshot
+    pub fn reconcile_balances(&mut self, exchange: Exchange, snapshot: Vec<Balance>) {
+        let currencies: HashSet<_> = snapshot.iter().map(|b| b.currency.clone()).collect();
+        for balance in snapshot {
+            match self.balances.get(&(exchange, balance.currency.clone())) {
+                Some(current) if current.total != balance.total => {
+                    warn!("{exchange:?} {} balance drifted from {} to {}", balance.currency, current.total, balance.total);
+                }
+                None if balance.total != Amount::ZERO => {
+                    warn!("{exchange:?} {} balance {} was missing", balance.currency, balance.total);
+                }
+                _ => {}
+            }
+            self.balances.insert((exchange, balance.currency.clone()), balance);
+        }
+        self.balances.retain(|(e, currency), balance| {
+            let keep = *e != exchange || currencies.contains(currency);
+            if !keep && balance.total != Amount::ZERO {
+                warn!("{exchange:?} {currency} balance {} is not in the snapshot anymore", balance.total);
+            }
+            keep
+        });
+    }
+
+    /// Replaces the positions of the exchange with the snapshot of the open positions
+    pub fn reconcile_positions(&mut self, exchange: Exchange, snapshot: Vec<Position>) {
+        let mut positions: HashMap<_, _> = snapshot
+            .into_iter()
+            .filter(|p| p.amount != Amount::ZERO)
+            .map(|p| ((p.exchange, p.symbol.clone(), p.side), p))
+            .collect();
+        for (key, current) in self.positions.iter().filter(|(key, _)| key.0 == exchange) {
+            match positions.get(key) {
+                Some(position) if position.amount != current.amount => {
+                    warn!("{exchange:?} {} position drifted from {} to {}", key.1, current.amount, position.amount);
+                }
+                None => warn!("{exchange:?} {} position {} is closed", key.1, current.amount),
+                _ => {}
+            }
+        }
+        for (key, position) in &positions {
+            if !self.positions.contains_key(key) {
+                warn!("{exchange:?} {} position {} was missing", key.1, position.amount);
+            }
+        }
+        self.positions.retain(|key, _| key.0 != exchange);
+        self.positions.extend(positions.drain());
+    }
+
+    /// Updates the unrealized PnL of the linear positions with the mid prices of `Storage`.
+    /// The contract values come from the instruments, one is used for spot and for the unknown instruments.
+    /// The inverse positions keep the PnL pushed by the exchange, as do the positions whose PnL overflows.
+    pub fn mark_to_market(&mut self, storage: &Storage, instruments: &InstrumentRegistry) {
+        for position in self.positions.values_mut() {
+            let (Some(mid), Some(avg_price)) = (storage.mid_price(position.exchange, &position.symbol), position.avg_price) else {
+                continue;
+            };
+            let instrument = position.instrument_id
+                .or_else(|| instruments.id_of(position.exchange, &position.symbol))
+                .and_then(|id| instruments.get(id));
+            if instrument.is_some_and(Instrument::is_inverse) {
+                trace!("{:?} {} position is inverse, skipping mark to market", position.exchange, position.symbol);
+                continue;
+            }
+            let contract_value = instrument.and_then(|i| i.contract_value).unwrap_or(Amount::ONE);
+            match linear_pnl(position, contract_value, avg_price, mid) {
+                Ok(pnl) => position.unrealized_pnl = Some(pnl),
+                Err(err) => warn!("failed to mark {:?} {} position to market, {err}", position.exchange, position.symbol),
+            }
+        }
+    }
+
+    pub fn balance(&self, exchange: Exchange, currency: &str) -> Option<&Balance> {
+        self.balances.get(&(exchange, CompactString::from(currency)))
+    }
+
+    pub fn position(&self, exchange: Exchange, symbol: &Symbol, side: PositionSide) -> Option<&Position> {
+        self.positions.get(&(exchange, symbol.clone(), side))
+    }
+
+    pub fn balances(&self) -> impl Iterator<Item = &Balance> {
+        self.balances.values()
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = &Position> {
+        self.positions.values()
+    }
+}
+
+fn linear_pnl(position: &Position, contract_value: Amount, avg_price: Price, mid: Price) -> Result<Amount, ArithmeticError> {
+    let amount = match position.side {
+        PositionSide::Net | PositionSide::Long => position.amount,
+        PositionSide::Short => position.amount.cneg()?,
+    };
+    let size = amount.rmul(contract_value, RoundMode::Nearest)?;
+    size.rmul(mid.csub(avg_price)?, RoundMode::Nearest)
+}
+
+/// Updates without the exchange time are always applied
+fn is_stale(current: Option<u64>, update: Option<u64>) -> bool {
+    matches!((current, update), (Some(current), Some(update)) if update < current)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::model::account::{AccountEvent, Balance, Position, PositionSide};
+    use crate::model::account_store::AccountStore;
+    use crate::model::exchange::Exchange;
+    use crate::model::instrument::{Instrument, InstrumentKind, InstrumentRegistry, InstrumentState};
+    use crate::model::internal::{L2Snapshot, MdInstrument, MdMessage, SingleLot};
+    use crate::model::storage::Storage;
+    use crate::model::symbol::Symbol;
+    use crate::utils::basic_types::{Amount, Price};
+
+    fn amount(s: &str) -> Amount {
+        Amount::from_str(s).unwrap()
+    }
+
+    fn balance(total: &str, available: Option<&str>, time: u64) -> Balance {
+        Balance {
+            exchange_time: Some(time),
+            exchange: Exchange::Okex,
+            currency: "USDT".into(),
+            total: amount(total),
+            available: available.map(amount),
+            frozen: None,
+        }
+    }
+
+    fn position(size: &str, time: u64) -> Position {
+        Position {
+            exchange_time: Some(time),
+            exchange: Exchange::Okex,
+            symbol: Symbol::perpetual("BTC", "USDT"),
+            instrument_id: None,
+            side: PositionSide::Net,
+            amount: amount(size),
+            avg_price: Some(Price::from_str("30000").unwrap()),
+            unrealized_pnl: None,
+        }
+    }
+
+    #[test]
+    fn incremental_updates() {
+        let mut store = AccountStore::new();
+        store.on_event(&AccountEvent::Balance(balance("100", Some("90"), 2)));
+        store.on_event(&AccountEvent::Balance(balance("120", None, 3)));
+        store.on_event(&AccountEvent::Balance(balance("50", Some("50"), 1)));
+        let usdt = store.balance(Exchange::Okex, "USDT").unwrap();
+        assert_eq!((usdt.total, usdt.available), (amount("120"), Some(amount("90"))));
+
+        let symbol = Symbol::perpetual("BTC", "USDT");
+        store.on_event(&AccountEvent::Position(position("-2", 1)));
+        assert_eq!(store.position(Exchange::Okex, &symbol, PositionSide::Net).unwrap().amount, amount("-2"));
+        store.on_event(&AccountEvent::Position(position("0", 2)));
+        assert!(store.position(Exchange::Okex, &symbol, PositionSide::Net).is_none());
+    }
+
+    #[test]
+    fn reconcile_replaces_state() {
+        let mut store = AccountStore::new();
+        store.on_event(&AccountEvent::Balance(balance("100", Some("90"), 1)));
+        store.on_event(&AccountEvent::Position(position("-2", 1)));
+
+        store.reconcile_balances(Exchange::Okex, vec![Balance { currency: "BTC".into(), ..balance("1", None, 2) }]);
+        assert!(store.balance(Exchange::Okex, "USDT").is_none());
+        assert_eq!(store.balance(Exchange::Okex, "BTC").unwrap().total, amount("1"));
+
+        store.reconcile_positions(Exchange::Okex, vec![position("-3", 2)]);
+        assert_eq!(store.positions().map(|p| p.amount).collect::<Vec<_>>(), vec![amount("-3")]);
+    }
+
+    #[test]
+    fn mark_to_market() {
+        let mut storage = Storage::new();
+        let level = |price: &str| SingleLot { price: Price::from_str(price).unwrap(), amount: amount("1") };
+        for (base, quote) in [("BTC", "USDT"), ("BTC", "USD"), ("ETH", "USDT")] {
+            storage.on_ws_update(MdMessage::L2Snapshot(L2Snapshot {
+                exchange_time: None,
+                sequence_no: None,
+                exchange: Exchange::Okex,
+                instrument: MdInstrument::Symbol(Symbol::perpetual(base, quote)),
+                bids: vec![level("30990")],
+                asks: vec![level("31010")],
+            }));
+        }
+        let instrument = |symbol: &str, quote: &str, settle: &str, contract_value: &str| Instrument {
+            exchange: Exchange::Okex,
+            symbol: symbol.into(),
+            internal_symbol: Symbol::perpetual("BTC", quote),
+            kind: InstrumentKind::Perpetual,
+            base_currency: "BTC".into(),
+            quote_currency: quote.into(),
+            settle_currency: Some(settle.into()),
+            tick_size: Price::from_str("0.1").unwrap(),
+            lot_size: amount("1"),
+            min_size: amount("1"),
+            contract_value: Some(amount(contract_value)),
+            expiry: None,
+            state: InstrumentState::Live,
+        };
+        let mut instruments = InstrumentRegistry::new();
+        instruments.insert(instrument("BTC-USDT-SWAP", "USDT", "USDT", "0.01"));
+        instruments.insert(instrument("BTC-USD-SWAP", "USD", "BTC", "100"));
+
+        let linear = Symbol::perpetual("BTC", "USDT");
+        let inverse = Symbol::perpetual("BTC", "USD");
+        let overflowing = Symbol::perpetual("ETH", "USDT");
+        let mut store = AccountStore::new();
+        store.on_event(&AccountEvent::Position(position("-2", 1)));
+        store.on_event(&AccountEvent::Position(Position {
+            symbol: inverse.clone(),
+            unrealized_pnl: Some(amount("0.001")),
+            ..position("1", 1)
+        }));
+        store.on_event(&AccountEvent::Position(Position { symbol: overflowing.clone(), ..position("1000000000000000000000", 1) }));
+        store.mark_to_market(&storage, &instruments);
+
+        let pnl = |symbol: &Symbol| store.position(Exchange::Okex, symbol, PositionSide::Net).unwrap().unrealized_pnl;
+        assert_eq!(pnl(&linear), Some(amount("-20")));
+        assert_eq!(pnl(&inverse), Some(amount("0.001")));
+        assert_eq!(pnl(&overflowing), None);
+    }
+}
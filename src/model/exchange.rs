@@ -3,4 +3,9 @@ use serde::{Deserialize, Serialize};
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Deserialize, Serialize)]
 pub enum Exchange {
     Okex,
+    Binance,
+    Bybit,
+    Coinbase,
+    Kraken,
+    Deribit,
 }
\ No newline at end of file
@@ -0,0 +1,200 @@
+use std::collections::HashMap;
+
+use compact_str::CompactString;
+use serde::{Deserialize, Serialize};
+use slotmap::SlotMap;
+
+use crate::model::exchange::Exchange;
+use crate::model::symbol::Symbol;
+use crate::utils::basic_types::{Amount, InstrumentId, Price};
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Instrument {
+    pub exchange: Exchange,
+    /// Symbol native to the exchange, e.g. BTC-USDT-SWAP
+    pub symbol: CompactString,
+    /// Exchange independent symbol, e.g. BTC/USDT-PERP
+    pub internal_symbol: Symbol,
+    pub kind: InstrumentKind,
+    pub base_currency: CompactString,
+    pub quote_currency: CompactString,
+    /// Currency of the margin and PnL, only applicable to derivatives
+    pub settle_currency: Option<CompactString>,
+    /// Minimal price increment
+    pub tick_size: Price,
+    /// Minimal amount increment, in base currency for spot and in contracts for derivatives
+    pub lot_size: Amount,
+    /// Minimal order amount, in the same units as `lot_size`
+    pub min_size: Amount,
+    /// Amount of the underlying per contract, only applicable to derivatives
+    pub contract_value: Option<Amount>,
+    /// Expiry time, Unix timestamp in milliseconds, only applicable to futures and options
+    pub expiry: Option<u64>,
+    pub state: InstrumentState,
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Hash, Copy, Clone)]
+pub enum InstrumentKind {
+    Spot,
+    Margin,
+    Perpetual,
+    Future,
+    Option,
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Hash, Copy, Clone)]
+pub enum InstrumentState {
+    Live,
+    Suspended,
+    /// Listed, but the trading has not started yet
+    PreOpen,
+    /// Available for test trading only
+    Test,
+}
+
+/// Instruments of all the exchanges, addressed by the compact `InstrumentId`
+#[derive(Debug, Default)]
+pub struct InstrumentRegistry {
+    instruments: SlotMap<InstrumentId, Instrument>,
+    ids: HashMap<(Exchange, CompactString), InstrumentId>,
+    internal_ids: HashMap<(Exchange, Symbol), InstrumentId>,
+}
+
+impl Instrument {
+    /// Coin-margined derivative, settled in the base currency, e.g. Okex BTC-USD-SWAP
+    pub fn is_inverse(&self) -> bool {
+        self.settle_currency.as_ref().is_some_and(|c| *c == self.base_currency)
+    }
+}
+
+impl InstrumentRegistry {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Adds the instrument or updates the existing one with the same exchange and symbol, keeping its id
+    pub fn insert(&mut self, instrument: Instrument) -> InstrumentId {
+        let key = (instrument.exchange, instrument.symbol.clone());
+        match self.ids.get(&key) {
+            Some(&id) => {
+                self.internal_ids.insert((instrument.exchange, instrument.internal_symbol.clone()), id);
+                self.instruments[id] = instrument;
+                id
+            }
+            None => {
+                let internal_key = (instrument.exchange, instrument.internal_symbol.clone());
+                let id = self.instruments.insert(instrument);
+                self.ids.insert(key, id);
+                self.internal_ids.insert(internal_key, id);
+                id
+            }
+        }
+    }
+
+    pub fn extend(&mut self, instruments: impl IntoIterator<Item = Instrument>) {
+        for instrument in instruments {
+            self.insert(instrument);
+        }
+    }
+
+    pub fn get(&self, id: InstrumentId) -> Option<&Instrument> {
+        self.instruments.get(id)
+    }
+
+    pub fn id(&self, exchange: Exchange, symbol: &str) -> Option<InstrumentId> {
+        self.ids.get(&(exchange, CompactString::from(symbol))).copied()
+    }
+
+    /// Id of the instrument by its exchange independent symbol
+    pub fn id_of(&self, exchange: Exchange, symbol: &Symbol) -> Option<InstrumentId> {
+        self.internal_ids.get(&(exchange, symbol.clone())).copied()
+    }
+
+    pub fn by_symbol(&self, exchange: Exchange, symbol: &str) -> Option<&Instrument> {
+        self.id(exchange, symbol).and_then(|id| self.get(id))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (InstrumentId, &Instrument)> {
+        self.instruments.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.instruments.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.instruments.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::gates::okex::common::symbol::OkexSymbols;
+    use crate::model::exchange::Exchange;
+    use crate::model::instrument::{Instrument, InstrumentKind, InstrumentRegistry, InstrumentState};
+    use crate::model::internal::{IndexPrice, MdInstrument, MdMessage, Side, Trade};
+    use crate::model::symbol::{Symbol, SymbolMapping};
+    use crate::utils::basic_types::Price;
+
+    fn instrument(symbol: &str, state: InstrumentState) -> Instrument {
+        Instrument {
+            exchange: Exchange::Okex,
+            symbol: symbol.into(),
+            internal_symbol: OkexSymbols::from_native(symbol).unwrap(),
+            kind: InstrumentKind::Spot,
+            base_currency: "BTC".into(),
+            quote_currency: "USDT".into(),
+            settle_currency: None,
+            tick_size: Price::from_str("0.1").unwrap(),
+            lot_size: Price::from_str("0.00000001").unwrap(),
+            min_size: Price::from_str("0.00001").unwrap(),
+            contract_value: None,
+            expiry: None,
+            state,
+        }
+    }
+
+    #[test]
+    fn insert_keeps_id_of_existing_instrument() {
+        let mut registry = InstrumentRegistry::new();
+        let btc = registry.insert(instrument("BTC-USDT", InstrumentState::PreOpen));
+        let eth = registry.insert(instrument("ETH-USDT", InstrumentState::Live));
+        assert_ne!(btc, eth);
+
+        let updated = registry.insert(instrument("BTC-USDT", InstrumentState::Live));
+        assert_eq!(updated, btc);
+        assert_eq!(registry.len(), 2);
+        assert_eq!(registry.get(btc).unwrap().state, InstrumentState::Live);
+        assert_eq!(registry.id(Exchange::Okex, "ETH-USDT"), Some(eth));
+        assert_eq!(registry.id_of(Exchange::Okex, &Symbol::spot("ETH", "USDT")), Some(eth));
+        assert!(registry.by_symbol(Exchange::Okex, "XRP-USDT").is_none());
+    }
+
+    #[test]
+    fn index_prices_are_not_resolved() {
+        let mut registry = InstrumentRegistry::new();
+        let btc = registry.insert(instrument("BTC-USDT", InstrumentState::Live));
+        let symbol = Symbol::spot("BTC", "USDT");
+        let mut trade = MdMessage::Trade(Trade {
+            exchange_time: None,
+            exchange: Exchange::Okex,
+            instrument: MdInstrument::Symbol(symbol),
+            trade_id: "1".into(),
+            price: Price::from_str("30000").unwrap(),
+            amount: Price::from_str("1").unwrap(),
+            side: Side::Bid,
+        });
+        let mut index = MdMessage::IndexPrice(IndexPrice {
+            exchange_time: None,
+            exchange: Exchange::Okex,
+            instrument: MdInstrument::Symbol(Symbol::index("BTC", "USDT")),
+            price: Price::from_str("30000").unwrap(),
+        });
+        trade.resolve_instrument(&registry);
+        index.resolve_instrument(&registry);
+        assert_eq!(trade.instrument(), &MdInstrument::Id(btc));
+        assert_eq!(index.instrument(), &MdInstrument::Symbol(Symbol::index("BTC", "USDT")));
+    }
+}
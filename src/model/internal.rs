@@ -1,19 +1,118 @@
 use compact_str::CompactString;
 use serde::{Deserialize, Serialize};
 
-use crate::utils::basic_types::{Amount, Price};
+use crate::model::exchange::Exchange;
+use crate::model::instrument::InstrumentRegistry;
+use crate::model::symbol::Symbol;
+use crate::utils::basic_types::{Amount, InstrumentId, Price};
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum MdMessage {
     L2Snapshot(L2Snapshot),
     L2Increment(L2Increment),
+    BookInvalid(BookInvalid),
+    Trade(Trade),
+    Ticker(Ticker),
+    FundingRate(FundingRate),
+    MarkPrice(MarkPrice),
+    IndexPrice(IndexPrice),
+    OpenInterest(OpenInterest),
+    Candle(Candle),
+}
+
+impl MdMessage {
+    pub fn instrument(&self) -> &MdInstrument {
+        match self {
+            MdMessage::L2Snapshot(m) => &m.instrument,
+            MdMessage::L2Increment(m) => &m.instrument,
+            MdMessage::BookInvalid(m) => &m.instrument,
+            MdMessage::Trade(m) => &m.instrument,
+            MdMessage::Ticker(m) => &m.instrument,
+            MdMessage::FundingRate(m) => &m.instrument,
+            MdMessage::MarkPrice(m) => &m.instrument,
+            MdMessage::IndexPrice(m) => &m.instrument,
+            MdMessage::OpenInterest(m) => &m.instrument,
+            MdMessage::Candle(m) => &m.instrument,
+        }
+    }
+
+    pub fn exchange(&self) -> Exchange {
+        match self {
+            MdMessage::L2Snapshot(m) => m.exchange,
+            MdMessage::L2Increment(m) => m.exchange,
+            MdMessage::BookInvalid(m) => m.exchange,
+            MdMessage::Trade(m) => m.exchange,
+            MdMessage::Ticker(m) => m.exchange,
+            MdMessage::FundingRate(m) => m.exchange,
+            MdMessage::MarkPrice(m) => m.exchange,
+            MdMessage::IndexPrice(m) => m.exchange,
+            MdMessage::OpenInterest(m) => m.exchange,
+            MdMessage::Candle(m) => m.exchange,
+        }
+    }
+
+    /// Replaces the symbol with the id of the instrument if the registry knows it.
+    /// Index prices keep their symbols, since the indices are not instruments.
+    pub fn resolve_instrument(&mut self, registry: &InstrumentRegistry) {
+        let exchange = self.exchange();
+        let instrument = self.instrument_mut();
+        if let Some(id) = instrument.symbol().and_then(|symbol| registry.id_of(exchange, symbol)) {
+            *instrument = MdInstrument::Id(id);
+        }
+    }
+
+    fn instrument_mut(&mut self) -> &mut MdInstrument {
+        match self {
+            MdMessage::L2Snapshot(m) => &mut m.instrument,
+            MdMessage::L2Increment(m) => &mut m.instrument,
+            MdMessage::BookInvalid(m) => &mut m.instrument,
+            MdMessage::Trade(m) => &mut m.instrument,
+            MdMessage::Ticker(m) => &mut m.instrument,
+            MdMessage::FundingRate(m) => &mut m.instrument,
+            MdMessage::MarkPrice(m) => &mut m.instrument,
+            MdMessage::IndexPrice(m) => &mut m.instrument,
+            MdMessage::OpenInterest(m) => &mut m.instrument,
+            MdMessage::Candle(m) => &mut m.instrument,
+        }
+    }
+}
+
+/// Instrument of a message: the compact id if the connection resolved it with its `InstrumentRegistry`,
+/// the symbol otherwise
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub enum MdInstrument {
+    Id(InstrumentId),
+    Symbol(Symbol),
+}
+
+impl MdInstrument {
+    pub fn id(&self) -> Option<InstrumentId> {
+        match self {
+            MdInstrument::Id(id) => Some(*id),
+            MdInstrument::Symbol(_) => None,
+        }
+    }
+
+    pub fn symbol(&self) -> Option<&Symbol> {
+        match self {
+            MdInstrument::Id(_) => None,
+            MdInstrument::Symbol(symbol) => Some(symbol),
+        }
+    }
+}
+
+impl From<Symbol> for MdInstrument {
+    fn from(symbol: Symbol) -> Self {
+        MdInstrument::Symbol(symbol)
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct L2Snapshot {
     pub exchange_time: Option<u64>,
     pub sequence_no: Option<u64>,
-    pub symbol: CompactString,
+    pub exchange: Exchange,
+    pub instrument: MdInstrument,
     pub bids: Vec<SingleLot>,
     pub asks: Vec<SingleLot>,
 }
@@ -28,7 +127,8 @@ pub struct SingleLot {
 pub struct L2Increment {
     pub exchange_time: Option<u64>,
     pub sequence_no: Option<u64>,
-    pub symbol: CompactString,
+    pub exchange: Exchange,
+    pub instrument: MdInstrument,
     pub side: Side,
     pub price: Price,
     pub amount: Amount,
@@ -40,3 +140,132 @@ pub enum Side {
     Bid,
     Ask,
 }
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Trade {
+    pub exchange_time: Option<u64>,
+    pub exchange: Exchange,
+    pub instrument: MdInstrument,
+    pub trade_id: CompactString,
+    pub price: Price,
+    pub amount: Amount,
+    /// Side of the taker: `Bid` if the buyer was the aggressor, `Ask` if the seller was
+    pub side: Side,
+}
+
+/// Top of the book and 24 hours statistics
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Ticker {
+    pub exchange_time: Option<u64>,
+    pub exchange: Exchange,
+    pub instrument: MdInstrument,
+    pub bid_price: Option<Price>,
+    pub bid_amount: Option<Amount>,
+    pub ask_price: Option<Price>,
+    pub ask_amount: Option<Amount>,
+    pub last_price: Option<Price>,
+    pub last_amount: Option<Amount>,
+    /// Traded volume for the last 24 hours, in base currency for spot and in contracts for derivatives
+    pub volume_24h: Option<Amount>,
+    /// Traded volume for the last 24 hours, in quote currency for spot and in base currency for derivatives
+    pub currency_volume_24h: Option<Amount>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct FundingRate {
+    pub exchange_time: Option<u64>,
+    pub exchange: Exchange,
+    pub instrument: MdInstrument,
+    /// Funding rate of the current period
+    pub rate: Price,
+    /// Settlement time of the current period, Unix timestamp in milliseconds
+    pub funding_time: u64,
+    /// Forecasted funding rate of the next period, if the exchange publishes it
+    pub next_rate: Option<Price>,
+    pub next_funding_time: Option<u64>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct MarkPrice {
+    pub exchange_time: Option<u64>,
+    pub exchange: Exchange,
+    pub instrument: MdInstrument,
+    pub price: Price,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct IndexPrice {
+    pub exchange_time: Option<u64>,
+    pub exchange: Exchange,
+    /// Index, e.g. BTC/USDT for the Okex BTC-USDT index
+    pub instrument: MdInstrument,
+    pub price: Price,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct OpenInterest {
+    pub exchange_time: Option<u64>,
+    pub exchange: Exchange,
+    pub instrument: MdInstrument,
+    /// Open interest in contracts
+    pub open_interest: Amount,
+    /// Open interest in base currency
+    pub open_interest_currency: Option<Amount>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Candle {
+    pub exchange: Exchange,
+    pub instrument: MdInstrument,
+    pub interval: CandleInterval,
+    /// Opening time of the candle, Unix timestamp in milliseconds
+    pub open_time: u64,
+    pub open: Price,
+    pub high: Price,
+    pub low: Price,
+    pub close: Price,
+    /// Traded volume in base currency for spot and in contracts for derivatives
+    pub volume: Amount,
+    /// Traded volume in quote currency
+    pub quote_volume: Amount,
+    /// `false` while the candle is still being formed
+    pub confirmed: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Hash, Copy, Clone)]
+pub enum CandleInterval {
+    Minute1,
+    Minute3,
+    Minute5,
+    Minute15,
+    Minute30,
+    Hour1,
+    Hour2,
+    Hour4,
+    Hour6,
+    Hour12,
+    Day1,
+    Week1,
+    Month1,
+}
+
+/// Local order book for the symbol can't be trusted anymore and should be dropped
+/// until the next `L2Snapshot` arrives
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct BookInvalid {
+    pub exchange_time: Option<u64>,
+    pub sequence_no: Option<u64>,
+    pub exchange: Exchange,
+    pub instrument: MdInstrument,
+    pub reason: BookInvalidReason,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum BookInvalidReason {
+    /// Checksum calculated over the local book doesn't match the one sent by exchange
+    /// Wide enough for both the signed (Okex) and unsigned (Kraken) 32-bit checksums
+    ChecksumMismatch { expected: i64, actual: i64 },
+    /// Incremental message doesn't continue the last received sequence,
+    /// `expected` is `None` when no snapshot was received for the symbol
+    SequenceGap { expected: Option<u64>, received: u64 },
+}
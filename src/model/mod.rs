@@ -1,5 +1,11 @@
+pub mod account;
+pub mod account_store;
 pub mod exchange;
+pub mod instrument;
 pub mod internal;
+pub mod order;
+pub mod order_tracker;
 pub mod order_book;
 pub mod stream;
-pub mod storage;
\ No newline at end of file
+pub mod storage;
+pub mod symbol;
@@ -0,0 +1,163 @@
+use compact_str::CompactString;
+use serde::{Deserialize, Serialize};
+
+use crate::model::exchange::Exchange;
+use crate::model::internal::Side;
+use crate::model::symbol::Symbol;
+use crate::utils::basic_types::{Amount, InstrumentId, Price};
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Hash, Copy, Clone)]
+pub enum OrderType {
+    Market,
+    Limit,
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Hash, Copy, Clone)]
+pub enum TimeInForce {
+    /// Good till canceled
+    Gtc,
+    /// Rejected instead of taking liquidity
+    PostOnly,
+    /// Immediate or cancel, the unfilled part is canceled
+    Ioc,
+    /// Fill or kill, either filled completely or canceled
+    Fok,
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Hash, Copy, Clone)]
+pub enum OrderStatus {
+    /// Accepted by the exchange and resting in the book
+    New,
+    PartiallyFilled,
+    Filled,
+    Canceled,
+    Rejected,
+}
+
+impl OrderStatus {
+    /// The order can't change anymore
+    pub fn is_final(&self) -> bool {
+        matches!(self, OrderStatus::Filled | OrderStatus::Canceled | OrderStatus::Rejected)
+    }
+}
+
+/// State of an order pushed by the exchange, carrying the fill which caused the update if any
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct OrderUpdate {
+    pub exchange_time: Option<u64>,
+    pub exchange: Exchange,
+    pub symbol: Symbol,
+    /// Set when the symbol is known to the `InstrumentRegistry` of the connection
+    pub instrument_id: Option<InstrumentId>,
+    /// Id assigned by the exchange
+    pub order_id: CompactString,
+    pub client_order_id: Option<CompactString>,
+    /// `Bid` for buy orders and `Ask` for sell orders
+    pub side: Side,
+    pub order_type: OrderType,
+    pub time_in_force: TimeInForce,
+    /// `None` for market orders
+    pub price: Option<Price>,
+    pub amount: Amount,
+    /// Accumulated filled amount
+    pub filled_amount: Amount,
+    pub avg_fill_price: Option<Price>,
+    pub status: OrderStatus,
+    pub fill: Option<Fill>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Fill {
+    pub trade_id: CompactString,
+    pub price: Price,
+    pub amount: Amount,
+    /// Positive when paid, negative for rebates
+    pub fee: Amount,
+    pub fee_currency: CompactString,
+}
+
+/// New order to place through `ExchangeTrader`
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct OrderRequest {
+    pub symbol: Symbol,
+    /// Generated by the exchange if not set
+    pub client_order_id: Option<CompactString>,
+    /// `Bid` to buy and `Ask` to sell
+    pub side: Side,
+    pub order_type: OrderType,
+    /// Ignored for market orders
+    pub time_in_force: TimeInForce,
+    /// Required for limit orders
+    pub price: Option<Price>,
+    /// In base currency for spot and in contracts for derivatives
+    pub amount: Amount,
+    /// Only reduces the position, derivatives and margin only
+    pub reduce_only: bool,
+}
+
+impl OrderRequest {
+    pub fn limit(symbol: Symbol, side: Side, price: Price, amount: Amount, time_in_force: TimeInForce) -> Self {
+        Self {
+            symbol,
+            client_order_id: None,
+            side,
+            order_type: OrderType::Limit,
+            time_in_force,
+            price: Some(price),
+            amount,
+            reduce_only: false,
+        }
+    }
+
+    pub fn market(symbol: Symbol, side: Side, amount: Amount) -> Self {
+        Self {
+            symbol,
+            client_order_id: None,
+            side,
+            order_type: OrderType::Market,
+            time_in_force: TimeInForce::Ioc,
+            price: None,
+            amount,
+            reduce_only: false,
+        }
+    }
+
+    pub fn with_client_order_id(self, client_order_id: impl Into<CompactString>) -> Self {
+        Self { client_order_id: Some(client_order_id.into()), ..self }
+    }
+
+    pub fn with_reduce_only(self) -> Self {
+        Self { reduce_only: true, ..self }
+    }
+}
+
+/// Order of a cancel or amend request, either by the exchange id or by the client id
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub enum OrderRef {
+    OrderId(CompactString),
+    ClientOrderId(CompactString),
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct CancelRequest {
+    pub symbol: Symbol,
+    pub order: OrderRef,
+}
+
+/// Changes the price and/or the amount of a resting order
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct AmendRequest {
+    pub symbol: Symbol,
+    pub order: OrderRef,
+    pub new_price: Option<Price>,
+    /// New total amount, including the filled part
+    pub new_amount: Option<Amount>,
+}
+
+/// Acceptance of a place, cancel or amend request by the exchange,
+/// the outcome is reported by the `OrderUpdate`s of the order later
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct OrderAck {
+    pub order_id: CompactString,
+    pub client_order_id: Option<CompactString>,
+}
@@ -1,7 +1,7 @@
 use std::collections::BTreeMap;
 use std::fmt::{Display, Formatter};
 
-use fixnum::ops::Zero;
+use fixnum::ops::{RoundMode, Zero};
 use log::info;
 
 use crate::model::internal::{L2Increment, L2Snapshot, Side};
@@ -9,6 +9,7 @@ use crate::utils::basic_types::{Amount, Price};
 
 const ZERO: Price = Price::ZERO;
 
+#[derive(Default)]
 pub struct OrderBook {
     pub bids: BTreeMap<Price, Amount>,
     pub asks: BTreeMap<Price, Amount>,
@@ -41,20 +42,51 @@ impl OrderBook {
     }
 
     pub fn process_update(&mut self, update: L2Increment) {
-        let book = match update.side {
+        self.set_level(update.side, update.price, update.amount);
+
+        if update.is_eot {
+            info!("{self}");
+        }
+    }
+
+    /// Sets the amount on the price level, zero amount removes the level
+    pub fn set_level(&mut self, side: Side, price: Price, amount: Amount) {
+        let book = match side {
             Side::Bid => &mut self.bids,
             Side::Ask => &mut self.asks,
         };
 
-        if update.amount != Amount::ZERO {
-            book.insert(update.price, update.amount);
+        if amount != Amount::ZERO {
+            book.insert(price, amount);
         } else {
-            book.remove(&update.price);
+            book.remove(&price);
         }
+    }
 
-        if update.is_eot {
-            info!("{self}");
+    pub fn best_bid(&self) -> Option<(Price, Amount)> {
+        self.bids.last_key_value().map(|(price, amount)| (*price, *amount))
+    }
+
+    pub fn best_ask(&self) -> Option<(Price, Amount)> {
+        self.asks.first_key_value().map(|(price, amount)| (*price, *amount))
+    }
+
+    /// Middle of the best bid and ask, `None` if either side is empty
+    pub fn mid_price(&self) -> Option<Price> {
+        let ((bid, _), (ask, _)) = (self.best_bid()?, self.best_ask()?);
+        Some(Price::half_sum(bid, ask, RoundMode::Nearest))
+    }
+
+    /// Keeps only the best `depth` levels of each side, returns the removed levels
+    pub fn truncate(&mut self, depth: usize) -> Vec<(Side, Price)> {
+        let mut removed = Vec::new();
+        while self.bids.len() > depth {
+            removed.extend(self.bids.pop_first().map(|(price, _)| (Side::Bid, price)));
+        }
+        while self.asks.len() > depth {
+            removed.extend(self.asks.pop_last().map(|(price, _)| (Side::Ask, price)));
         }
+        removed
     }
 }
 
@@ -0,0 +1,423 @@
+use std::collections::{HashMap, HashSet};
+
+use compact_str::{CompactString, format_compact};
+use fixnum::ops::Zero;
+use log::{trace, warn};
+use slotmap::{new_key_type, SlotMap};
+
+use crate::model::exchange::Exchange;
+use crate::model::internal::Side;
+use crate::model::order::{Fill, OrderAck, OrderRef, OrderRequest, OrderStatus, OrderType, OrderUpdate, TimeInForce};
+use crate::model::symbol::Symbol;
+use crate::utils::basic_types::{Amount, Price};
+
+new_key_type! {
+    struct OrderKey;
+}
+
+/// Local state of an order, `New` and `PendingNew` precede the acceptance by the exchange
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub enum OrderState {
+    /// Created locally, not sent yet
+    New,
+    /// Sent, waiting for the acknowledgement
+    PendingNew,
+    /// Accepted by the exchange and resting in the book
+    Live,
+    PartiallyFilled,
+    Filled,
+    Canceled,
+    Rejected,
+}
+
+impl OrderState {
+    pub fn is_final(&self) -> bool {
+        matches!(self, OrderState::Filled | OrderState::Canceled | OrderState::Rejected)
+    }
+
+    pub fn is_open(&self) -> bool {
+        !self.is_final()
+    }
+
+    /// States only move forward, the final ones share the last rank
+    fn rank(&self) -> u8 {
+        match self {
+            OrderState::New => 0,
+            OrderState::PendingNew => 1,
+            OrderState::Live => 2,
+            OrderState::PartiallyFilled => 3,
+            OrderState::Filled | OrderState::Canceled | OrderState::Rejected => 4,
+        }
+    }
+}
+
+impl From<OrderStatus> for OrderState {
+    fn from(status: OrderStatus) -> Self {
+        match status {
+            OrderStatus::New => OrderState::Live,
+            OrderStatus::PartiallyFilled => OrderState::PartiallyFilled,
+            OrderStatus::Filled => OrderState::Filled,
+            OrderStatus::Canceled => OrderState::Canceled,
+            OrderStatus::Rejected => OrderState::Rejected,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct TrackedOrder {
+    pub exchange: Exchange,
+    pub symbol: Symbol,
+    pub client_order_id: Option<CompactString>,
+    /// Known once the order is acknowledged or reported by the exchange
+    pub order_id: Option<CompactString>,
+    pub side: Side,
+    pub order_type: OrderType,
+    pub time_in_force: TimeInForce,
+    pub price: Option<Price>,
+    pub amount: Amount,
+    pub filled_amount: Amount,
+    pub avg_fill_price: Option<Price>,
+    pub state: OrderState,
+    /// Fills of the order, each reported once
+    pub fills: Vec<Fill>,
+    /// Time of the last applied exchange update, Unix timestamp in milliseconds
+    pub exchange_time: Option<u64>,
+    /// Set for the orders rejected by the exchange on placement
+    pub reject_reason: Option<CompactString>,
+}
+
+impl TrackedOrder {
+    fn from_request(exchange: Exchange, request: &OrderRequest) -> Self {
+        Self {
+            exchange,
+            symbol: request.symbol.clone(),
+            client_order_id: request.client_order_id.clone(),
+            order_id: None,
+            side: request.side,
+            order_type: request.order_type,
+            time_in_force: request.time_in_force,
+            price: request.price,
+            amount: request.amount,
+            filled_amount: Amount::ZERO,
+            avg_fill_price: None,
+            state: OrderState::New,
+            fills: Vec::new(),
+            exchange_time: None,
+            reject_reason: None,
+        }
+    }
+
+    fn from_update(update: &OrderUpdate) -> Self {
+        Self {
+            exchange: update.exchange,
+            symbol: update.symbol.clone(),
+            client_order_id: update.client_order_id.clone(),
+            order_id: Some(update.order_id.clone()),
+            side: update.side,
+            order_type: update.order_type,
+            time_in_force: update.time_in_force,
+            price: update.price,
+            amount: update.amount,
+            filled_amount: update.filled_amount,
+            avg_fill_price: update.avg_fill_price,
+            state: update.status.into(),
+            fills: update.fill.iter().cloned().collect(),
+            exchange_time: update.exchange_time,
+            reject_reason: None,
+        }
+    }
+
+    /// Updates may arrive out of order, e.g. the websocket push and the REST snapshot of the same order.
+    /// The one with more filled amount wins, then the one with the later state, then the later one.
+    fn is_older_than(&self, update: &OrderUpdate) -> bool {
+        let state = OrderState::from(update.status);
+        if self.state.is_final() {
+            false
+        } else if self.filled_amount != update.filled_amount {
+            self.filled_amount < update.filled_amount
+        } else if self.state.rank() != state.rank() {
+            self.state.rank() < state.rank()
+        } else {
+            match (self.exchange_time, update.exchange_time) {
+                (Some(current), Some(time)) => current <= time,
+                _ => true,
+            }
+        }
+    }
+
+    fn apply(&mut self, update: &OrderUpdate) {
+        if let Some(fill) = &update.fill {
+            if !self.fills.iter().any(|f| f.trade_id == fill.trade_id) {
+                self.fills.push(fill.clone());
+            }
+        }
+        if self.client_order_id.is_none() {
+            self.client_order_id = update.client_order_id.clone();
+        }
+        self.order_id = Some(update.order_id.clone());
+        if !self.is_older_than(update) {
+            trace!("stale update of order {} ignored", update.order_id);
+            return;
+        }
+        self.price = update.price;
+        self.amount = update.amount;
+        self.filled_amount = update.filled_amount;
+        self.avg_fill_price = update.avg_fill_price;
+        self.state = update.status.into();
+        self.exchange_time = update.exchange_time;
+    }
+}
+
+/// Single source of truth for the state of the orders of an account.
+///
+/// Merges the acknowledgements of `ExchangeTrader` with the order updates of `AccountConnection`,
+/// whichever arrives first, and recovers the state from the open orders snapshot after a reconnect.
+/// Orders are addressed by either id, the client id is generated for the orders created without one.
+#[derive(Debug)]
+pub struct OrderTracker {
+    exchange: Exchange,
+    orders: SlotMap<OrderKey, TrackedOrder>,
+    by_client_id: HashMap<CompactString, OrderKey>,
+    by_order_id: HashMap<CompactString, OrderKey>,
+    /// Prefix of the generated client ids, should be unique per process to avoid collisions after restarts
+    client_id_prefix: CompactString,
+    last_client_id: u64,
+}
+
+impl OrderTracker {
+    pub fn new(exchange: Exchange, client_id_prefix: impl Into<CompactString>) -> Self {
+        Self {
+            exchange,
+            orders: SlotMap::with_key(),
+            by_client_id: HashMap::new(),
+            by_order_id: HashMap::new(),
+            client_id_prefix: client_id_prefix.into(),
+            last_client_id: 0,
+        }
+    }
+
+    /// Starts tracking the order in the `New` state.
+    /// Returns the request with the client id, generated if missing, which should be sent to the exchange.
+    pub fn on_new(&mut self, mut request: OrderRequest) -> OrderRequest {
+        let client_order_id = request.client_order_id.get_or_insert_with(|| {
+            self.last_client_id += 1;
+            format_compact!("{}{}", self.client_id_prefix, self.last_client_id)
+        }).clone();
+        let key = self.orders.insert(TrackedOrder::from_request(self.exchange, &request));
+        self.by_client_id.insert(client_order_id, key);
+        request
+    }
+
+    /// The order was sent to the exchange
+    pub fn on_sent(&mut self, client_order_id: &str) {
+        if let Some(order) = self.get_mut(&OrderRef::ClientOrderId(client_order_id.into())) {
+            if order.state == OrderState::New {
+                order.state = OrderState::PendingNew;
+            }
+        }
+    }
+
+    /// The exchange accepted the order, which may have been reported by an update already
+    pub fn on_ack(&mut self, client_order_id: &str, ack: &OrderAck) {
+        let Some(&key) = self.by_client_id.get(client_order_id) else {
+            warn!("acknowledgement of unknown order {ack:?}");
+            return;
+        };
+        let order = &mut self.orders[key];
+        if order.state.rank() < OrderState::Live.rank() {
+            order.state = OrderState::Live;
+        }
+        order.order_id = Some(ack.order_id.clone());
+        self.by_order_id.insert(ack.order_id.clone(), key);
+    }
+
+    /// The exchange rejected the order. Requests with unknown outcome, e.g. timed out ones,
+    /// should stay pending to be resolved by the updates or by `on_open_orders`.
+    pub fn on_rejected(&mut self, client_order_id: &str, reason: impl Into<CompactString>) {
+        if let Some(order) = self.get_mut(&OrderRef::ClientOrderId(client_order_id.into())) {
+            if order.state.rank() < OrderState::Live.rank() {
+                order.state = OrderState::Rejected;
+                order.reject_reason = Some(reason.into());
+            }
+        }
+    }
+
+    /// Applies the update unless a later state of the order is known already.
+    /// Fills are recorded even from stale updates.
+    pub fn on_update(&mut self, update: &OrderUpdate) {
+        let key = update.client_order_id
+            .as_ref()
+            .and_then(|id| self.by_client_id.get(id))
+            .or_else(|| self.by_order_id.get(&update.order_id))
+            .copied();
+        let key = match key {
+            Some(key) => {
+                self.orders[key].apply(update);
+                key
+            }
+            None => self.orders.insert(TrackedOrder::from_update(update)),
+        };
+        self.by_order_id.insert(update.order_id.clone(), key);
+        if let Some(client_order_id) = &update.client_order_id {
+            self.by_client_id.insert(client_order_id.clone(), key);
+        }
+    }
+
+    /// Applies the snapshot of all the open orders of the exchange, e.g. queried after a reconnect.
+    /// Returns the orders which are open locally, but are missing from the snapshot,
+    /// their final state should be looked up in the order history.
+    pub fn on_open_orders(&mut self, open_orders: &[OrderUpdate]) -> Vec<TrackedOrder> {
+        let mut present = HashSet::new();
+        for update in open_orders {
+            self.on_update(update);
+            present.insert(update.order_id.clone());
+        }
+        self.orders
+            .values()
+            .filter(|o| o.state.is_open() && o.state != OrderState::New)
+            .filter(|o| o.order_id.as_ref().is_none_or(|id| !present.contains(id)))
+            .cloned()
+            .collect()
+    }
+
+    pub fn get(&self, order: &OrderRef) -> Option<&TrackedOrder> {
+        let key = match order {
+            OrderRef::OrderId(id) => self.by_order_id.get(id),
+            OrderRef::ClientOrderId(id) => self.by_client_id.get(id),
+        };
+        key.and_then(|key| self.orders.get(*key))
+    }
+
+    fn get_mut(&mut self, order: &OrderRef) -> Option<&mut TrackedOrder> {
+        let key = match order {
+            OrderRef::OrderId(id) => self.by_order_id.get(id),
+            OrderRef::ClientOrderId(id) => self.by_client_id.get(id),
+        };
+        key.and_then(|key| self.orders.get_mut(*key))
+    }
+
+    /// Orders which are not in a final state, including the ones not acknowledged yet
+    pub fn open_orders(&self) -> impl Iterator<Item = &TrackedOrder> {
+        self.orders.values().filter(|o| o.state.is_open())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &TrackedOrder> {
+        self.orders.values()
+    }
+
+    /// Stops tracking the orders in a final state
+    pub fn remove_final(&mut self) {
+        self.orders.retain(|_, o| o.state.is_open());
+        let orders = &self.orders;
+        self.by_client_id.retain(|_, key| orders.contains_key(*key));
+        self.by_order_id.retain(|_, key| orders.contains_key(*key));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use compact_str::CompactString;
+
+    use crate::model::exchange::Exchange;
+    use crate::model::internal::Side;
+    use crate::model::order::{Fill, OrderAck, OrderRef, OrderRequest, OrderStatus, OrderType, OrderUpdate, TimeInForce};
+    use crate::model::order_tracker::{OrderState, OrderTracker};
+    use crate::model::symbol::Symbol;
+    use crate::utils::basic_types::{Amount, Price};
+
+    fn request() -> OrderRequest {
+        OrderRequest::limit(
+            Symbol::spot("BTC", "USDT"),
+            Side::Bid,
+            Price::from_str("30000").unwrap(),
+            Amount::from_str("2").unwrap(),
+            TimeInForce::Gtc,
+        )
+    }
+
+    fn update(client_order_id: &str, status: OrderStatus, filled: &str, time: u64) -> OrderUpdate {
+        let filled_amount = Amount::from_str(filled).unwrap();
+        let fill = (status != OrderStatus::New && status != OrderStatus::Canceled).then(|| Fill {
+            trade_id: format!("t{filled}").into(),
+            price: Price::from_str("30000").unwrap(),
+            amount: Amount::from_str("1").unwrap(),
+            fee: Amount::from_str("0.01").unwrap(),
+            fee_currency: "USDT".into(),
+        });
+        OrderUpdate {
+            exchange_time: Some(time),
+            exchange: Exchange::Okex,
+            symbol: Symbol::spot("BTC", "USDT"),
+            instrument_id: None,
+            order_id: "100".into(),
+            client_order_id: Some(client_order_id.into()),
+            side: Side::Bid,
+            order_type: OrderType::Limit,
+            time_in_force: TimeInForce::Gtc,
+            price: Some(Price::from_str("30000").unwrap()),
+            amount: Amount::from_str("2").unwrap(),
+            filled_amount,
+            avg_fill_price: None,
+            status,
+            fill,
+        }
+    }
+
+    #[test]
+    fn ack_and_updates_are_merged_in_any_order() {
+        let mut tracker = OrderTracker::new(Exchange::Okex, "t");
+        let request = tracker.on_new(request());
+        let id = request.client_order_id.clone().unwrap();
+        assert_eq!(id, "t1");
+        tracker.on_sent(&id);
+        assert_eq!(tracker.get(&OrderRef::ClientOrderId(id.clone())).unwrap().state, OrderState::PendingNew);
+
+        // the fills are pushed before the acknowledgement and the first one is delivered last
+        tracker.on_update(&update(&id, OrderStatus::Filled, "2", 3));
+        tracker.on_ack(&id, &OrderAck { order_id: "100".into(), client_order_id: Some(id.clone()) });
+        tracker.on_update(&update(&id, OrderStatus::PartiallyFilled, "1", 2));
+        tracker.on_update(&update(&id, OrderStatus::New, "0", 1));
+
+        let order = tracker.get(&OrderRef::OrderId("100".into())).unwrap();
+        assert_eq!(order.state, OrderState::Filled);
+        assert_eq!(order.filled_amount, Amount::from_str("2").unwrap());
+        assert_eq!(order.fills.len(), 2);
+        assert_eq!(tracker.open_orders().count(), 0);
+
+        tracker.remove_final();
+        assert!(tracker.get(&OrderRef::ClientOrderId(id)).is_none());
+    }
+
+    #[test]
+    fn rejected_and_unknown_orders() {
+        let mut tracker = OrderTracker::new(Exchange::Okex, "t");
+        let rejected = tracker.on_new(request()).client_order_id.unwrap();
+        tracker.on_sent(&rejected);
+        tracker.on_rejected(&rejected, "51008:Insufficient balance");
+        let order = tracker.get(&OrderRef::ClientOrderId(rejected)).unwrap();
+        assert_eq!(order.state, OrderState::Rejected);
+
+        // placed outside of the tracker
+        tracker.on_update(&update("web1", OrderStatus::New, "0", 1));
+        assert_eq!(tracker.get(&OrderRef::OrderId("100".into())).unwrap().state, OrderState::Live);
+    }
+
+    #[test]
+    fn open_orders_snapshot_reports_missing_orders() {
+        let mut tracker = OrderTracker::new(Exchange::Okex, "t");
+        let live = tracker.on_new(request().with_client_order_id("live")).client_order_id.unwrap();
+        tracker.on_sent(&live);
+        tracker.on_ack(&live, &OrderAck { order_id: "100".into(), client_order_id: Some(live.clone()) });
+        let lost = tracker.on_new(request()).client_order_id.unwrap();
+        tracker.on_sent(&lost);
+        let unsent = tracker.on_new(request()).client_order_id.unwrap();
+
+        let missing = tracker.on_open_orders(&[update(&live, OrderStatus::PartiallyFilled, "1", 5)]);
+        let missing: Vec<CompactString> = missing.into_iter().filter_map(|o| o.client_order_id).collect();
+        assert_eq!(missing, vec![lost]);
+        assert_eq!(tracker.get(&OrderRef::ClientOrderId(live)).unwrap().state, OrderState::PartiallyFilled);
+        assert_eq!(tracker.get(&OrderRef::ClientOrderId(unsent)).unwrap().state, OrderState::New);
+    }
+}
@@ -1,26 +1,45 @@
 use std::collections::hash_map::Entry;
 use std::collections::HashMap;
+use std::sync::Arc;
 
-use compact_str::CompactString;
+use log::warn;
 
-use crate::model::internal::MdMessage;
+use crate::model::exchange::Exchange;
+use crate::model::instrument::InstrumentRegistry;
+use crate::model::internal::{MdInstrument, MdMessage};
 use crate::model::order_book::OrderBook;
+use crate::model::symbol::Symbol;
+use crate::utils::basic_types::Price;
 
+/// Order books of all the exchanges, the same symbol is kept separately per exchange
+#[derive(Default)]
 pub struct Storage {
-    order_books: HashMap<CompactString, OrderBook>
+    order_books: HashMap<(Exchange, Symbol), OrderBook>,
+    /// Resolves the symbols of the messages carrying an `InstrumentId`
+    registry: Option<Arc<InstrumentRegistry>>,
 }
 
 impl Storage {
     pub fn new() -> Self {
         Self {
             order_books: HashMap::new(),
+            registry: None,
         }
     }
 
+    /// Required to process the messages of the connections resolving the instrument ids
+    pub fn with_registry(mut self, registry: Arc<InstrumentRegistry>) -> Self {
+        self.registry = Some(registry);
+        self
+    }
+
     pub fn on_ws_update(&mut self, message: MdMessage) {
         match message {
             MdMessage::L2Snapshot(snapshot) => {
-                match self.order_books.entry(snapshot.symbol.clone()) {
+                let Some(key) = self.book_key(snapshot.exchange, &snapshot.instrument) else {
+                    return;
+                };
+                match self.order_books.entry(key) {
                     Entry::Occupied(mut o) => {
                         o.get_mut().process_snapshot(snapshot);
                     }
@@ -32,7 +51,10 @@ impl Storage {
                 }
             }
             MdMessage::L2Increment(increment) => {
-                match self.order_books.entry(increment.symbol.clone()) {
+                let Some(key) = self.book_key(increment.exchange, &increment.instrument) else {
+                    return;
+                };
+                match self.order_books.entry(key) {
                     Entry::Occupied(mut o) => {
                         o.get_mut().process_update(increment);
                     }
@@ -43,11 +65,42 @@ impl Storage {
                     }
                 }
             }
+            MdMessage::BookInvalid(invalid) => {
+                if let Some(key) = self.book_key(invalid.exchange, &invalid.instrument) {
+                    self.order_books.remove(&key);
+                }
+            }
+            MdMessage::Trade(_)
+            | MdMessage::Ticker(_)
+            | MdMessage::FundingRate(_)
+            | MdMessage::MarkPrice(_)
+            | MdMessage::IndexPrice(_)
+            | MdMessage::OpenInterest(_)
+            | MdMessage::Candle(_) => {}
         }
     }
 
-    pub fn on_order_book(&mut self, symbol: CompactString, order_book: OrderBook) {
-        match self.order_books.entry(symbol) {
+    fn book_key(&self, exchange: Exchange, instrument: &MdInstrument) -> Option<(Exchange, Symbol)> {
+        let symbol = match instrument {
+            MdInstrument::Symbol(symbol) => Some(symbol),
+            MdInstrument::Id(id) => self.registry.as_ref().and_then(|r| r.get(*id)).map(|i| &i.internal_symbol),
+        };
+        if symbol.is_none() {
+            warn!("{exchange:?} order book of unknown instrument {instrument:?} is skipped");
+        }
+        symbol.map(|symbol| (exchange, symbol.clone()))
+    }
+
+    pub fn order_book(&self, exchange: Exchange, symbol: &Symbol) -> Option<&OrderBook> {
+        self.order_books.get(&(exchange, symbol.clone()))
+    }
+
+    pub fn mid_price(&self, exchange: Exchange, symbol: &Symbol) -> Option<Price> {
+        self.order_book(exchange, symbol).and_then(OrderBook::mid_price)
+    }
+
+    pub fn on_order_book(&mut self, exchange: Exchange, symbol: Symbol, order_book: OrderBook) {
+        match self.order_books.entry((exchange, symbol)) {
             Entry::Occupied(mut o) => {
                 o.get_mut().update_on_order_book(order_book);
             }
@@ -58,4 +111,75 @@ impl Storage {
             }
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::str::FromStr;
+    use std::sync::Arc;
+
+    use crate::gates::coinbase::md::model::CoinbaseWsMessage;
+    use crate::model::exchange::Exchange;
+    use crate::model::instrument::{Instrument, InstrumentKind, InstrumentRegistry, InstrumentState};
+    use crate::model::internal::{L2Snapshot, MdInstrument, MdMessage, SingleLot};
+    use crate::model::storage::Storage;
+    use crate::model::symbol::Symbol;
+    use crate::utils::basic_types::{Amount, Price};
+
+    #[test]
+    fn books_are_kept_per_exchange() {
+        let symbol = Symbol::spot("BTC", "USD");
+        let snapshot_str = fs::read_to_string("tests/coinbase_ws_snapshot.json").unwrap();
+        let CoinbaseWsMessage::Snapshot(snapshot) = serde_json::from_str(&snapshot_str).unwrap() else {
+            panic!("expected snapshot");
+        };
+        let coinbase = snapshot.to_internal(symbol.clone());
+        let okex = L2Snapshot { exchange: Exchange::Okex, asks: Vec::new(), ..coinbase.clone() };
+
+        let mut storage = Storage::new();
+        storage.on_ws_update(MdMessage::L2Snapshot(coinbase));
+        storage.on_ws_update(MdMessage::L2Snapshot(okex));
+        assert_eq!(storage.order_book(Exchange::Coinbase, &symbol).unwrap().asks.len(), 2);
+        assert!(storage.order_book(Exchange::Okex, &symbol).unwrap().asks.is_empty());
+        assert!(storage.order_book(Exchange::Binance, &symbol).is_none());
+    }
+
+    #[test]
+    fn instrument_ids_are_resolved_with_registry() {
+        let symbol = Symbol::spot("BTC", "USDT");
+        let mut registry = InstrumentRegistry::new();
+        let id = registry.insert(Instrument {
+            exchange: Exchange::Okex,
+            symbol: "BTC-USDT".into(),
+            internal_symbol: symbol.clone(),
+            kind: InstrumentKind::Spot,
+            base_currency: "BTC".into(),
+            quote_currency: "USDT".into(),
+            settle_currency: None,
+            tick_size: Price::from_str("0.1").unwrap(),
+            lot_size: Amount::from_str("0.00000001").unwrap(),
+            min_size: Amount::from_str("0.00001").unwrap(),
+            contract_value: None,
+            expiry: None,
+            state: InstrumentState::Live,
+        });
+        let level = |price: &str| SingleLot { price: Price::from_str(price).unwrap(), amount: Amount::from_str("1").unwrap() };
+        let snapshot = MdMessage::L2Snapshot(L2Snapshot {
+            exchange_time: None,
+            sequence_no: None,
+            exchange: Exchange::Okex,
+            instrument: MdInstrument::Id(id),
+            bids: vec![level("29990")],
+            asks: vec![level("30010")],
+        });
+
+        let mut storage = Storage::new();
+        storage.on_ws_update(snapshot.clone());
+        assert!(storage.order_book(Exchange::Okex, &symbol).is_none());
+
+        let mut storage = Storage::new().with_registry(Arc::new(registry));
+        storage.on_ws_update(snapshot);
+        assert_eq!(storage.mid_price(Exchange::Okex, &symbol), Some(Price::from_str("30000").unwrap()));
+    }
+}
@@ -5,8 +5,21 @@ use serde::Serialize;
 pub trait WsStream {
     type Kind: Eq + Hash;
     type Subscribe: Serialize + Send + 'static + Clone;
+    type Login: Serialize + Send + 'static + Clone;
 
     fn kind(&self) -> Self::Kind;
 
     fn subscribe_requests(&self) -> Vec<Self::Subscribe>;
+
+    /// Requests authenticating the connection, sent and acknowledged before the subscriptions
+    fn login_requests(&self) -> Vec<Self::Login> {
+        Vec::new()
+    }
+
+    /// Sets the id the exchange echoes in the response to the subscribe request.
+    /// `WebSocket::subscribe` waits for the responses of such requests and fails on an error response.
+    /// Returns `false` for fire-and-forget requests, which is the default.
+    fn set_request_id(_request: &mut Self::Subscribe, _id: u64) -> bool {
+        false
+    }
 }
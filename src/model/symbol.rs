@@ -0,0 +1,264 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use compact_str::{CompactString, ToCompactString};
+use eyre::{eyre, OptionExt};
+use serde::{Deserialize, Serialize};
+
+use crate::utils::basic_types::Price;
+
+/// Exchange independent identifier of an instrument.
+///
+/// Formatted as `BASE/QUOTE` for spot, `BASE/QUOTE-PERP` for perpetual swaps,
+/// `BASE/QUOTE-YYYYMMDD` for futures, `BASE/QUOTE-YYYYMMDD-STRIKE-C|P` for options
+/// and `BASE/QUOTE-INDEX` for price indices,
+/// e.g. `BTC/USDT`, `BTC/USD-PERP`, `BTC/USD-20231222`, `BTC/USD-20231222-30000-C`, `BTC/USD-INDEX`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "CompactString", into = "CompactString")]
+pub struct Symbol {
+    pub base: CompactString,
+    pub quote: CompactString,
+    pub kind: SymbolKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum SymbolKind {
+    Spot,
+    Perpetual,
+    Future { expiry: Expiry },
+    Option { expiry: Expiry, strike: Price, option_type: OptionType },
+    /// Price index of the pair, not tradable
+    Index,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Expiry {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum OptionType {
+    Call,
+    Put,
+}
+
+/// Bidirectional mapping between `Symbol` and the symbols native to an exchange
+pub trait SymbolMapping {
+    /// Fails if the exchange has no instruments of the symbol kind
+    fn to_native(symbol: &Symbol) -> eyre::Result<CompactString>;
+
+    fn from_native(native: &str) -> eyre::Result<Symbol>;
+}
+
+impl Symbol {
+    pub fn spot(base: impl Into<CompactString>, quote: impl Into<CompactString>) -> Self {
+        Self { base: base.into(), quote: quote.into(), kind: SymbolKind::Spot }
+    }
+
+    pub fn perpetual(base: impl Into<CompactString>, quote: impl Into<CompactString>) -> Self {
+        Self { base: base.into(), quote: quote.into(), kind: SymbolKind::Perpetual }
+    }
+
+    pub fn future(base: impl Into<CompactString>, quote: impl Into<CompactString>, expiry: Expiry) -> Self {
+        Self { base: base.into(), quote: quote.into(), kind: SymbolKind::Future { expiry } }
+    }
+
+    pub fn option(
+        base: impl Into<CompactString>,
+        quote: impl Into<CompactString>,
+        expiry: Expiry,
+        strike: Price,
+        option_type: OptionType,
+    ) -> Self {
+        Self { base: base.into(), quote: quote.into(), kind: SymbolKind::Option { expiry, strike, option_type } }
+    }
+
+    pub fn index(base: impl Into<CompactString>, quote: impl Into<CompactString>) -> Self {
+        Self { base: base.into(), quote: quote.into(), kind: SymbolKind::Index }
+    }
+
+    pub fn expiry(&self) -> Option<Expiry> {
+        match self.kind {
+            SymbolKind::Spot | SymbolKind::Perpetual | SymbolKind::Index => None,
+            SymbolKind::Future { expiry } | SymbolKind::Option { expiry, .. } => Some(expiry),
+        }
+    }
+}
+
+impl Expiry {
+    pub fn new(year: u16, month: u8, day: u8) -> eyre::Result<Self> {
+        if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+            return Err(eyre!("invalid expiry date {year}-{month}-{day}"));
+        }
+        Ok(Self { year, month, day })
+    }
+
+    /// Parses `YYYYMMDD`
+    pub fn from_yyyymmdd(s: &str) -> eyre::Result<Self> {
+        if s.len() != 8 || !s.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(eyre!("expiry {s} is not in YYYYMMDD format"));
+        }
+        Self::new(s[0..4].parse()?, s[4..6].parse()?, s[6..8].parse()?)
+    }
+
+    /// Parses `YYMMDD`, the years are counted from 2000
+    pub fn from_yymmdd(s: &str) -> eyre::Result<Self> {
+        if s.len() != 6 || !s.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(eyre!("expiry {s} is not in YYMMDD format"));
+        }
+        Self::new(2000 + s[0..2].parse::<u16>()?, s[2..4].parse()?, s[4..6].parse()?)
+    }
+
+    pub fn to_yymmdd(&self) -> CompactString {
+        compact_str::format_compact!("{:02}{:02}{:02}", self.year % 100, self.month, self.day)
+    }
+
+    /// Parses `DMMMYY` or `DDMMMYY`, e.g. `5JAN24` or `29DEC23`, the years are counted from 2000
+    pub fn from_dmmmyy(s: &str) -> eyre::Result<Self> {
+        let day_len = s.len()
+            .checked_sub(5)
+            .filter(|l| (1..=2).contains(l) && s.is_ascii())
+            .ok_or_else(|| eyre!("expiry {s} is not in DDMMMYY format"))?;
+        let (day, rest) = s.split_at(day_len);
+        let (month, year) = rest.split_at(3);
+        let month = MONTHS
+            .iter()
+            .position(|m| *m == month)
+            .ok_or_else(|| eyre!("unknown month {month} in expiry {s}"))?;
+        Self::new(2000 + year.parse::<u16>()?, month as u8 + 1, day.parse()?)
+    }
+
+    /// Formats as `DMMMYY` without the leading zero of the day, e.g. `5JAN24`
+    pub fn to_dmmmyy(&self) -> CompactString {
+        let month = MONTHS[usize::from(self.month - 1)];
+        compact_str::format_compact!("{}{month}{:02}", self.day, self.year % 100)
+    }
+}
+
+const MONTHS: [&str; 12] = ["JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC"];
+
+impl Display for Expiry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:04}{:02}{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl OptionType {
+    pub fn letter(&self) -> char {
+        match self {
+            OptionType::Call => 'C',
+            OptionType::Put => 'P',
+        }
+    }
+
+    pub fn from_letter(letter: &str) -> eyre::Result<Self> {
+        match letter {
+            "C" => Ok(OptionType::Call),
+            "P" => Ok(OptionType::Put),
+            _ => Err(eyre!("unknown option type {letter}")),
+        }
+    }
+}
+
+/// Formats the strike without the trailing fractional zeros, e.g. `30000` instead of `30000.0`
+pub fn format_strike(strike: Price) -> CompactString {
+    let s = strike.to_compact_string();
+    match s.split_once('.') {
+        Some((int, frac)) if frac.bytes().all(|b| b == b'0') => int.into(),
+        _ => s,
+    }
+}
+
+impl Display for Symbol {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.base, self.quote)?;
+        match &self.kind {
+            SymbolKind::Spot => Ok(()),
+            SymbolKind::Perpetual => write!(f, "-PERP"),
+            SymbolKind::Index => write!(f, "-INDEX"),
+            SymbolKind::Future { expiry } => write!(f, "-{expiry}"),
+            SymbolKind::Option { expiry, strike, option_type } => {
+                write!(f, "-{expiry}-{}-{}", format_strike(*strike), option_type.letter())
+            }
+        }
+    }
+}
+
+impl FromStr for Symbol {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (base, rest) = s.split_once('/').ok_or_eyre(format!("symbol {s} has no '/'"))?;
+        let mut parts = rest.split('-');
+        let quote = parts.next().unwrap_or_default();
+        if base.is_empty() || quote.is_empty() {
+            return Err(eyre!("symbol {s} has empty base or quote currency"));
+        }
+        let kind = match (parts.next(), parts.next(), parts.next(), parts.next()) {
+            (None, ..) => SymbolKind::Spot,
+            (Some("PERP"), None, ..) => SymbolKind::Perpetual,
+            (Some("INDEX"), None, ..) => SymbolKind::Index,
+            (Some(expiry), None, ..) => SymbolKind::Future { expiry: Expiry::from_yyyymmdd(expiry)? },
+            (Some(expiry), Some(strike), Some(option_type), None) => SymbolKind::Option {
+                expiry: Expiry::from_yyyymmdd(expiry)?,
+                strike: Price::from_str(strike).map_err(|_| eyre!("non-decimal strike {strike}"))?,
+                option_type: OptionType::from_letter(option_type)?,
+            },
+            _ => return Err(eyre!("unknown symbol format {s}")),
+        };
+
+        Ok(Self { base: base.into(), quote: quote.into(), kind })
+    }
+}
+
+impl TryFrom<CompactString> for Symbol {
+    type Error = eyre::Report;
+
+    fn try_from(s: CompactString) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<Symbol> for CompactString {
+    fn from(symbol: Symbol) -> Self {
+        symbol.to_compact_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::model::symbol::{Expiry, OptionType, Symbol};
+    use crate::utils::basic_types::Price;
+
+    #[test]
+    fn symbol_formatting_round_trip() {
+        let expiry = Expiry::new(2023, 12, 22).unwrap();
+        let symbols = [
+            (Symbol::spot("BTC", "USDT"), "BTC/USDT"),
+            (Symbol::perpetual("BTC", "USD"), "BTC/USD-PERP"),
+            (Symbol::index("BTC", "USD"), "BTC/USD-INDEX"),
+            (Symbol::future("BTC", "USD", expiry), "BTC/USD-20231222"),
+            (Symbol::option("BTC", "USD", expiry, Price::from_str("30000").unwrap(), OptionType::Call), "BTC/USD-20231222-30000-C"),
+            (Symbol::option("ETH", "USD", expiry, Price::from_str("2.5").unwrap(), OptionType::Put), "ETH/USD-20231222-2.5-P"),
+        ];
+        for (symbol, formatted) in symbols {
+            assert_eq!(symbol.to_string(), formatted);
+            assert_eq!(Symbol::from_str(formatted).unwrap(), symbol);
+            let deserialized: Symbol = serde_json::from_str(&format!("\"{formatted}\"")).unwrap();
+            assert_eq!(deserialized, symbol);
+        }
+
+        assert_eq!(Expiry::from_dmmmyy("22DEC23").unwrap(), expiry);
+        assert_eq!(Expiry::from_dmmmyy("5JAN24").unwrap().to_dmmmyy(), "5JAN24");
+        assert_eq!(Expiry::from_dmmmyy("05JAN24").unwrap().to_dmmmyy(), "5JAN24");
+        assert!(Expiry::from_dmmmyy("5JUX24").is_err());
+
+        assert!(Symbol::from_str("BTC-USDT").is_err());
+        assert!(Symbol::from_str("BTC/USD-20231322").is_err());
+        assert!(Symbol::from_str("BTC/USD-20231222-30000-X").is_err());
+    }
+}
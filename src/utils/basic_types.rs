@@ -3,21 +3,44 @@ use std::str::FromStr;
 use compact_str::CompactString;
 use fixnum::FixedPoint;
 use fixnum::typenum::U16;
-use serde::{Deserialize, Deserializer, Serialize};
+use serde::{Deserialize, Deserializer};
+use slotmap::{KeyData, new_key_type};
 use serde::de::Error;
 
 pub type Amount = FixedPoint<i128, U16>;
 pub type Price = FixedPoint<i128, U16>;
 
-#[derive(Copy, Clone, Debug, Serialize, Deserialize, Eq, PartialEq, Hash)]
-pub struct InstrumentId(u64);
+new_key_type! {
+    /// Compact identifier of an instrument in `InstrumentRegistry`
+    pub struct InstrumentId;
+}
+
+impl From<u64> for InstrumentId {
+    fn from(id: u64) -> Self {
+        KeyData::from_ffi(id).into()
+    }
+}
 
-impl Into<InstrumentId> for u64 {
-    fn into(self) -> InstrumentId {
-        InstrumentId(self)
+impl InstrumentId {
+    pub fn as_u64(&self) -> u64 {
+        self.0.as_ffi()
     }
 }
 
+/// Okex and some other exchanges send an empty string instead of a missing decimal value
+pub fn deserialize_optional_decimal<'de, D>(deserializer: D) -> Result<Option<Price>, D::Error>
+    where
+        D: Deserializer<'de>,
+{
+    let s = CompactString::deserialize(deserializer)?;
+    if s.is_empty() {
+        return Ok(None);
+    }
+    Price::from_str(s.as_str())
+        .map(Some)
+        .map_err(|_| D::Error::custom(format!("non-decimal {s}")))
+}
+
 pub fn deserialize_u64<'de, D>(deserializer: D) -> Result<u64, D::Error>
     where
         D: Deserializer<'de>,
@@ -25,3 +48,127 @@ pub fn deserialize_u64<'de, D>(deserializer: D) -> Result<u64, D::Error>
     let s = CompactString::deserialize(deserializer)?;
     u64::from_str(s.as_str()).map_err(|_| D::Error::custom(format!("non-integer {s}")))
 }
+
+/// Empty string is deserialized as `None`
+pub fn deserialize_optional_u64<'de, D>(deserializer: D) -> Result<Option<u64>, D::Error>
+    where
+        D: Deserializer<'de>,
+{
+    let s = CompactString::deserialize(deserializer)?;
+    if s.is_empty() {
+        return Ok(None);
+    }
+    u64::from_str(s.as_str())
+        .map(Some)
+        .map_err(|_| D::Error::custom(format!("non-integer {s}")))
+}
+
+/// Deserializes a JSON number as the shortest decimal which parses back to the same `f64`,
+/// which is the form Kraken and Deribit send them in. Converting the `f64` directly keeps its binary noise.
+pub fn deserialize_float_decimal<'de, D>(deserializer: D) -> Result<Price, D::Error>
+    where
+        D: Deserializer<'de>,
+{
+    let f = f64::deserialize(deserializer)?;
+    Price::from_str(&f.to_string()).map_err(|_| D::Error::custom(format!("non-decimal {f}")))
+}
+
+/// Deserializes UTC time in RFC 3339 format, e.g. `2019-08-14T20:42:27.265Z`, as Unix timestamp in milliseconds
+pub fn deserialize_rfc3339_millis<'de, D>(deserializer: D) -> Result<u64, D::Error>
+    where
+        D: Deserializer<'de>,
+{
+    let s = CompactString::deserialize(deserializer)?;
+    parse_rfc3339_millis(&s).ok_or_else(|| D::Error::custom(format!("non-RFC 3339 UTC time {s}")))
+}
+
+/// Parses UTC time in RFC 3339 format as Unix timestamp in milliseconds, the fractional seconds are optional
+pub fn parse_rfc3339_millis(s: &str) -> Option<u64> {
+    let s = s.strip_suffix('Z')?;
+    let (date, time) = s.split_once('T')?;
+    let mut date = date.splitn(3, '-').map(u64::from_str);
+    let (year, month, day) = (date.next()?.ok()?, date.next()?.ok()?, date.next()?.ok()?);
+    let (time, fraction) = time.split_once('.').unwrap_or((time, "0"));
+    let mut time = time.splitn(3, ':').map(u64::from_str);
+    let (hour, minute, second) = (time.next()?.ok()?, time.next()?.ok()?, time.next()?.ok()?);
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) || hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+    if !fraction.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let millis: u64 = format!("{fraction:0<3}").get(..3)?.parse().ok()?;
+    let days = days_from_civil(year, month, day)?;
+
+    Some(((days * 24 + hour) * 60 + minute) * 60_000 + second * 1000 + millis)
+}
+
+/// Formats Unix timestamp in milliseconds as UTC time in RFC 3339 format, e.g. `2020-12-08T09:08:57.715Z`
+pub fn format_rfc3339_millis(millis: u64) -> CompactString {
+    let secs = millis / 1000;
+    let (days, day_secs) = (secs / 86400, secs % 86400);
+    let (year, month, day) = civil_from_days(days);
+    compact_str::format_compact!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.{:03}Z",
+        day_secs / 3600,
+        day_secs % 3600 / 60,
+        day_secs % 60,
+        millis % 1000,
+    )
+}
+
+// Conversions between the days since the epoch and the dates of the proleptic Gregorian calendar,
+// see http://howardhinnant.github.io/date_algorithms.html
+
+/// `None` for the dates before the epoch
+fn days_from_civil(year: u64, month: u64, day: u64) -> Option<u64> {
+    let (y, m) = if month <= 2 { (year.checked_sub(1)?, month + 9) } else { (year, month - 3) };
+    let era = y / 400;
+    let day_of_era = (y % 400) * 365 + (y % 400) / 4 - (y % 400) / 100 + (153 * m + 2) / 5 + day - 1;
+    (era * 146097 + day_of_era).checked_sub(719468)
+}
+
+fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    let z = days + 719468;
+    let era = z / 146097;
+    let day_of_era = z % 146097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    (year_of_era + era * 400 + u64::from(month <= 2), month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::basic_types::{format_rfc3339_millis, parse_rfc3339_millis};
+
+    #[test]
+    fn rfc3339_parsing() {
+        assert_eq!(parse_rfc3339_millis("1970-01-01T00:00:00Z"), Some(0));
+        assert_eq!(parse_rfc3339_millis("2019-08-14T20:42:27.265Z"), Some(1565815347265));
+        assert_eq!(parse_rfc3339_millis("2024-02-29T23:59:59.123456Z"), Some(1709251199123));
+        assert_eq!(parse_rfc3339_millis("2019-08-14T20:42:27.265"), None);
+        assert_eq!(parse_rfc3339_millis("2019-13-14T20:42:27Z"), None);
+    }
+
+    #[test]
+    fn rfc3339_parsing_rejects_malformed_input() {
+        assert_eq!(parse_rfc3339_millis("2019-08-14T20:42:27.éZ"), None);
+        assert_eq!(parse_rfc3339_millis("2019-08-14T20:42:27.1éZ"), None);
+        assert_eq!(parse_rfc3339_millis("2019-08-14T20:42:27.12xZ"), None);
+        assert_eq!(parse_rfc3339_millis("0000-01-01T00:00:00Z"), None);
+        assert_eq!(parse_rfc3339_millis("0000-03-01T00:00:00Z"), None);
+    }
+
+    #[test]
+    fn rfc3339_formatting() {
+        assert_eq!(format_rfc3339_millis(1538054050975), "2018-09-27T13:14:10.975Z");
+        assert_eq!(format_rfc3339_millis(951782400000), "2000-02-29T00:00:00.000Z");
+        assert_eq!(format_rfc3339_millis(0), "1970-01-01T00:00:00.000Z");
+        for millis in [0, 951782400000, 1538054050975, 1709251199123, 4102444800001] {
+            assert_eq!(parse_rfc3339_millis(&format_rfc3339_millis(millis)), Some(millis));
+        }
+    }
+}
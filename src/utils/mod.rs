@@ -1 +1 @@
-pub mod basic_types;
\ No newline at end of file
+pub mod basic_types;